@@ -0,0 +1,56 @@
+use super::{visitor, Expr};
+
+/// Number of expressions in `expr` including itself
+/// (e.g. 5 for `1 + 2 * 3`)
+pub fn node_count(expr: &Expr) -> usize {
+    let mut metrics = Metrics::default();
+    visitor::go_expr(&mut metrics, expr);
+    metrics.count
+}
+
+/// Maximum nesting of expressions in `expr`
+/// (e.g. 3 for `1 + 2 * 3`)
+pub fn depth(expr: &Expr) -> usize {
+    let mut metrics = Metrics::default();
+    visitor::go_expr(&mut metrics, expr);
+    metrics.max_depth
+}
+
+#[derive(Default)]
+struct Metrics {
+    count: usize,
+    /// Number of expressions enclosing the current one
+    depth: usize,
+    max_depth: usize,
+}
+
+impl visitor::Visitor<'_> for Metrics {
+    fn visit_expr(&mut self, _expr: &Expr) {
+        self.count += 1;
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+
+    fn visit_expr_post(&mut self, _expr: &Expr) {
+        self.depth -= 1;
+    }
+}
+
+#[test]
+fn test_metrics() {
+    use crate::parse::Parser;
+
+    let metrics = |src: &str| {
+        let expr = Parser::from_source(src).parse_expr().unwrap();
+        (node_count(&expr), depth(&expr))
+    };
+    assert_eq!(metrics("1 + 2 * 3"), (5, 3));
+    assert_eq!(metrics("a"), (1, 1));
+    assert_eq!(metrics("f(a, b.c)"), (5, 3));
+    // statements and blocks are not counted, but expressions in them are
+    assert_eq!(metrics("{ let x = 1; x }"), (3, 2));
+    // the branch of `if` is a block expression
+    assert_eq!(metrics("if a { loop { break 1; } }"), (6, 5));
+    // including literals in patterns
+    assert_eq!(metrics("match x { 0 => y, _ => return }"), (5, 2));
+}
@@ -1,5 +1,11 @@
+pub mod metrics;
+pub mod pretty;
+pub mod rename;
+pub mod span;
+pub mod structural;
 pub mod visitor;
 
+use crate::lexer::Token;
 use crate::span::{Ident, Span};
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
@@ -25,99 +31,209 @@ pub struct Crate {
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Item {
     pub kind: ItemKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ItemKind {
     Func(Func),
     Struct(StructItem),
     ExternBlock(ExternBlock),
     Mod(Module),
+    Use(UseItem),
+    Static(StaticItem),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Module {
     pub name: Ident,
     pub items: Vec<Item>,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct UseItem {
+    pub path: Path,
+    /// `use a::b::*;`
+    pub is_glob: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StaticItem {
+    pub name: Ident,
+    /// `static mut`
+    pub mutable: bool,
+    pub ty: Ty,
+    pub value: Expr,
+}
+
+#[derive(Debug, Clone)]
 pub struct ExternBlock {
     pub funcs: Vec<Func>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StructItem {
     pub ident: Ident,
     pub fields: Vec<(Ident, Ty)>,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Func {
     pub name: Ident,
+    /// Type parameters
+    pub generics: Vec<Ident>,
     pub params: Vec<(Ident, Ty)>,
+    /// Unit if the return type is omitted
     pub ret_ty: Ty,
     /// Extern abi
     pub ext: Option<String>,
+    /// `const fn`
+    pub is_const: bool,
     pub body: Option<Block>,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stmt {
     pub kind: StmtKind,
     pub id: NodeId,
     pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StmtKind {
     /// Expression without trailing semicolon
     Expr(Box<Expr>),
     /// Expression with trailing semicolon
     Semi(Box<Expr>),
     Let(LetStmt),
+    /// Item declared in a block (e.g. `fn f() {}`)
+    Item(Box<Item>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LetStmt {
-    pub ident: Ident,
+    pub pat: Pattern,
+    /// `let mut`
+    pub mutable: bool,
     pub ty: Option<Ty>,
     pub init: Option<Expr>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expr {
     pub kind: ExprKind,
     pub id: NodeId,
     pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExprKind {
     Binary(BinOp, Box<Expr>, Box<Expr>),
     Unary(UnOp, Box<Expr>),
-    NumLit(u32),
+    /// value, type suffix (e.g. `i32` of `1i32`)
+    NumLit(u128, Option<String>),
+    /// value, type suffix (e.g. `f32` of `1.0f32`)
+    FloatLit(f64, Option<String>),
     BoolLit(bool),
     StrLit(String),
+    CharLit(char),
+    /// `b'a'`
+    ByteLit(u8),
+    /// `b"abc"`
+    ByteStrLit(Vec<u8>),
     Unit,
     Path(Path),
-    Assign(Box<Expr>, Box<Expr>),
-    Return(Box<Expr>),
-    Call(Box<Expr>, Vec<Expr>),
+    /// lhs, rhs, span of `=`
+    Assign(Box<Expr>, Box<Expr>, Span),
+    /// compound assignment (e.g. `a += 1`): op, lhs, rhs, span of `op=`
+    AssignOp(BinOp, Box<Expr>, Box<Expr>, Span),
+    /// value
+    Return(Option<Box<Expr>>),
+    /// callee, type arguments given by turbofish (e.g. `f::<i32>()`), arguments
+    Call(Box<Expr>, Vec<Ty>, Vec<Expr>),
     Block(Block),
     /// cond, then (only block expr), else
     If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
     Index(Box<Expr>, Box<Expr>),
     Field(Box<Expr>, Ident),
-    Struct(Path, Vec<(Ident, Box<Expr>)>),
+    /// path, fields, base of functional update syntax (i.e. `..base`)
+    Struct(Path, Vec<(Ident, Box<Expr>)>, Option<Box<Expr>>),
     Array(Vec<Expr>),
     Cast(Box<Expr>, Ty),
+    /// Parenthesized expression (only if `Parser::preserve_parens` is set)
+    Paren(Box<Expr>),
+    /// body, label
+    Loop(Block, Option<Ident>),
+    /// cond, body, label
+    While(Box<Expr>, Block, Option<Ident>),
+    /// body, label (e.g. `'a: { break 'a 1; }`)
+    LabeledBlock(Block, Ident),
+    /// pattern, iterator, body, label
+    For(Pattern, Box<Expr>, Block, Option<Ident>),
+    /// label, value
+    Break(Option<Ident>, Option<Box<Expr>>),
+    /// label
+    Continue(Option<Ident>),
+    /// start, end (exclusive)
+    Range(Option<Box<Expr>>, Option<Box<Expr>>),
+    /// name, delimiter, tokens between the delimiters (not parsed)
+    MacroCall(Ident, Delim, Vec<Token>),
+    /// `expr?`
+    Try(Box<Expr>),
+    /// `expr.await`
+    Await(Box<Expr>),
+    /// scrutinee, arms
+    Match(Box<Expr>, Vec<MatchArm>),
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pat: Pattern,
+    /// `if` guard (e.g. `n if n > 0 => ...`)
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    /// `_`
+    Wild,
+    /// Binding (e.g. `x`)
+    Ident(Ident),
+    /// Literal (e.g. `1`, `'a'`, `true`)
+    Lit(Box<Expr>),
+    /// Range of literals (e.g. `'a'..='z'`, `0..10`, `5..`, `..=9`)
+    /// start, end, whether `end` is inclusive
+    Range(Option<Box<Expr>>, Option<Box<Expr>>, bool),
+    /// `(a, (b, c))`
+    Tuple(Vec<Pattern>),
+    /// `[x, y]`
+    Array(Vec<Pattern>),
+    /// `..` in tuple or array patterns (e.g. `[first, .., last]`)
+    Rest,
+}
+
+/// Delimiter of macro invocations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Delim {
+    /// `()`
+    Paren,
+    /// `[]`
+    Bracket,
+    /// `{}`
+    Brace,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -141,14 +257,263 @@ impl std::fmt::Debug for Path {
     }
 }
 
-#[derive(Debug)]
+impl Expr {
+    /// Block-like expressions can be used as statements without trailing semicolon
+    /// ref: https://doc.rust-lang.org/reference/statements.html#expression-statements
+    pub fn is_block_like(&self) -> bool {
+        matches!(
+            self.kind,
+            ExprKind::Block(_)
+                | ExprKind::If(..)
+                | ExprKind::Loop(..)
+                | ExprKind::While(..)
+                | ExprKind::For(..)
+                | ExprKind::LabeledBlock(..)
+                | ExprKind::Match(..)
+        )
+    }
+
+    /// Clone the expression while allocating new node ids to it and all of its descendants,
+    /// so that the copy can be placed in the AST alongside the original (e.g. when desugaring).
+    /// `next_id` allocates a new node id (e.g. `Parser::get_next_id`).
+    pub fn deep_clone_with_fresh_ids(&self, next_id: &mut impl FnMut() -> NodeId) -> Expr {
+        let mut expr = self.clone();
+        expr.renew_ids(next_id);
+        expr
+    }
+
+    fn renew_ids(&mut self, next_id: &mut impl FnMut() -> NodeId) {
+        match &mut self.kind {
+            ExprKind::Binary(_, lhs, rhs)
+            | ExprKind::Assign(lhs, rhs, _)
+            | ExprKind::AssignOp(_, lhs, rhs, _)
+            | ExprKind::Index(lhs, rhs) => {
+                lhs.renew_ids(next_id);
+                rhs.renew_ids(next_id);
+            }
+            ExprKind::Unary(_, expr)
+            | ExprKind::Field(expr, _)
+            | ExprKind::Cast(expr, _)
+            | ExprKind::Paren(expr)
+            | ExprKind::Try(expr)
+            | ExprKind::Await(expr) => expr.renew_ids(next_id),
+            ExprKind::Call(func, _, args) => {
+                func.renew_ids(next_id);
+                for arg in args {
+                    arg.renew_ids(next_id);
+                }
+            }
+            ExprKind::Block(block)
+            | ExprKind::Loop(block, _)
+            | ExprKind::LabeledBlock(block, _) => block.renew_ids(next_id),
+            ExprKind::If(cond, then, els) => {
+                cond.renew_ids(next_id);
+                then.renew_ids(next_id);
+                if let Some(els) = els {
+                    els.renew_ids(next_id);
+                }
+            }
+            ExprKind::Struct(_, fields, base) => {
+                for (_, expr) in fields {
+                    expr.renew_ids(next_id);
+                }
+                if let Some(base) = base {
+                    base.renew_ids(next_id);
+                }
+            }
+            ExprKind::Array(elems) => {
+                for elem in elems {
+                    elem.renew_ids(next_id);
+                }
+            }
+            ExprKind::While(cond, body, _) => {
+                cond.renew_ids(next_id);
+                body.renew_ids(next_id);
+            }
+            ExprKind::For(pat, iter, body, _) => {
+                pat.renew_ids(next_id);
+                iter.renew_ids(next_id);
+                body.renew_ids(next_id);
+            }
+            ExprKind::Return(value) | ExprKind::Break(_, value) => {
+                if let Some(value) = value {
+                    value.renew_ids(next_id);
+                }
+            }
+            ExprKind::Range(start, end) => {
+                for operand in [start, end].into_iter().flatten() {
+                    operand.renew_ids(next_id);
+                }
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                scrutinee.renew_ids(next_id);
+                for arm in arms {
+                    arm.pat.renew_ids(next_id);
+                    if let Some(guard) = &mut arm.guard {
+                        guard.renew_ids(next_id);
+                    }
+                    arm.body.renew_ids(next_id);
+                }
+            }
+            ExprKind::NumLit(..)
+            | ExprKind::FloatLit(..)
+            | ExprKind::BoolLit(_)
+            | ExprKind::StrLit(_)
+            | ExprKind::CharLit(_)
+            | ExprKind::ByteLit(_)
+            | ExprKind::ByteStrLit(_)
+            | ExprKind::Unit
+            | ExprKind::Path(_)
+            | ExprKind::Continue(_)
+            | ExprKind::MacroCall(..) => (),
+        }
+        self.id = next_id();
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Block {
     pub stmts: Vec<Stmt>,
     pub span: Span,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+impl ExprKind {
+    /// Numeric tag of the variant for serialization and tooling.
+    /// Tags are stable: never change or reuse them, and give new variants new tags.
+    pub fn discriminant(&self) -> u16 {
+        match self {
+            ExprKind::Binary(..) => 0,
+            ExprKind::Unary(..) => 1,
+            ExprKind::NumLit(..) => 2,
+            ExprKind::BoolLit(_) => 3,
+            ExprKind::StrLit(_) => 4,
+            ExprKind::Unit => 5,
+            ExprKind::Path(_) => 6,
+            ExprKind::Assign(..) => 7,
+            ExprKind::Return(_) => 8,
+            ExprKind::Call(..) => 9,
+            ExprKind::Block(_) => 10,
+            ExprKind::If(..) => 11,
+            ExprKind::Index(..) => 12,
+            ExprKind::Field(..) => 13,
+            ExprKind::Struct(..) => 14,
+            ExprKind::Array(_) => 15,
+            ExprKind::Cast(..) => 16,
+            ExprKind::Paren(_) => 17,
+            ExprKind::Loop(..) => 18,
+            ExprKind::While(..) => 19,
+            ExprKind::Break(..) => 20,
+            ExprKind::Continue(_) => 21,
+            ExprKind::Range(..) => 22,
+            ExprKind::MacroCall(..) => 23,
+            ExprKind::Try(_) => 24,
+            ExprKind::CharLit(_) => 25,
+            ExprKind::Match(..) => 26,
+            ExprKind::For(..) => 27,
+            ExprKind::LabeledBlock(..) => 28,
+            ExprKind::FloatLit(..) => 29,
+            ExprKind::Await(_) => 30,
+            ExprKind::ByteLit(_) => 31,
+            ExprKind::ByteStrLit(_) => 32,
+            ExprKind::AssignOp(..) => 33,
+        }
+    }
+}
+
+/// Iterate over statements including the tail expression
+impl<'a> IntoIterator for &'a Block {
+    type Item = &'a Stmt;
+    type IntoIter = std::slice::Iter<'a, Stmt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stmts.iter()
+    }
+}
+
+impl Block {
+    fn renew_ids(&mut self, next_id: &mut impl FnMut() -> NodeId) {
+        for stmt in &mut self.stmts {
+            match &mut stmt.kind {
+                StmtKind::Expr(expr) | StmtKind::Semi(expr) => expr.renew_ids(next_id),
+                StmtKind::Let(LetStmt { init, .. }) => {
+                    if let Some(init) = init {
+                        init.renew_ids(next_id);
+                    }
+                }
+                StmtKind::Item(item) => item.renew_ids(next_id),
+            }
+            stmt.id = next_id();
+        }
+        self.id = next_id();
+    }
+}
+
+impl Item {
+    fn renew_ids(&mut self, next_id: &mut impl FnMut() -> NodeId) {
+        match &mut self.kind {
+            ItemKind::Func(func) => func.renew_ids(next_id),
+            ItemKind::Struct(struct_item) => struct_item.id = next_id(),
+            ItemKind::ExternBlock(block) => {
+                for func in &mut block.funcs {
+                    func.renew_ids(next_id);
+                }
+            }
+            ItemKind::Mod(module) => {
+                for item in &mut module.items {
+                    item.renew_ids(next_id);
+                }
+                module.id = next_id();
+            }
+            ItemKind::Static(static_item) => static_item.value.renew_ids(next_id),
+            ItemKind::Use(_) => (),
+        }
+    }
+}
+
+impl Func {
+    fn renew_ids(&mut self, next_id: &mut impl FnMut() -> NodeId) {
+        if let Some(body) = &mut self.body {
+            body.renew_ids(next_id);
+        }
+        self.id = next_id();
+    }
+}
+
+impl Pattern {
+    fn renew_ids(&mut self, next_id: &mut impl FnMut() -> NodeId) {
+        match &mut self.kind {
+            PatternKind::Wild | PatternKind::Ident(_) | PatternKind::Rest => (),
+            PatternKind::Lit(lit) => lit.renew_ids(next_id),
+            PatternKind::Range(start, end, _) => {
+                for bound in [start, end].into_iter().flatten() {
+                    bound.renew_ids(next_id);
+                }
+            }
+            PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+                for pat in pats {
+                    pat.renew_ids(next_id);
+                }
+            }
+        }
+    }
+
+    /// Variables bound by the pattern in order of appearance
+    pub fn bindings(&self) -> Vec<&Ident> {
+        match &self.kind {
+            PatternKind::Ident(ident) => vec![ident],
+            PatternKind::Wild
+            | PatternKind::Lit(_)
+            | PatternKind::Range(..)
+            | PatternKind::Rest => vec![],
+            PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+                pats.iter().flat_map(|pat| pat.bindings()).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BinOp {
     Add,
     Sub,
@@ -157,31 +522,183 @@ pub enum BinOp {
     Ne,
     Gt,
     Lt,
+    /// `&&` (lazy)
+    And,
 }
 
-#[derive(Debug)]
+impl BinOp {
+    pub const ALL: [BinOp; 8] = [
+        BinOp::Add,
+        BinOp::Sub,
+        BinOp::Mul,
+        BinOp::Eq,
+        BinOp::Ne,
+        BinOp::Gt,
+        BinOp::Lt,
+        BinOp::And,
+    ];
+
+    /// Operators with higher precedence bind tighter
+    /// ref: https://doc.rust-lang.org/reference/expressions.html#expression-precedence
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinOp::Mul => 4,
+            BinOp::Add | BinOp::Sub => 3,
+            BinOp::Eq | BinOp::Ne | BinOp::Gt | BinOp::Lt => 2,
+            BinOp::And => 1,
+        }
+    }
+
+    /// Comparison and logical operators produce bool regardless of operand types
+    pub fn result_is_bool(&self) -> bool {
+        match self {
+            BinOp::Add | BinOp::Sub | BinOp::Mul => false,
+            BinOp::Eq | BinOp::Ne | BinOp::Gt | BinOp::Lt | BinOp::And => true,
+        }
+    }
+
+    /// Comparison operators are non-associative (i.e. `a == b == c` is rejected)
+    pub fn is_left_assoc(&self) -> bool {
+        match self {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::And => true,
+            BinOp::Eq | BinOp::Ne | BinOp::Gt | BinOp::Lt => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum UnOp {
     Plus,
     Minus,
+    /// `!`
+    Not,
+    /// `*`
+    Deref,
+    /// `&`
+    Ref,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ty {
     pub kind: TyKind,
     pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TyKind {
     Unit,
     Bool,
     I32,
     Str,
     Array(Box<Ty>, usize),
-    Adt(Path),
+    /// path, generic args (e.g. `Vec<i32>`)
+    Adt(Path, Vec<Ty>),
     Ref(Option<Region>, Box<Ty>),
     ConstPtr(Box<Ty>),
     Never,
+    /// params, return type
+    Fn(Vec<Ty>, Box<Ty>),
+    Tuple(Vec<Ty>),
+    /// `impl Trait` (the trait is not resolved)
+    ImplTrait(Path),
+    /// `dyn Trait` (the trait is not resolved)
+    DynTrait(Path),
 }
 
 pub type Region = String;
+
+#[test]
+fn test_binop_precedence() {
+    assert!(BinOp::Mul.precedence() > BinOp::Add.precedence());
+    assert_eq!(BinOp::Add.precedence(), BinOp::Sub.precedence());
+    assert!(BinOp::Sub.precedence() > BinOp::Lt.precedence());
+    assert_eq!(BinOp::Eq.precedence(), BinOp::Gt.precedence());
+    assert!(BinOp::Add.is_left_assoc());
+    assert!(BinOp::Mul.is_left_assoc());
+    assert!(!BinOp::Eq.is_left_assoc());
+    assert!(!BinOp::Ne.is_left_assoc());
+    assert!(!BinOp::Lt.is_left_assoc());
+}
+
+#[test]
+fn test_deep_clone_with_fresh_ids() {
+    use crate::parse::Parser;
+
+    fn collect_ids(expr: &Expr, ids: &mut Vec<NodeId>) {
+        ids.push(expr.id);
+        match &expr.kind {
+            ExprKind::Binary(_, lhs, rhs) => {
+                collect_ids(lhs, ids);
+                collect_ids(rhs, ids);
+            }
+            ExprKind::Unary(_, expr) => collect_ids(expr, ids),
+            ExprKind::Block(block) => {
+                ids.push(block.id);
+                for stmt in block {
+                    ids.push(stmt.id);
+                    if let StmtKind::Expr(expr) | StmtKind::Semi(expr) = &stmt.kind {
+                        collect_ids(expr, ids);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut parser = Parser::from_source("-a + { b; 1 }");
+    let expr = parser.parse_expr().unwrap();
+    let cloned = expr.deep_clone_with_fresh_ids(&mut || parser.get_next_id());
+
+    let mut ids = vec![];
+    collect_ids(&expr, &mut ids);
+    let num_ids = ids.len();
+    collect_ids(&cloned, &mut ids);
+    assert_eq!(ids.len(), num_ids * 2);
+    // all ids are distinct
+    let unique: std::collections::HashSet<_> = ids.iter().collect();
+    assert_eq!(unique.len(), ids.len());
+    assert_eq!(cloned.span, expr.span);
+}
+
+#[test]
+fn test_expr_kind_discriminant() {
+    use crate::parse::Parser;
+
+    let parse = |src: &str| Parser::from_source(src).parse_expr().unwrap().kind;
+
+    assert_eq!(parse("1 + 2").discriminant(), 0);
+    assert_eq!(parse("-1").discriminant(), 1);
+    assert_eq!(parse("1").discriminant(), 2);
+    assert_eq!(parse("x").discriminant(), 6);
+    assert_eq!(parse("f()").discriminant(), 9);
+    assert_eq!(parse("{ 1 }").discriminant(), 10);
+    assert_eq!(parse("loop {}").discriminant(), 18);
+    assert_eq!(parse("'a'").discriminant(), 25);
+    assert_eq!(parse("match x { _ => 1 }").discriminant(), 26);
+    assert_eq!(parse("'a: {}").discriminant(), 28);
+    assert_eq!(parse("a += 1").discriminant(), 33);
+}
+
+#[test]
+fn test_binop_result_is_bool() {
+    assert!(BinOp::Lt.result_is_bool());
+    assert!(BinOp::Eq.result_is_bool());
+    assert!(BinOp::And.result_is_bool());
+    assert!(!BinOp::Add.result_is_bool());
+    assert!(!BinOp::Mul.result_is_bool());
+}
+
+#[test]
+fn test_iterate_block() {
+    use crate::parse::Parser;
+
+    let block = Parser::from_source("{ let x = 1; f(x); x }")
+        .parse_block()
+        .unwrap();
+    let stmts: Vec<&Stmt> = (&block).into_iter().collect();
+    assert_eq!(stmts.len(), 3);
+    assert!(matches!(stmts[0].kind, StmtKind::Let(_)));
+    assert!(matches!(stmts[1].kind, StmtKind::Semi(_)));
+    // tail expression
+    assert!(matches!(stmts[2].kind, StmtKind::Expr(_)));
+}
@@ -0,0 +1,662 @@
+use super::*;
+use crate::lexer::{tokens_to_string, Lexer, TokenKind};
+
+/// Print the crate as source code.
+/// Parentheses are inserted where precedence requires them, so parsing the output
+/// produces the same tree (modulo spans and node ids).
+pub fn print_crate(krate: &Crate) -> String {
+    let mut printer = Printer::default();
+    for (i, item) in krate.items.iter().enumerate() {
+        if i != 0 {
+            printer.newline();
+        }
+        printer.print_item(item);
+    }
+    printer.out.push('\n');
+    printer.out
+}
+
+/// Binding powers of expressions used to decide where parentheses are needed.
+/// Operands binding looser than their position allows are parenthesized.
+/// Binary operators use `BinOp::precedence` shifted by `Bp::BINARY`.
+struct Bp;
+
+impl Bp {
+    /// `return` and `break` with a value, assignment
+    const JUMP: u8 = 0;
+    const RANGE: u8 = 1;
+    const BINARY: u8 = 1;
+    const CAST: u8 = 6;
+    const PREFIX: u8 = 7;
+    const POSTFIX: u8 = 8;
+    const PRIMARY: u8 = 9;
+}
+
+fn binding_power(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Assign(..)
+        | ExprKind::AssignOp(..)
+        | ExprKind::Return(Some(_))
+        | ExprKind::Break(_, Some(_)) => Bp::JUMP,
+        ExprKind::Range(..) => Bp::RANGE,
+        ExprKind::Binary(op, _, _) => Bp::BINARY + op.precedence(),
+        ExprKind::Cast(..) => Bp::CAST,
+        ExprKind::Unary(..) => Bp::PREFIX,
+        ExprKind::Call(..)
+        | ExprKind::Index(..)
+        | ExprKind::Field(..)
+        | ExprKind::Try(_)
+        | ExprKind::Await(_) => Bp::POSTFIX,
+        _ => Bp::PRIMARY,
+    }
+}
+
+/// Whether the expression starts with a struct literal, which is not allowed before a block
+/// (e.g. `match S {} {}`)
+fn starts_with_struct_literal(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Struct(..) => true,
+        ExprKind::Binary(_, lhs, _)
+        | ExprKind::Assign(lhs, _, _)
+        | ExprKind::AssignOp(_, lhs, _, _)
+        | ExprKind::Range(Some(lhs), _)
+        | ExprKind::Cast(lhs, _)
+        | ExprKind::Call(lhs, _, _)
+        | ExprKind::Index(lhs, _)
+        | ExprKind::Field(lhs, _)
+        | ExprKind::Try(lhs)
+        | ExprKind::Await(lhs) => starts_with_struct_literal(lhs),
+        _ => false,
+    }
+}
+
+/// Whether the expression ends with a path, which would be parsed as a struct literal
+/// before a block (e.g. `if a == b {}` is `if a == (b {})`)
+fn ends_with_path(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Path(_) => true,
+        ExprKind::Binary(_, _, rhs)
+        | ExprKind::Unary(_, rhs)
+        | ExprKind::Assign(_, rhs, _)
+        | ExprKind::AssignOp(_, _, rhs, _)
+        | ExprKind::Range(_, Some(rhs))
+        | ExprKind::Return(Some(rhs))
+        | ExprKind::Break(_, Some(rhs)) => ends_with_path(rhs),
+        _ => false,
+    }
+}
+
+fn binop_str(op: &BinOp) -> &'static str {
+    match op {
+        BinOp::Add => "+",
+        BinOp::Sub => "-",
+        BinOp::Mul => "*",
+        BinOp::Eq => "==",
+        BinOp::Ne => "!=",
+        BinOp::Gt => ">",
+        BinOp::Lt => "<",
+        BinOp::And => "&&",
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn print_ident(&mut self, ident: &Ident) {
+        // keywords used as identifiers must be raw (e.g. `r#return`)
+        let name = ident.as_str();
+        let mut lexer = Lexer::new(name.to_string());
+        if !matches!(lexer.skip_token().kind, TokenKind::Ident(_)) {
+            self.out.push_str("r#");
+        }
+        self.out.push_str(name);
+    }
+
+    fn print_label(&mut self, label: &Ident) {
+        self.out.push('\'');
+        self.out.push_str(label.as_str());
+    }
+
+    fn print_path(&mut self, path: &Path) {
+        for (i, seg) in path.segments.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str("::");
+            }
+            self.print_ident(seg);
+        }
+    }
+
+    fn print_item(&mut self, item: &Item) {
+        match &item.kind {
+            ItemKind::Func(func) => self.print_func(func),
+            ItemKind::Struct(strct) => {
+                self.out.push_str("struct ");
+                self.print_ident(&strct.ident);
+                self.out.push_str(" {");
+                self.indent += 1;
+                for (name, ty) in &strct.fields {
+                    self.newline();
+                    self.print_ident(name);
+                    self.out.push_str(": ");
+                    self.print_ty(ty);
+                    self.out.push(',');
+                }
+                self.indent -= 1;
+                if !strct.fields.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+            ItemKind::ExternBlock(block) => {
+                self.out.push_str("extern \"C\" {");
+                self.indent += 1;
+                for func in &block.funcs {
+                    self.newline();
+                    self.print_func(func);
+                }
+                self.indent -= 1;
+                if !block.funcs.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+            ItemKind::Mod(module) => {
+                self.out.push_str("mod ");
+                self.print_ident(&module.name);
+                self.out.push_str(" {");
+                self.indent += 1;
+                for item in &module.items {
+                    self.newline();
+                    self.print_item(item);
+                }
+                self.indent -= 1;
+                if !module.items.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+            ItemKind::Use(use_item) => {
+                self.out.push_str("use ");
+                self.print_path(&use_item.path);
+                if use_item.is_glob {
+                    self.out.push_str("::*");
+                }
+                self.out.push(';');
+            }
+            ItemKind::Static(static_item) => {
+                self.out.push_str("static ");
+                if static_item.mutable {
+                    self.out.push_str("mut ");
+                }
+                self.print_ident(&static_item.name);
+                self.out.push_str(": ");
+                self.print_ty(&static_item.ty);
+                self.out.push_str(" = ");
+                self.print_expr(&static_item.value);
+                self.out.push(';');
+            }
+        }
+    }
+
+    fn print_func(&mut self, func: &Func) {
+        if func.is_const {
+            self.out.push_str("const ");
+        }
+        self.out.push_str("fn ");
+        self.print_ident(&func.name);
+        if !func.generics.is_empty() {
+            self.out.push('<');
+            for (i, generic) in func.generics.iter().enumerate() {
+                if i != 0 {
+                    self.out.push_str(", ");
+                }
+                self.print_ident(generic);
+            }
+            self.out.push('>');
+        }
+        self.out.push('(');
+        for (i, (name, ty)) in func.params.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str(", ");
+            }
+            self.print_ident(name);
+            self.out.push_str(": ");
+            self.print_ty(ty);
+        }
+        self.out.push(')');
+        if !matches!(func.ret_ty.kind, TyKind::Unit) {
+            self.out.push_str(" -> ");
+            self.print_ty(&func.ret_ty);
+        }
+        match &func.body {
+            Some(body) => {
+                self.out.push(' ');
+                self.print_block(body);
+            }
+            None => self.out.push(';'),
+        }
+    }
+
+    fn print_ty(&mut self, ty: &Ty) {
+        match &ty.kind {
+            TyKind::Unit => self.out.push_str("()"),
+            TyKind::Bool => self.out.push_str("bool"),
+            TyKind::I32 => self.out.push_str("i32"),
+            TyKind::Str => self.out.push_str("str"),
+            TyKind::Never => self.out.push('!'),
+            TyKind::Array(elem_ty, n) => {
+                self.out.push('[');
+                self.print_ty(elem_ty);
+                self.out.push_str(&format!("; {}]", n));
+            }
+            TyKind::Adt(path, args) => {
+                self.print_path(path);
+                if !args.is_empty() {
+                    self.out.push('<');
+                    self.print_ty_list(args);
+                    self.out.push('>');
+                }
+            }
+            TyKind::Ref(region, referent) => {
+                self.out.push('&');
+                if let Some(region) = region {
+                    self.out.push_str(&format!("'{} ", region));
+                }
+                self.print_ty(referent);
+            }
+            TyKind::ConstPtr(referent) => {
+                self.out.push_str("*const ");
+                self.print_ty(referent);
+            }
+            TyKind::Fn(params, ret_ty) => {
+                self.out.push_str("fn(");
+                self.print_ty_list(params);
+                self.out.push_str(") -> ");
+                self.print_ty(ret_ty);
+            }
+            TyKind::Tuple(elems) => {
+                self.out.push('(');
+                self.print_ty_list(elems);
+                // one-element tuple needs a trailing comma
+                if elems.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            TyKind::ImplTrait(path) => {
+                self.out.push_str("impl ");
+                self.print_path(path);
+            }
+            TyKind::DynTrait(path) => {
+                self.out.push_str("dyn ");
+                self.print_path(path);
+            }
+        }
+    }
+
+    fn print_ty_list(&mut self, tys: &[Ty]) {
+        for (i, ty) in tys.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str(", ");
+            }
+            self.print_ty(ty);
+        }
+    }
+
+    fn print_pattern(&mut self, pat: &Pattern) {
+        match &pat.kind {
+            PatternKind::Wild => self.out.push('_'),
+            PatternKind::Rest => self.out.push_str(".."),
+            PatternKind::Ident(ident) => self.print_ident(ident),
+            PatternKind::Lit(lit) => self.print_expr(lit),
+            PatternKind::Range(start, end, inclusive) => {
+                if let Some(start) = start {
+                    self.print_expr(start);
+                }
+                self.out.push_str(if *inclusive { "..=" } else { ".." });
+                if let Some(end) = end {
+                    self.print_expr(end);
+                }
+            }
+            PatternKind::Tuple(pats) => {
+                self.out.push('(');
+                self.print_pattern_list(pats);
+                // `(x,)` is a tuple, but `(x)` is not
+                if pats.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            PatternKind::Array(pats) => {
+                self.out.push('[');
+                self.print_pattern_list(pats);
+                self.out.push(']');
+            }
+        }
+    }
+
+    fn print_pattern_list(&mut self, pats: &[Pattern]) {
+        for (i, pat) in pats.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str(", ");
+            }
+            self.print_pattern(pat);
+        }
+    }
+
+    fn print_block(&mut self, block: &Block) {
+        if block.stmts.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+        self.out.push('{');
+        self.indent += 1;
+        for stmt in block {
+            self.newline();
+            self.print_stmt(stmt);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.out.push('}');
+    }
+
+    fn print_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Item(item) => self.print_item(item),
+            StmtKind::Expr(expr) => self.print_expr(expr),
+            StmtKind::Semi(expr) => {
+                self.print_expr(expr);
+                self.out.push(';');
+            }
+            StmtKind::Let(let_stmt) => {
+                self.out.push_str("let ");
+                if let_stmt.mutable {
+                    self.out.push_str("mut ");
+                }
+                self.print_pattern(&let_stmt.pat);
+                if let Some(ty) = &let_stmt.ty {
+                    self.out.push_str(": ");
+                    self.print_ty(ty);
+                }
+                if let Some(init) = &let_stmt.init {
+                    self.out.push_str(" = ");
+                    self.print_expr(init);
+                }
+                self.out.push(';');
+            }
+        }
+    }
+
+    /// Print the expression, parenthesized if it binds looser than `min_bp`
+    fn print_expr_bp(&mut self, expr: &Expr, min_bp: u8) {
+        if binding_power(expr) < min_bp {
+            self.out.push('(');
+            self.print_expr(expr);
+            self.out.push(')');
+        } else {
+            self.print_expr(expr);
+        }
+    }
+
+    /// Print the expression followed by a block, where struct literals are not allowed
+    fn print_expr_no_struct(&mut self, expr: &Expr) {
+        if starts_with_struct_literal(expr) || ends_with_path(expr) {
+            self.out.push('(');
+            self.print_expr(expr);
+            self.out.push(')');
+        } else {
+            self.print_expr(expr);
+        }
+    }
+
+    fn print_exprs(&mut self, exprs: &[Expr]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i != 0 {
+                self.out.push_str(", ");
+            }
+            self.print_expr(expr);
+        }
+    }
+
+    fn print_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::NumLit(n, suffix) => {
+                self.out.push_str(&n.to_string());
+                self.out.push_str(suffix.as_deref().unwrap_or(""));
+            }
+            ExprKind::FloatLit(n, suffix) => {
+                self.out.push_str(&format!("{:?}", n));
+                self.out.push_str(suffix.as_deref().unwrap_or(""));
+            }
+            ExprKind::BoolLit(b) => self.out.push_str(&b.to_string()),
+            ExprKind::StrLit(s) => self.out.push_str(&format!("{:?}", s)),
+            ExprKind::CharLit(c) => self.out.push_str(&format!("{:?}", c)),
+            ExprKind::ByteLit(b) => self.out.push_str(&TokenKind::ByteLit(*b).to_string()),
+            ExprKind::ByteStrLit(s) => {
+                let t = TokenKind::ByteStrLit(s.clone());
+                self.out.push_str(&t.to_string());
+            }
+            ExprKind::Unit => self.out.push_str("()"),
+            ExprKind::Path(path) => self.print_path(path),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let prec = Bp::BINARY + op.precedence();
+                // comparisons are non-associative, so `(a == b) == c` keeps its parentheses
+                let lhs_bp = if op.is_left_assoc() { prec } else { prec + 1 };
+                self.print_expr_bp(lhs, lhs_bp);
+                self.out.push_str(&format!(" {} ", binop_str(op)));
+                self.print_expr_bp(rhs, prec + 1);
+            }
+            ExprKind::Unary(op, operand) => {
+                self.out.push_str(match op {
+                    UnOp::Plus => "+",
+                    UnOp::Minus => "-",
+                    UnOp::Not => "!",
+                    UnOp::Deref => "*",
+                    UnOp::Ref => "&",
+                });
+                // `& &x` is not `&&x`
+                if matches!(op, UnOp::Ref) && matches!(operand.kind, ExprKind::Unary(UnOp::Ref, _))
+                {
+                    self.out.push(' ');
+                }
+                self.print_expr_bp(operand, Bp::PREFIX);
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                self.print_expr_bp(lhs, Bp::RANGE);
+                self.out.push_str(" = ");
+                self.print_expr(rhs);
+            }
+            ExprKind::AssignOp(op, lhs, rhs, _) => {
+                self.print_expr_bp(lhs, Bp::RANGE);
+                self.out.push_str(&format!(" {}= ", binop_str(op)));
+                self.print_expr(rhs);
+            }
+            ExprKind::Return(value) => {
+                self.out.push_str("return");
+                if let Some(value) = value {
+                    self.out.push(' ');
+                    self.print_expr(value);
+                }
+            }
+            ExprKind::Call(callee, type_args, args) => {
+                self.print_expr_bp(callee, Bp::POSTFIX);
+                if !type_args.is_empty() {
+                    self.out.push_str("::<");
+                    self.print_ty_list(type_args);
+                    self.out.push('>');
+                }
+                self.out.push('(');
+                self.print_exprs(args);
+                self.out.push(')');
+            }
+            ExprKind::Block(block) => self.print_block(block),
+            ExprKind::If(cond, then, els) => {
+                self.out.push_str("if ");
+                self.print_expr_no_struct(cond);
+                self.out.push(' ');
+                self.print_expr(then);
+                if let Some(els) = els {
+                    self.out.push_str(" else ");
+                    self.print_expr(els);
+                }
+            }
+            ExprKind::Index(base, index) => {
+                self.print_expr_bp(base, Bp::POSTFIX);
+                self.out.push('[');
+                self.print_expr(index);
+                self.out.push(']');
+            }
+            ExprKind::Field(base, name) => {
+                self.print_expr_bp(base, Bp::POSTFIX);
+                self.out.push('.');
+                self.print_ident(name);
+            }
+            ExprKind::Struct(path, fields, base) => {
+                self.print_path(path);
+                self.out.push_str(" {");
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    self.out.push_str(if i == 0 { " " } else { ", " });
+                    self.print_ident(name);
+                    self.out.push_str(": ");
+                    self.print_expr(value);
+                }
+                if let Some(base) = base {
+                    self.out
+                        .push_str(if fields.is_empty() { " .." } else { ", .." });
+                    self.print_expr(base);
+                }
+                if !fields.is_empty() || base.is_some() {
+                    self.out.push(' ');
+                }
+                self.out.push('}');
+            }
+            ExprKind::Array(elems) => {
+                self.out.push('[');
+                self.print_exprs(elems);
+                self.out.push(']');
+            }
+            ExprKind::Cast(operand, ty) => {
+                self.print_expr_bp(operand, Bp::CAST);
+                self.out.push_str(" as ");
+                self.print_ty(ty);
+            }
+            ExprKind::Paren(inner) => {
+                self.out.push('(');
+                self.print_expr(inner);
+                self.out.push(')');
+            }
+            ExprKind::Loop(body, label) => {
+                self.print_loop_label(label.as_ref());
+                self.out.push_str("loop ");
+                self.print_block(body);
+            }
+            ExprKind::While(cond, body, label) => {
+                self.print_loop_label(label.as_ref());
+                self.out.push_str("while ");
+                self.print_expr_no_struct(cond);
+                self.out.push(' ');
+                self.print_block(body);
+            }
+            ExprKind::LabeledBlock(body, label) => {
+                self.print_loop_label(Some(label));
+                self.print_block(body);
+            }
+            ExprKind::For(pat, iter, body, label) => {
+                self.print_loop_label(label.as_ref());
+                self.out.push_str("for ");
+                self.print_pattern(pat);
+                self.out.push_str(" in ");
+                self.print_expr_no_struct(iter);
+                self.out.push(' ');
+                self.print_block(body);
+            }
+            ExprKind::Break(label, value) => {
+                self.out.push_str("break");
+                if let Some(label) = label {
+                    self.out.push(' ');
+                    self.print_label(label);
+                }
+                if let Some(value) = value {
+                    self.out.push(' ');
+                    self.print_expr(value);
+                }
+            }
+            ExprKind::Continue(label) => {
+                self.out.push_str("continue");
+                if let Some(label) = label {
+                    self.out.push(' ');
+                    self.print_label(label);
+                }
+            }
+            ExprKind::Range(start, end) => {
+                if let Some(start) = start {
+                    self.print_expr_bp(start, Bp::BINARY + 1);
+                }
+                self.out.push_str("..");
+                if let Some(end) = end {
+                    self.print_expr_bp(end, Bp::BINARY + 1);
+                }
+            }
+            ExprKind::MacroCall(name, delim, tokens) => {
+                self.print_ident(name);
+                let (open, close) = match delim {
+                    Delim::Paren => ('(', ')'),
+                    Delim::Bracket => ('[', ']'),
+                    Delim::Brace => ('{', '}'),
+                };
+                self.out.push('!');
+                self.out.push(open);
+                self.out.push_str(&tokens_to_string(tokens));
+                self.out.push(close);
+            }
+            ExprKind::Try(operand) => {
+                self.print_expr_bp(operand, Bp::POSTFIX);
+                self.out.push('?');
+            }
+            ExprKind::Await(operand) => {
+                self.print_expr_bp(operand, Bp::POSTFIX);
+                self.out.push_str(".await");
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                self.out.push_str("match ");
+                self.print_expr_no_struct(scrutinee);
+                self.out.push_str(" {");
+                self.indent += 1;
+                for arm in arms {
+                    self.newline();
+                    self.print_pattern(&arm.pat);
+                    if let Some(guard) = &arm.guard {
+                        self.out.push_str(" if ");
+                        self.print_expr(guard);
+                    }
+                    self.out.push_str(" => ");
+                    self.print_expr(&arm.body);
+                    self.out.push(',');
+                }
+                self.indent -= 1;
+                if !arms.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+        }
+    }
+
+    fn print_loop_label(&mut self, label: Option<&Ident>) {
+        if let Some(label) = label {
+            self.print_label(label);
+            self.out.push_str(": ");
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use super::visitor::{walk_expr_mut, MutVisitor};
+use super::{Block, Expr, ExprKind, Pattern, StmtKind, Ty};
+use crate::span::{Span, Symbol};
+
+/// Rename free occurrences of the variable `from` in `expr` to `to` (i.e. alpha-renaming).
+/// Occurrences bound by `let`, `for` or match arms inside `expr` are left as they are.
+/// If a renamed occurrence would be captured by a binding of `to`
+/// (e.g. `x` to `y` in `{ let y = 1; x }`), `expr` is not changed and the span of the
+/// occurrence is returned.
+pub fn rename(expr: &mut Expr, from: &str, to: &str) -> Result<(), Span> {
+    let mut renamer = Renamer {
+        from: Symbol::intern(from),
+        to: Symbol::intern(to),
+        to_bound: false,
+        captured: None,
+    };
+    let mut renamed = expr.clone();
+    renamer.visit_expr(&mut renamed);
+    match renamer.captured {
+        Some(span) => Err(span),
+        None => {
+            *expr = renamed;
+            Ok(())
+        }
+    }
+}
+
+struct Renamer {
+    from: Symbol,
+    to: Symbol,
+    /// `to` is bound in the current scope
+    to_bound: bool,
+    /// First occurrence which would be captured
+    captured: Option<Span>,
+}
+
+impl Renamer {
+    /// Check if `pat` binds `name`
+    fn binds(pat: &Pattern, name: Symbol) -> bool {
+        pat.bindings().iter().any(|ident| ident.symbol == name)
+    }
+
+    /// Visit the scope of the bindings of `pat` with `f`, where `from` is not free if `pat` binds it
+    fn in_scope_of(&mut self, pat: &Pattern, f: impl FnOnce(&mut Self)) {
+        if Self::binds(pat, self.from) {
+            return;
+        }
+        let to_bound = self.to_bound;
+        self.to_bound |= Self::binds(pat, self.to);
+        f(self);
+        self.to_bound = to_bound;
+    }
+}
+
+impl MutVisitor for Renamer {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Path(path) => {
+                let [ident] = &mut path.segments[..] else {
+                    return;
+                };
+                if ident.symbol != self.from {
+                    return;
+                }
+                if self.to_bound {
+                    self.captured.get_or_insert(ident.span.clone());
+                } else {
+                    ident.symbol = self.to;
+                }
+            }
+            ExprKind::For(pat, iter, body, _) => {
+                self.visit_expr(iter);
+                self.in_scope_of(pat, |this| this.visit_block(body));
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.in_scope_of(&arm.pat, |this| {
+                        if let Some(guard) = &mut arm.guard {
+                            this.visit_expr(guard);
+                        }
+                        this.visit_expr(&mut arm.body);
+                    });
+                }
+            }
+            _ => walk_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        let to_bound = self.to_bound;
+        for stmt in &mut block.stmts {
+            let StmtKind::Let(let_stmt) = &mut stmt.kind else {
+                self.visit_stmt(stmt);
+                continue;
+            };
+            // the initializer is out of the scope of the new bindings
+            if let Some(init) = &mut let_stmt.init {
+                self.visit_expr(init);
+            }
+            // `from` is not free in the rest of the block
+            if Self::binds(&let_stmt.pat, self.from) {
+                break;
+            }
+            self.to_bound |= Self::binds(&let_stmt.pat, self.to);
+        }
+        self.to_bound = to_bound;
+    }
+
+    // patterns and types do not refer to variables
+    fn visit_pattern(&mut self, _pat: &mut Pattern) {}
+    fn visit_type(&mut self, _ty: &mut Ty) {}
+}
+
+#[test]
+fn test_rename() {
+    use crate::ast::structural::Structural;
+    use crate::parse::Parser;
+
+    let parse = |src: &str| Parser::from_source(src).parse_expr().unwrap();
+    let assert_renamed = |src: &str, from: &str, to: &str, expected: &str| {
+        let mut expr = parse(src);
+        rename(&mut expr, from, to).unwrap();
+        assert!(Structural(&expr) == Structural(&parse(expected)), "{}", src);
+    };
+
+    // only free occurrences are renamed
+    assert_renamed(
+        "x + { let a = x; let x = 1; x + a }",
+        "x",
+        "y",
+        "y + { let a = y; let x = 1; x + a }",
+    );
+    // the initializer refers to the outer binding
+    assert_renamed("{ let x = x + 1; x }", "x", "y", "{ let x = y + 1; x }");
+    assert_renamed(
+        "match x { (x, b) => x + b, c if c > x => x }",
+        "x",
+        "z",
+        "match z { (x, b) => x + b, c if c > z => z }",
+    );
+    // the guard is in the scope of the arm
+    assert_renamed(
+        "match v { x if x > 0 => x, _ => x }",
+        "x",
+        "y",
+        "match v { x if x > 0 => x, _ => y }",
+    );
+    assert_renamed(
+        "for x in 0..x { f(x); }",
+        "x",
+        "i",
+        "for x in 0..i { f(x); }",
+    );
+    // paths and field names are not variables
+    assert_renamed(
+        "m::x + s.x + S { x: x }.x",
+        "x",
+        "y",
+        "m::x + s.x + S { x: y }.x",
+    );
+
+    // `y` would refer to the inner binding
+    let mut expr = parse("{ let y = 1; x + y }");
+    let span = rename(&mut expr, "x", "y").unwrap_err();
+    assert_eq!(span.to_snippet(), "x");
+    // no capture if `x` is shadowed as well
+    let mut expr = parse("{ let y = 1; let x = 2; x + y }");
+    assert!(rename(&mut expr, "x", "y").is_ok());
+}
@@ -0,0 +1,62 @@
+use super::{visitor, Expr};
+
+/// Find the innermost expression whose span contains the byte offset
+/// (e.g. `2` in `1 + 2 * 3` for the offset of `2`)
+pub fn node_at(expr: &Expr, offset: usize) -> Option<&Expr> {
+    let mut finder = NodeFinder {
+        offset,
+        found: None,
+    };
+    visitor::go_expr(&mut finder, expr);
+    finder.found
+}
+
+struct NodeFinder<'ctx> {
+    offset: usize,
+    /// Innermost expression found so far
+    found: Option<&'ctx Expr>,
+}
+
+impl<'ctx> visitor::Visitor<'ctx> for NodeFinder<'ctx> {
+    fn visit_expr(&mut self, expr: &'ctx Expr) {
+        // subexpressions are visited after their parent
+        if expr.span.contains(self.offset) {
+            self.found = Some(expr);
+        }
+    }
+}
+
+#[test]
+fn test_node_at() {
+    use super::{BinOp, ExprKind};
+    use crate::parse::Parser;
+
+    let expr = Parser::from_source("1 + 2 * 3").parse_expr().unwrap();
+    let node = node_at(&expr, 4).unwrap();
+    assert!(matches!(node.kind, ExprKind::NumLit(2, None)));
+    // `*` is only contained in the multiplication
+    let node = node_at(&expr, 6).unwrap();
+    assert!(matches!(node.kind, ExprKind::Binary(BinOp::Mul, _, _)));
+    assert_eq!(node.span.to_snippet(), "2 * 3");
+    let node = node_at(&expr, 2).unwrap();
+    assert!(matches!(node.kind, ExprKind::Binary(BinOp::Add, _, _)));
+    assert!(node_at(&expr, 9).is_none());
+
+    let expr = Parser::from_source("f(a, { b.c })").parse_expr().unwrap();
+    let node = node_at(&expr, 8).unwrap();
+    assert_eq!(node.span.to_snippet(), "b.c");
+    let node = node_at(&expr, 7).unwrap();
+    assert_eq!(node.span.to_snippet(), "b");
+    let node = node_at(&expr, 5).unwrap();
+    assert_eq!(node.span.to_snippet(), "{ b.c }");
+
+    // offsets are in bytes (`é` is 2 bytes)
+    let src = "f('é', x)";
+    let expr = Parser::from_source(src).parse_expr().unwrap();
+    let offset = src.find('x').unwrap();
+    assert_eq!(offset, 8);
+    let node = node_at(&expr, offset).unwrap();
+    assert_eq!(node.span.to_snippet(), "x");
+    let node = node_at(&expr, 4).unwrap();
+    assert!(matches!(node.kind, ExprKind::CharLit('é')));
+}
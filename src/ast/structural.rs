@@ -0,0 +1,420 @@
+use super::*;
+use crate::span::Symbol;
+use std::hash::{Hash, Hasher};
+
+/// Compare and hash AST nodes by structure, ignoring node ids and spans
+/// (e.g. to find common subexpressions).
+/// `Structural(&a) == Structural(&b)` iff `a` and `b` have the same kinds recursively,
+/// and equal nodes have the same hash.
+pub struct Structural<'a, T>(pub &'a T);
+
+impl<T: Structure> PartialEq for Structural<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        atoms(self.0) == atoms(other.0)
+    }
+}
+
+impl<T: Structure> Eq for Structural<'_, T> {}
+
+impl<T: Structure> Hash for Structural<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        atoms(self.0).hash(state);
+    }
+}
+
+/// Pre-order serialization of a node.
+/// Variants and lengths are recorded so that different trees never have the same atoms.
+#[derive(PartialEq, Eq, Hash)]
+pub enum Atom<'a> {
+    Tag(u16),
+    Len(usize),
+    Sym(Symbol),
+    Str(&'a str),
+    /// Tokens of macro calls
+    Token(String),
+    Int(u128),
+    Bool(bool),
+    Char(char),
+    Bytes(&'a [u8]),
+    BinOp(&'a BinOp),
+    UnOp(&'a UnOp),
+}
+
+/// AST nodes which can be wrapped by `Structural`
+pub trait Structure {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>);
+}
+
+fn atoms<T: Structure>(node: &T) -> Vec<Atom<'_>> {
+    let mut out = vec![];
+    node.atoms(&mut out);
+    out
+}
+
+impl<T: Structure> Structure for Box<T> {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        (**self).atoms(out);
+    }
+}
+
+impl<T: Structure> Structure for Option<T> {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        match self {
+            None => out.push(Atom::Tag(0)),
+            Some(node) => {
+                out.push(Atom::Tag(1));
+                node.atoms(out);
+            }
+        }
+    }
+}
+
+impl<T: Structure> Structure for Vec<T> {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        out.push(Atom::Len(self.len()));
+        for node in self {
+            node.atoms(out);
+        }
+    }
+}
+
+impl Structure for Ident {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        out.push(Atom::Sym(self.symbol));
+    }
+}
+
+impl Structure for Path {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        self.segments.atoms(out);
+    }
+}
+
+impl Structure for String {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        out.push(Atom::Str(self));
+    }
+}
+
+impl Structure for Expr {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        out.push(Atom::Tag(self.kind.discriminant()));
+        match &self.kind {
+            ExprKind::Binary(op, lhs, rhs) => {
+                out.push(Atom::BinOp(op));
+                lhs.atoms(out);
+                rhs.atoms(out);
+            }
+            ExprKind::Unary(op, operand) => {
+                out.push(Atom::UnOp(op));
+                operand.atoms(out);
+            }
+            ExprKind::NumLit(n, suffix) => {
+                out.push(Atom::Int(*n));
+                suffix.atoms(out);
+            }
+            ExprKind::FloatLit(n, suffix) => {
+                out.push(Atom::Int(n.to_bits() as u128));
+                suffix.atoms(out);
+            }
+            ExprKind::BoolLit(b) => out.push(Atom::Bool(*b)),
+            ExprKind::StrLit(s) => out.push(Atom::Str(s)),
+            ExprKind::CharLit(c) => out.push(Atom::Char(*c)),
+            ExprKind::ByteLit(b) => out.push(Atom::Int(*b as u128)),
+            ExprKind::ByteStrLit(s) => out.push(Atom::Bytes(s)),
+            ExprKind::Unit => (),
+            ExprKind::Path(path) => path.atoms(out),
+            ExprKind::Assign(lhs, rhs, _) => {
+                lhs.atoms(out);
+                rhs.atoms(out);
+            }
+            ExprKind::AssignOp(op, lhs, rhs, _) => {
+                out.push(Atom::BinOp(op));
+                lhs.atoms(out);
+                rhs.atoms(out);
+            }
+            ExprKind::Return(value) => value.atoms(out),
+            ExprKind::Paren(expr) | ExprKind::Try(expr) | ExprKind::Await(expr) => expr.atoms(out),
+            ExprKind::Call(callee, generic_args, args) => {
+                callee.atoms(out);
+                generic_args.atoms(out);
+                args.atoms(out);
+            }
+            ExprKind::Block(block) => block.atoms(out),
+            ExprKind::If(cond, then, els) => {
+                cond.atoms(out);
+                then.atoms(out);
+                els.atoms(out);
+            }
+            ExprKind::Index(base, index) => {
+                base.atoms(out);
+                index.atoms(out);
+            }
+            ExprKind::Field(base, name) => {
+                base.atoms(out);
+                name.atoms(out);
+            }
+            ExprKind::Struct(path, fields, base) => {
+                path.atoms(out);
+                out.push(Atom::Len(fields.len()));
+                for (name, value) in fields {
+                    name.atoms(out);
+                    value.atoms(out);
+                }
+                base.atoms(out);
+            }
+            ExprKind::Array(elems) => elems.atoms(out),
+            ExprKind::Cast(expr, ty) => {
+                expr.atoms(out);
+                ty.atoms(out);
+            }
+            ExprKind::Loop(body, label) => {
+                body.atoms(out);
+                label.atoms(out);
+            }
+            ExprKind::While(cond, body, label) => {
+                cond.atoms(out);
+                body.atoms(out);
+                label.atoms(out);
+            }
+            ExprKind::LabeledBlock(body, label) => {
+                body.atoms(out);
+                label.atoms(out);
+            }
+            ExprKind::For(pat, iter, body, label) => {
+                pat.atoms(out);
+                iter.atoms(out);
+                body.atoms(out);
+                label.atoms(out);
+            }
+            ExprKind::Break(label, value) => {
+                label.atoms(out);
+                value.atoms(out);
+            }
+            ExprKind::Continue(label) => label.atoms(out),
+            ExprKind::Range(start, end) => {
+                start.atoms(out);
+                end.atoms(out);
+            }
+            ExprKind::MacroCall(name, delim, tokens) => {
+                name.atoms(out);
+                out.push(Atom::Tag(*delim as u16));
+                out.push(Atom::Len(tokens.len()));
+                for token in tokens {
+                    out.push(Atom::Token(token.kind.to_string()));
+                }
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                scrutinee.atoms(out);
+                out.push(Atom::Len(arms.len()));
+                for arm in arms {
+                    arm.pat.atoms(out);
+                    arm.guard.atoms(out);
+                    arm.body.atoms(out);
+                }
+            }
+        }
+    }
+}
+
+impl Structure for Block {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        self.stmts.atoms(out);
+    }
+}
+
+impl Structure for Stmt {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        match &self.kind {
+            StmtKind::Expr(expr) => {
+                out.push(Atom::Tag(0));
+                expr.atoms(out);
+            }
+            StmtKind::Semi(expr) => {
+                out.push(Atom::Tag(1));
+                expr.atoms(out);
+            }
+            StmtKind::Let(let_stmt) => {
+                out.push(Atom::Tag(2));
+                let_stmt.pat.atoms(out);
+                out.push(Atom::Bool(let_stmt.mutable));
+                let_stmt.ty.atoms(out);
+                let_stmt.init.atoms(out);
+            }
+            StmtKind::Item(item) => {
+                out.push(Atom::Tag(3));
+                item.atoms(out);
+            }
+        }
+    }
+}
+
+impl Structure for Item {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        match &self.kind {
+            ItemKind::Func(func) => {
+                out.push(Atom::Tag(0));
+                func.atoms(out);
+            }
+            ItemKind::Struct(struct_item) => {
+                out.push(Atom::Tag(1));
+                struct_item.ident.atoms(out);
+                out.push(Atom::Len(struct_item.fields.len()));
+                for (name, ty) in &struct_item.fields {
+                    name.atoms(out);
+                    ty.atoms(out);
+                }
+            }
+            ItemKind::ExternBlock(block) => {
+                out.push(Atom::Tag(2));
+                block.funcs.atoms(out);
+            }
+            ItemKind::Mod(module) => {
+                out.push(Atom::Tag(3));
+                module.name.atoms(out);
+                module.items.atoms(out);
+            }
+            ItemKind::Use(use_item) => {
+                out.push(Atom::Tag(4));
+                use_item.path.atoms(out);
+                out.push(Atom::Bool(use_item.is_glob));
+            }
+            ItemKind::Static(static_item) => {
+                out.push(Atom::Tag(5));
+                static_item.name.atoms(out);
+                out.push(Atom::Bool(static_item.mutable));
+                static_item.ty.atoms(out);
+                static_item.value.atoms(out);
+            }
+        }
+    }
+}
+
+impl Structure for Func {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        self.name.atoms(out);
+        self.generics.atoms(out);
+        out.push(Atom::Len(self.params.len()));
+        for (name, ty) in &self.params {
+            name.atoms(out);
+            ty.atoms(out);
+        }
+        self.ret_ty.atoms(out);
+        self.ext.atoms(out);
+        out.push(Atom::Bool(self.is_const));
+        self.body.atoms(out);
+    }
+}
+
+impl Structure for Pattern {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        match &self.kind {
+            PatternKind::Wild => out.push(Atom::Tag(0)),
+            PatternKind::Ident(ident) => {
+                out.push(Atom::Tag(1));
+                ident.atoms(out);
+            }
+            PatternKind::Lit(lit) => {
+                out.push(Atom::Tag(2));
+                lit.atoms(out);
+            }
+            PatternKind::Range(start, end, inclusive) => {
+                out.push(Atom::Tag(3));
+                start.atoms(out);
+                end.atoms(out);
+                out.push(Atom::Bool(*inclusive));
+            }
+            PatternKind::Tuple(pats) => {
+                out.push(Atom::Tag(4));
+                pats.atoms(out);
+            }
+            PatternKind::Array(pats) => {
+                out.push(Atom::Tag(5));
+                pats.atoms(out);
+            }
+            PatternKind::Rest => out.push(Atom::Tag(6)),
+        }
+    }
+}
+
+impl Structure for Ty {
+    fn atoms<'a>(&'a self, out: &mut Vec<Atom<'a>>) {
+        match &self.kind {
+            TyKind::Unit => out.push(Atom::Tag(0)),
+            TyKind::Bool => out.push(Atom::Tag(1)),
+            TyKind::I32 => out.push(Atom::Tag(2)),
+            TyKind::Str => out.push(Atom::Tag(3)),
+            TyKind::Array(elem, n) => {
+                out.push(Atom::Tag(4));
+                elem.atoms(out);
+                out.push(Atom::Int(*n as u128));
+            }
+            TyKind::Adt(path, generic_args) => {
+                out.push(Atom::Tag(5));
+                path.atoms(out);
+                generic_args.atoms(out);
+            }
+            TyKind::Ref(region, inner) => {
+                out.push(Atom::Tag(6));
+                region.atoms(out);
+                inner.atoms(out);
+            }
+            TyKind::ConstPtr(inner) => {
+                out.push(Atom::Tag(7));
+                inner.atoms(out);
+            }
+            TyKind::Never => out.push(Atom::Tag(8)),
+            TyKind::Fn(params, ret) => {
+                out.push(Atom::Tag(9));
+                params.atoms(out);
+                ret.atoms(out);
+            }
+            TyKind::Tuple(elems) => {
+                out.push(Atom::Tag(10));
+                elems.atoms(out);
+            }
+            TyKind::ImplTrait(path) => {
+                out.push(Atom::Tag(11));
+                path.atoms(out);
+            }
+            TyKind::DynTrait(path) => {
+                out.push(Atom::Tag(12));
+                path.atoms(out);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_structural_hash() {
+    use crate::parse::Parser;
+    use std::hash::DefaultHasher;
+
+    let parse = |src: &str| Parser::from_source(src).parse_expr().unwrap();
+    let hash = |expr: &Expr| {
+        let mut hasher = DefaultHasher::new();
+        Structural(expr).hash(&mut hasher);
+        hasher.finish()
+    };
+
+    // different ids and spans
+    let mut parser = Parser::from_source("1 + 2");
+    let a = parser.parse_expr().unwrap();
+    parser.set_source("  1+2");
+    let b = parser.parse_expr().unwrap();
+    assert_ne!(a.id, b.id);
+    assert!(Structural(&a) == Structural(&b));
+    assert_eq!(hash(&a), hash(&b));
+
+    assert!(Structural(&a) != Structural(&parse("1 + 3")));
+    assert!(Structural(&a) != Structural(&parse("1 - 2")));
+    // parentheses are discarded by default
+    assert!(Structural(&a) == Structural(&parse("(1 + 2)")));
+    // lengths are recorded, so nesting is distinguished
+    assert!(Structural(&parse("[[1], 2]")) != Structural(&parse("[[1, 2]]")));
+
+    let a = parse("match x { [y, ..] => f::<i32>(y), _ => { let z = 0; z } }");
+    let b = parse("match x { [y, ..] => f::<i32>(y), _ => { let z = 0; z } }");
+    assert!(Structural(&a) == Structural(&b));
+    assert_eq!(hash(&a), hash(&b));
+}
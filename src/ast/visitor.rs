@@ -22,6 +22,8 @@ pub trait Visitor<'ctx>: Sized {
     fn visit_block_post(&mut self, _block: &'ctx Block) {}
     fn visit_type(&mut self, _ty: &'ctx Ty) {}
     fn visit_type_post(&mut self, _ty: &'ctx Ty) {}
+    fn visit_pattern(&mut self, _pat: &'ctx Pattern) {}
+    fn visit_pattern_post(&mut self, _pat: &'ctx Pattern) {}
     fn visit_path(&mut self, _path: &'ctx Path) {}
 }
 
@@ -33,6 +35,10 @@ pub fn go_func<'ctx, V: Visitor<'ctx>>(v: &mut V, func: &'ctx Func) {
     walk_func(v, func);
 }
 
+pub fn go_expr<'ctx, V: Visitor<'ctx>>(v: &mut V, expr: &'ctx Expr) {
+    walk_expr(v, expr);
+}
+
 fn walk_crate<'ctx, V: Visitor<'ctx>>(v: &mut V, krate: &'ctx Crate) {
     v.visit_crate(krate);
     for item in &krate.items {
@@ -56,6 +62,8 @@ fn walk_item<'ctx, V: Visitor<'ctx>>(v: &mut V, item: &'ctx Item) {
         ItemKind::Mod(module) => {
             walk_module_item(v, module);
         }
+        // TODO: walk static items once they are supported
+        ItemKind::Use(_) | ItemKind::Static(_) => (),
     }
     v.visit_item_post(item);
 }
@@ -104,7 +112,8 @@ fn walk_stmt<'ctx, V: Visitor<'ctx>>(v: &mut V, stmt: &'ctx Stmt) {
         StmtKind::Semi(expr) => walk_expr(v, expr),
         StmtKind::Expr(expr) => walk_expr(v, expr),
         StmtKind::Let(let_stmt) => {
-            let LetStmt { ident: _, ty, init } = let_stmt;
+            let LetStmt { pat, ty, init, .. } = let_stmt;
+            walk_pattern(v, pat);
             if let Some(ty) = ty {
                 walk_type(v, ty);
             }
@@ -112,6 +121,8 @@ fn walk_stmt<'ctx, V: Visitor<'ctx>>(v: &mut V, stmt: &'ctx Stmt) {
                 walk_expr(v, init);
             }
         }
+        // TODO: walk items in blocks once they are supported
+        StmtKind::Item(_) => (),
     }
     v.visit_stmt_post(stmt);
 }
@@ -126,21 +137,68 @@ fn walk_type<'ctx, V: Visitor<'ctx>>(v: &mut V, ty: &'ctx Ty) {
         TyKind::Ref(_region, inner_ty) => {
             walk_type(v, inner_ty);
         }
-        TyKind::Adt(path) => {
+        TyKind::Adt(path, args) => {
             walk_path(v, path);
+            for arg in args {
+                walk_type(v, arg);
+            }
         }
         TyKind::ConstPtr(referent) => {
             walk_type(v, &referent);
         }
+        TyKind::Fn(params, ret_ty) => {
+            for param in params {
+                walk_type(v, param);
+            }
+            walk_type(v, ret_ty);
+        }
+        TyKind::Tuple(elems) => {
+            for elem in elems {
+                walk_type(v, elem);
+            }
+        }
+        // trait bounds are not resolved
+        TyKind::ImplTrait(_) | TyKind::DynTrait(_) => (),
     }
     v.visit_type_post(ty);
 }
 
+fn walk_pattern<'ctx, V: Visitor<'ctx>>(v: &mut V, pat: &'ctx Pattern) {
+    v.visit_pattern(pat);
+    match &pat.kind {
+        PatternKind::Wild | PatternKind::Ident(_) | PatternKind::Rest => (),
+        PatternKind::Lit(lit) => {
+            walk_expr(v, lit);
+        }
+        PatternKind::Range(start, end, _) => {
+            if let Some(start) = start {
+                walk_expr(v, start);
+            }
+            if let Some(end) = end {
+                walk_expr(v, end);
+            }
+        }
+        PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+            for pat in pats {
+                walk_pattern(v, pat);
+            }
+        }
+    }
+    v.visit_pattern_post(pat);
+}
+
 fn walk_expr<'ctx, V: Visitor<'ctx>>(v: &mut V, expr: &'ctx Expr) {
     v.visit_expr(expr);
     match &expr.kind {
-        ExprKind::NumLit(_) | ExprKind::BoolLit(_) | ExprKind::StrLit(_) | ExprKind::Unit => (),
-        ExprKind::Binary(_, l, r) | ExprKind::Assign(l, r) => {
+        ExprKind::NumLit(..)
+        | ExprKind::FloatLit(..)
+        | ExprKind::BoolLit(_)
+        | ExprKind::StrLit(_)
+        | ExprKind::CharLit(_)
+        | ExprKind::ByteLit(_)
+        | ExprKind::ByteStrLit(_)
+        | ExprKind::Unit => (),
+        ExprKind::Binary(_, l, r) | ExprKind::Assign(l, r, _) | ExprKind::AssignOp(_, l, r, _) => {
             walk_expr(v, l);
             walk_expr(v, r);
         }
@@ -150,11 +208,14 @@ fn walk_expr<'ctx, V: Visitor<'ctx>>(v: &mut V, expr: &'ctx Expr) {
         ExprKind::Path(path) => {
             walk_path(v, path);
         }
-        ExprKind::Return(inner) => {
+        ExprKind::Paren(inner) => {
             walk_expr(v, inner);
         }
-        ExprKind::Call(func, args) => {
+        ExprKind::Call(func, type_args, args) => {
             walk_expr(v, func);
+            for type_arg in type_args {
+                walk_type(v, type_arg);
+            }
             for arg in args {
                 walk_expr(v, arg);
             }
@@ -176,11 +237,14 @@ fn walk_expr<'ctx, V: Visitor<'ctx>>(v: &mut V, expr: &'ctx Expr) {
         ExprKind::Field(receiver, _field) => {
             walk_expr(v, receiver);
         }
-        ExprKind::Struct(path, fds) => {
+        ExprKind::Struct(path, fds, base) => {
             walk_path(v, path);
             for (_ident, expr) in fds {
                 walk_expr(v, expr);
             }
+            if let Some(base) = base {
+                walk_expr(v, base);
+            }
         }
         ExprKind::Array(elems) => {
             for e in elems {
@@ -191,13 +255,54 @@ fn walk_expr<'ctx, V: Visitor<'ctx>>(v: &mut V, expr: &'ctx Expr) {
             walk_expr(v, inner);
             walk_type(v, ty);
         }
+        ExprKind::Try(inner) | ExprKind::Await(inner) => {
+            walk_expr(v, inner);
+        }
+        ExprKind::Match(scrutinee, arms) => {
+            walk_expr(v, scrutinee);
+            for arm in arms {
+                walk_pattern(v, &arm.pat);
+                if let Some(guard) = &arm.guard {
+                    walk_expr(v, guard);
+                }
+                walk_expr(v, &arm.body);
+            }
+        }
+        ExprKind::Loop(body, _) | ExprKind::LabeledBlock(body, _) => {
+            walk_block(v, body);
+        }
+        ExprKind::While(cond, body, _label) => {
+            walk_expr(v, cond);
+            walk_block(v, body);
+        }
+        ExprKind::For(pat, iter, body, _label) => {
+            walk_pattern(v, pat);
+            walk_expr(v, iter);
+            walk_block(v, body);
+        }
+        ExprKind::Return(value) | ExprKind::Break(_, value) => {
+            if let Some(value) = value {
+                walk_expr(v, value);
+            }
+        }
+        ExprKind::Continue(_label) => (),
+        // tokens of macro invocations are not parsed
+        ExprKind::MacroCall(..) => (),
+        ExprKind::Range(start, end) => {
+            if let Some(start) = start {
+                walk_expr(v, start);
+            }
+            if let Some(end) = end {
+                walk_expr(v, end);
+            }
+        }
     }
     v.visit_expr_post(expr);
 }
 
 fn walk_block<'ctx, V: Visitor<'ctx>>(v: &mut V, block: &'ctx Block) {
     v.visit_block(block);
-    for stmt in &block.stmts {
+    for stmt in block {
         walk_stmt(v, stmt);
     }
     v.visit_block_post(block);
@@ -206,3 +311,189 @@ fn walk_block<'ctx, V: Visitor<'ctx>>(v: &mut V, block: &'ctx Block) {
 fn walk_path<'ctx, V: Visitor<'ctx>>(v: &mut V, path: &'ctx Path) {
     v.visit_path(path);
 }
+
+/// Mutable AST visitor for transformations.
+/// Unlike `Visitor`, each method walks the children by default, so that implementors can
+/// do work around the walk (e.g. to track scopes) or skip it by overriding the method.
+pub trait MutVisitor: Sized {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+    fn visit_type(&mut self, ty: &mut Ty) {
+        walk_type_mut(self, ty);
+    }
+    fn visit_pattern(&mut self, pat: &mut Pattern) {
+        walk_pattern_mut(self, pat);
+    }
+    fn visit_path(&mut self, _path: &mut Path) {}
+}
+
+pub fn walk_stmt_mut<V: MutVisitor>(v: &mut V, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        StmtKind::Semi(expr) | StmtKind::Expr(expr) => v.visit_expr(expr),
+        StmtKind::Let(let_stmt) => {
+            let LetStmt { pat, ty, init, .. } = let_stmt;
+            v.visit_pattern(pat);
+            if let Some(ty) = ty {
+                v.visit_type(ty);
+            }
+            if let Some(init) = init {
+                v.visit_expr(init);
+            }
+        }
+        // TODO: walk items in blocks once they are supported
+        StmtKind::Item(_) => (),
+    }
+}
+
+pub fn walk_type_mut<V: MutVisitor>(v: &mut V, ty: &mut Ty) {
+    match &mut ty.kind {
+        TyKind::Bool | TyKind::I32 | TyKind::Never | TyKind::Str | TyKind::Unit => (),
+        TyKind::Array(inner, _) | TyKind::Ref(_, inner) | TyKind::ConstPtr(inner) => {
+            v.visit_type(inner);
+        }
+        TyKind::Adt(path, args) => {
+            v.visit_path(path);
+            for arg in args {
+                v.visit_type(arg);
+            }
+        }
+        TyKind::Fn(params, ret_ty) => {
+            for param in params {
+                v.visit_type(param);
+            }
+            v.visit_type(ret_ty);
+        }
+        TyKind::Tuple(elems) => {
+            for elem in elems {
+                v.visit_type(elem);
+            }
+        }
+        // trait bounds are not resolved
+        TyKind::ImplTrait(_) | TyKind::DynTrait(_) => (),
+    }
+}
+
+pub fn walk_pattern_mut<V: MutVisitor>(v: &mut V, pat: &mut Pattern) {
+    match &mut pat.kind {
+        PatternKind::Wild | PatternKind::Ident(_) | PatternKind::Rest => (),
+        PatternKind::Lit(lit) => v.visit_expr(lit),
+        PatternKind::Range(start, end, _) => {
+            for bound in [start, end].into_iter().flatten() {
+                v.visit_expr(bound);
+            }
+        }
+        PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+            for pat in pats {
+                v.visit_pattern(pat);
+            }
+        }
+    }
+}
+
+pub fn walk_expr_mut<V: MutVisitor>(v: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::NumLit(..)
+        | ExprKind::FloatLit(..)
+        | ExprKind::BoolLit(_)
+        | ExprKind::StrLit(_)
+        | ExprKind::CharLit(_)
+        | ExprKind::ByteLit(_)
+        | ExprKind::ByteStrLit(_)
+        | ExprKind::Unit
+        | ExprKind::Continue(_) => (),
+        // tokens of macro invocations are not parsed
+        ExprKind::MacroCall(..) => (),
+        ExprKind::Binary(_, l, r)
+        | ExprKind::Assign(l, r, _)
+        | ExprKind::AssignOp(_, l, r, _)
+        | ExprKind::Index(l, r) => {
+            v.visit_expr(l);
+            v.visit_expr(r);
+        }
+        ExprKind::Unary(_, inner)
+        | ExprKind::Paren(inner)
+        | ExprKind::Field(inner, _)
+        | ExprKind::Try(inner)
+        | ExprKind::Await(inner) => v.visit_expr(inner),
+        ExprKind::Path(path) => v.visit_path(path),
+        ExprKind::Call(func, type_args, args) => {
+            v.visit_expr(func);
+            for type_arg in type_args {
+                v.visit_type(type_arg);
+            }
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Block(block) | ExprKind::Loop(block, _) | ExprKind::LabeledBlock(block, _) => {
+            v.visit_block(block);
+        }
+        ExprKind::If(cond, then, els) => {
+            v.visit_expr(cond);
+            v.visit_expr(then);
+            if let Some(els) = els {
+                v.visit_expr(els);
+            }
+        }
+        ExprKind::Struct(path, fields, base) => {
+            v.visit_path(path);
+            for (_ident, field) in fields {
+                v.visit_expr(field);
+            }
+            if let Some(base) = base {
+                v.visit_expr(base);
+            }
+        }
+        ExprKind::Array(elems) => {
+            for elem in elems {
+                v.visit_expr(elem);
+            }
+        }
+        ExprKind::Cast(inner, ty) => {
+            v.visit_expr(inner);
+            v.visit_type(ty);
+        }
+        ExprKind::Match(scrutinee, arms) => {
+            v.visit_expr(scrutinee);
+            for arm in arms {
+                v.visit_pattern(&mut arm.pat);
+                if let Some(guard) = &mut arm.guard {
+                    v.visit_expr(guard);
+                }
+                v.visit_expr(&mut arm.body);
+            }
+        }
+        ExprKind::While(cond, body, _) => {
+            v.visit_expr(cond);
+            v.visit_block(body);
+        }
+        ExprKind::For(pat, iter, body, _) => {
+            v.visit_pattern(pat);
+            v.visit_expr(iter);
+            v.visit_block(body);
+        }
+        ExprKind::Return(value) | ExprKind::Break(_, value) => {
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        ExprKind::Range(start, end) => {
+            for bound in [start, end].into_iter().flatten() {
+                v.visit_expr(bound);
+            }
+        }
+    }
+}
+
+pub fn walk_block_mut<V: MutVisitor>(v: &mut V, block: &mut Block) {
+    for stmt in &mut block.stmts {
+        v.visit_stmt(stmt);
+    }
+}
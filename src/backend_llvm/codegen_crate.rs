@@ -1,7 +1,7 @@
 use std::rc::Rc;
 use super::{Codegen, LLValue};
 use crate::{
-    ast::{Block, Crate, ExternBlock, Func, Item, ItemKind, LetStmt, Stmt, StmtKind},
+    ast::{Block, Crate, ExternBlock, Func, Item, ItemKind, LetStmt, PatternKind, Stmt, StmtKind},
     backend_llvm::{
         frame::{compute_frame, LocalKind},
         llvm::{LLReg, LLTy},
@@ -22,7 +22,7 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
             ItemKind::Func(func) => {
                 self.gen_func(func)?;
             }
-            ItemKind::Struct(_) => (),
+            ItemKind::Struct(_) | ItemKind::Use(_) | ItemKind::Static(_) => (),
             ItemKind::ExternBlock(ext_block) => self.gen_external_block(ext_block)?,
             ItemKind::Mod(module) => {
                 for inner_item in &module.items {
@@ -133,7 +133,10 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
 
         let body_val = self.gen_block(body)?;
 
-        if !self.ctx.get_type(body.id).is_never() {
+        if self.ctx.get_type(body.id).is_never() {
+            // the last bb may follow `return`
+            println!("\tunreachable");
+        } else {
             if ret_llty.eval_to_ptr() {
                 let LLValue::Reg(body_val_reg) = body_val else {
                     panic!("ICE");
@@ -155,7 +158,7 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
 
     pub fn gen_block(&mut self, block: &'gen Block) -> Result<LLValue, ()> {
         let mut last_stmt_val = None;
-        for stmt in &block.stmts {
+        for stmt in block {
             last_stmt_val = Some(self.gen_stmt(stmt)?);
         }
         let ret = last_stmt_val.unwrap_or(LLValue::Imm(LLImm::Void));
@@ -170,17 +173,29 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                 LLValue::Imm(LLImm::Void)
             }
             StmtKind::Expr(expr) => self.eval_expr(expr)?,
-            StmtKind::Let(LetStmt { ident, ty: _, init }) => {
-                let binding = self.ctx.get_binding(ident).unwrap();
-                let local = self.peek_frame().get_local(&binding);
-
-                if let Some(init) = init && local.kind == LocalKind::Ptr {
-                    let ptr = self.gen_binding_lval(&binding).unwrap();
-                    // assign initializer
-                    self.initialize_memory_with_value(&ptr, init)?;
+            StmtKind::Let(LetStmt { pat, init, .. }) => {
+                match &pat.kind {
+                    PatternKind::Ident(ident) => {
+                        let binding = self.ctx.get_binding(ident).unwrap();
+                        let local = self.peek_frame().get_local(&binding);
+
+                        if let Some(init) = init && local.kind == LocalKind::Ptr {
+                            let ptr = self.gen_binding_lval(&binding).unwrap();
+                            // assign initializer
+                            self.initialize_memory_with_value(&ptr, init)?;
+                        }
+                    }
+                    // `let _ = init;` evaluates the initializer only for its side effects
+                    PatternKind::Wild => {
+                        if let Some(init) = init {
+                            self.eval_expr(init)?;
+                        }
+                    }
+                    _ => panic!("ICE: patterns in let statements are rejected by typeck"),
                 }
                 LLValue::Imm(LLImm::Void)
             }
+            StmtKind::Item(_) => panic!("ICE: items in blocks are rejected by typeck"),
         };
         println!("; Finished stmt `{}`", stmt.span.to_snippet());
         Ok(val)
@@ -1,7 +1,8 @@
-use super::{Codegen, LLValue};
+use super::{frame::LoopLabels, Codegen, LLValue};
 use crate::{
-    ast::{self, Expr, ExprKind, NodeId},
+    ast::{self, Block, Expr, ExprKind, NodeId},
     backend_llvm::{llvm::LLConst, LLImm, LLReg, LLTy},
+    span::Ident,
 };
 use std::rc::Rc;
 
@@ -17,10 +18,10 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
         }
 
         let ret: LLValue = match &expr.kind {
-            ExprKind::NumLit(n) => {
-                // FIXME: Panics in some cases
-                let casted: i32 = (*n).try_into().unwrap();
-                LLValue::Imm(LLImm::I32(casted))
+            ExprKind::NumLit(n, _) => {
+                // typeck ensures that the literal fits in i32 (or is `2^31` negated by unary minus).
+                // `2^31` wraps to `i32::MIN` and then `0 - i32::MIN` is `i32::MIN`.
+                LLValue::Imm(LLImm::I32(*n as i32))
             }
             ExprKind::BoolLit(b) => {
                 if *b {
@@ -29,6 +30,8 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                     LLValue::Imm(LLImm::I1(false))
                 }
             }
+            // chars are represented as unicode scalar values
+            ExprKind::CharLit(c) => LLValue::Imm(LLImm::I32(*c as i32)),
             ExprKind::Unit => LLValue::Imm(LLImm::Void),
             ExprKind::StrLit(s) => {
                 let llcons = Rc::new(LLConst {
@@ -53,10 +56,30 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                     LLValue::Reg(LLReg::new(reg, inner_val.llty()))
                 }
                 ast::UnOp::Plus => self.eval_expr(inner)?,
+                ast::UnOp::Not => {
+                    let inner_val = self.eval_expr(inner)?;
+                    // bool variables are loaded as i8
+                    let inner_val = self.bool_to_i1(inner_val);
+                    assert!(inner_val.llty().is_integer() || *inner_val.llty() == LLTy::I1);
+                    let reg = self.peek_frame_mut().get_fresh_reg();
+                    // flip all bits (`true` for i1)
+                    println!(
+                        "\t{reg} = xor {} {}, -1",
+                        inner_val.llty().to_string(),
+                        inner_val.to_string()
+                    );
+                    LLValue::Reg(LLReg::new(reg, inner_val.llty()))
+                }
+                ast::UnOp::Deref | ast::UnOp::Ref => {
+                    panic!("ICE: references are rejected by typeck")
+                }
             },
+            ExprKind::Binary(ast::BinOp::And, lhs, rhs) => self.gen_lazy_and(lhs, rhs)?,
             ExprKind::Binary(binop, lhs, rhs) => {
                 let l = self.eval_expr(lhs)?;
                 let r = self.eval_expr(rhs)?;
+                // bool variables are loaded as i8, but bool literals are i1
+                let (l, r) = (self.bool_to_i1(l), self.bool_to_i1(r));
                 // checks if rhs and lhs have the same type
                 assert_eq!(self.ctx.get_type(lhs.id), self.ctx.get_type(rhs.id));
                 let rhs_lhs_llty = self.ty_to_llty(&self.ctx.get_type(lhs.id));
@@ -91,7 +114,8 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                         rhs_lhs_llty
                     }
                     ast::BinOp::Eq => {
-                        assert!(rhs_lhs_llty.is_integer());
+                        // bools are compared as i1
+                        assert!(rhs_lhs_llty.is_integer() || *l.llty() == LLTy::I1);
                         println!(
                             "\t{reg_name} = icmp eq {}, {}",
                             l.to_string_with_type(),
@@ -100,7 +124,8 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                         LLTy::I1
                     }
                     ast::BinOp::Ne => {
-                        assert!(rhs_lhs_llty.is_integer());
+                        // bools are compared as i1
+                        assert!(rhs_lhs_llty.is_integer() || *l.llty() == LLTy::I1);
                         println!(
                             "\t{reg_name} = icmp ne {}, {}",
                             l.to_string_with_type(),
@@ -126,10 +151,16 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                         );
                         LLTy::I1
                     }
+                    ast::BinOp::And => unreachable!(),
                 };
                 LLValue::Reg(LLReg::new(reg_name, Rc::new(llty)))
             }
-            ExprKind::Return(inner) => {
+            ExprKind::Return(None) => {
+                println!("\tret void");
+                self.start_unreachable_bb();
+                LLValue::Imm(LLImm::Void)
+            }
+            ExprKind::Return(Some(inner)) => {
                 // pointer if sret used, o.w. value
                 let inner_val_or_ptr = self.eval_expr(inner)?;
                 if let Some(sret_reg) = self.peek_frame().get_sret_reg() {
@@ -143,9 +174,11 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                     // value
                     println!("\tret {}", inner_val_or_ptr.to_string_with_type());
                 }
+                self.start_unreachable_bb();
                 LLValue::Imm(LLImm::Void)
             }
             ExprKind::Block(block) => self.gen_block(block)?,
+            ExprKind::Paren(inner) => self.eval_expr(inner)?,
             // identifiers may not be allocated on memory
             ExprKind::Path(path) => LLValue::Reg(self.load_path(path)?),
             // arrays and structs are always allocated on memory
@@ -154,7 +187,7 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                 let rval = self.load_ptr(&lval)?;
                 LLValue::Reg(rval)
             }
-            ExprKind::Assign(lhs, rhs) => {
+            ExprKind::Assign(lhs, rhs, _) => {
                 let rhs_llty = self.ty_to_llty(&self.ctx.get_type(rhs.id));
 
                 if rhs_llty.eval_to_ptr() {
@@ -175,11 +208,36 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
 
                 LLValue::Imm(LLImm::Void)
             }
-            ExprKind::Call(func, args) => self.gen_call_expr(expr.id, func, args)?,
-            ExprKind::If(cond, then, els) => {
-                let res = self.gen_if_expr(cond, then, els)?;
-                LLValue::Reg(res.0)
+            ExprKind::AssignOp(binop, lhs, rhs, _) => {
+                let rhs_val = self.eval_expr(rhs)?;
+                // the place is computed once (e.g. `a[f()] += 1` calls `f` once)
+                let lhs_ptr = self.gen_lval(lhs)?;
+                let lhs_val = self.load_ptr(&lhs_ptr)?;
+                assert!(lhs_val.llty.is_integer());
+                let inst = match binop {
+                    ast::BinOp::Add => "add",
+                    ast::BinOp::Sub => "sub",
+                    ast::BinOp::Mul => "mul",
+                    _ => panic!("ICE: `{:?}=` is not a compound assignment", binop),
+                };
+                let reg_name = self.peek_frame_mut().get_fresh_reg();
+                println!(
+                    "\t{reg_name} = {inst} {}, {}",
+                    lhs_val.to_string_with_type(),
+                    rhs_val.to_string()
+                );
+                println!(
+                    "\tstore {} {}, {} {}",
+                    lhs_val.llty.to_string(),
+                    reg_name,
+                    lhs_ptr.llty.to_string(),
+                    lhs_ptr.name,
+                );
+
+                LLValue::Imm(LLImm::Void)
             }
+            ExprKind::Call(func, _, args) => self.gen_call_expr(expr.id, func, args)?,
+            ExprKind::If(cond, then, els) => self.gen_if_expr(cond, then, els)?,
             ExprKind::Cast(inner, _) => {
                 // ref: https://doc.rust-lang.org/reference/expressions/operator-expr.html#type-cast-expressions
                 let to = self.ty_to_llty(&self.ctx.get_type(expr.id));
@@ -189,88 +247,239 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                     _ => panic!("ICE"),
                 }
             }
+            ExprKind::Loop(body, label) => {
+                if let Some(result) = self.gen_loop_expr(expr.id, body, label)? {
+                    LLValue::Reg(self.load_ptr(&result)?)
+                } else {
+                    LLValue::Imm(LLImm::Void)
+                }
+            }
+            ExprKind::While(cond, body, label) => {
+                let cond_label = self.get_fresh_label_name();
+                let body_label = self.get_fresh_label_name();
+                let end_label = self.get_fresh_label_name();
+                println!("\tbr label %{}", cond_label);
+                println!("{}:\t; While", cond_label);
+                let cond_val = self.eval_expr(cond)?;
+                let cond_val = self.bool_to_i1(cond_val);
+                println!(
+                    "\tbr {}, label %{}, label %{}",
+                    cond_val.to_string_with_type(),
+                    body_label,
+                    end_label
+                );
+                println!("{}:\t; WhileBody", body_label);
+                self.peek_frame_mut().push_loop(LoopLabels {
+                    label: label.as_ref().map(|l| l.symbol),
+                    continue_label: cond_label.clone(),
+                    break_label: end_label.clone(),
+                    result: None,
+                });
+                self.gen_block(body)?;
+                self.peek_frame_mut().pop_loop();
+                println!("\tbr label %{}", cond_label);
+                println!("{}:\t; EndWhile", end_label);
+                LLValue::Imm(LLImm::Void)
+            }
+            ExprKind::Break(label, value) => {
+                let target = self.peek_frame().find_loop(label).unwrap();
+                if let Some(value) = value {
+                    let value_ty = self.ctx.get_type(value.id);
+                    match &target.result {
+                        Some(result) if !value_ty.is_never() => {
+                            if self.ty_to_llty(&value_ty).eval_to_ptr() {
+                                let value_ptr = self.gen_lval(value)?;
+                                self.memcpy(result, &value_ptr);
+                            } else {
+                                let value = self.eval_expr(value)?;
+                                println!(
+                                    "\tstore {}, {}",
+                                    value.to_string_with_type(),
+                                    result.to_string_with_type()
+                                );
+                            }
+                        }
+                        // value has void-like type
+                        _ => {
+                            self.eval_expr(value)?;
+                        }
+                    }
+                }
+                println!("\tbr label %{}", target.break_label);
+                self.start_unreachable_bb();
+                LLValue::Imm(LLImm::Void)
+            }
+            ExprKind::Continue(label) => {
+                let target = self.peek_frame().find_loop(label).unwrap();
+                println!("\tbr label %{}", target.continue_label);
+                self.start_unreachable_bb();
+                LLValue::Imm(LLImm::Void)
+            }
             ExprKind::Struct(..) | ExprKind::Array(..) => panic!("ICE"),
+            ExprKind::Range(..) => panic!("ICE: range is rejected by typeck"),
+            ExprKind::MacroCall(..) => panic!("ICE: macro call is rejected by typeck"),
+            ExprKind::Try(..) => panic!("ICE: `?` is rejected by typeck"),
+            ExprKind::Await(..) => panic!("ICE: `.await` is rejected by typeck"),
+            ExprKind::FloatLit(..) => panic!("ICE: float literal is rejected by typeck"),
+            ExprKind::ByteLit(_) | ExprKind::ByteStrLit(_) => {
+                panic!("ICE: byte literal is rejected by typeck")
+            }
+            ExprKind::Match(..) => panic!("ICE: match is rejected by typeck"),
+            ExprKind::For(..) => panic!("ICE: for loop is rejected by typeck"),
+            ExprKind::LabeledBlock(..) => panic!("ICE: labeled block is rejected by typeck"),
         };
 
         println!("; Finishes expr `{}`", expr.span.to_snippet());
         Ok(ret)
     }
 
-    /// Generate code for if expression. Returns the label of the last bb.
+    /// Generate code for if expression
     pub fn gen_if_expr(
         &mut self,
         cond: &'gen Expr,
         then: &'gen Expr,
         els: &'gen Option<Box<Expr>>,
-    ) -> Result<(Rc<LLReg>, String), ()> {
+    ) -> Result<LLValue, ()> {
         let cond = self.eval_expr(cond)?;
+        let cond = self.bool_to_i1(cond);
         let then_label = self.get_fresh_label_name();
         let endif_label = self.get_fresh_label_name();
-        let mut else_result = None;
-        // if `else if` is found, this contains its endif label
-        let mut else_label = None;
-
-        if let Some(els) = els {
-            else_label = Some(self.get_fresh_label_name());
-            println!(
-                "\tbr {}, label %{}, label %{}",
-                cond.to_string_with_type(),
-                then_label,
-                else_label.as_ref().unwrap()
-            );
-            // else_label:
-            println!("{}:\t; Else", else_label.as_ref().unwrap());
-            // else block
-            else_result = match &els.kind {
-                ExprKind::If(cond2, then2, else2) => {
-                    let res = self.gen_if_expr(cond2, then2, else2)?;
-                    else_label = Some(res.1);
-                    Some(LLValue::Reg(res.0))
-                }
-                ExprKind::Block(_) => Some(self.eval_expr(els)?),
-                _ => panic!("ICE: else must be if expr or block expr"),
-            };
-            println!("\tbr label %{}", endif_label);
+        let else_label = if els.is_some() {
+            self.get_fresh_label_name()
         } else {
-            println!(
-                "\tbr {}, label %{}, label %{}",
-                cond.to_string_with_type(),
-                then_label,
-                endif_label
-            );
-        }
+            endif_label.clone()
+        };
+        println!(
+            "\tbr {}, label %{}, label %{}",
+            cond.to_string_with_type(),
+            then_label,
+            else_label
+        );
+
+        // incoming values and labels of the phi node
+        let mut incomings = vec![];
+
         // then_label:
-        println!("{}:\t;Then", then_label);
+        println!("{}:\t; Then", then_label);
         // then block
         let then_result = self.eval_expr(then)?;
-        println!("\tbr label %{}", endif_label);
+        if let Some(label) = self.gen_branch_end(then, &endif_label) {
+            incomings.push((then_result, label));
+        }
+
+        if let Some(els) = els {
+            // else_label:
+            println!("{}:\t; Else", else_label);
+            // else block or `else if`
+            let else_result = self.eval_expr(els)?;
+            if let Some(label) = self.gen_branch_end(els, &endif_label) {
+                incomings.push((else_result, label));
+            }
+        }
 
         println!("{}:\t; Endif", endif_label);
-        if let Some(else_result) = else_result {
-            let reg_name = self.peek_frame_mut().get_fresh_reg();
-            println!(
-                "\t{} = phi {} [{}, %{}], [{}, %{}]",
-                reg_name,
-                then_result.llty().to_string(),
-                then_result.to_string(),
-                then_label,
-                else_result.to_string(),
-                else_label.as_ref().unwrap(),
-            );
-            Ok((LLReg::new(reg_name, then_result.llty()), endif_label))
+        // if-expression without else block has `()` type
+        if els.is_none() || incomings.is_empty() || incomings[0].0.llty().is_void() {
+            return Ok(LLValue::Imm(LLImm::Void));
+        }
+
+        let reg_name = self.peek_frame_mut().get_fresh_reg();
+        let llty = incomings[0].0.llty();
+        let incomings: Vec<String> = incomings
+            .iter()
+            .map(|(val, label)| format!("[{}, %{}]", val.to_string(), label))
+            .collect();
+        println!(
+            "\t{} = phi {} {}",
+            reg_name,
+            llty.to_string(),
+            incomings.join(", ")
+        );
+        Ok(LLValue::Reg(LLReg::new(reg_name, llty)))
+    }
+
+    /// Generate code for `lhs && rhs`, where rhs is evaluated only if lhs is true
+    fn gen_lazy_and(&mut self, lhs: &'gen Expr, rhs: &'gen Expr) -> Result<LLValue, ()> {
+        let l = self.eval_expr(lhs)?;
+        let l = self.bool_to_i1(l);
+        let lhs_label = self.get_fresh_label_name();
+        let rhs_label = self.get_fresh_label_name();
+        let end_label = self.get_fresh_label_name();
+        // lhs may consist of several bbs, so branch from a bb whose label is known
+        println!("\tbr label %{}", lhs_label);
+        println!("{}:\t; AndLhs", lhs_label);
+        println!(
+            "\tbr {}, label %{}, label %{}",
+            l.to_string_with_type(),
+            rhs_label,
+            end_label
+        );
+
+        println!("{}:\t; AndRhs", rhs_label);
+        let r = self.eval_expr(rhs)?;
+        let r = self.bool_to_i1(r);
+        let rhs_end_label = self.gen_branch_end(rhs, &end_label);
+
+        println!("{}:\t; EndAnd", end_label);
+        let reg_name = self.peek_frame_mut().get_fresh_reg();
+        let mut incomings = format!("[false, %{}]", lhs_label);
+        if let Some(label) = rhs_end_label {
+            incomings += &format!(", [{}, %{}]", r.to_string(), label);
+        }
+        println!("\t{} = phi i1 {}", reg_name, incomings);
+        Ok(LLValue::Reg(LLReg::new(reg_name, Rc::new(LLTy::I1))))
+    }
+
+    /// Jump from the end of a branch to `dest`.
+    /// Returns the label of the bb which jumps, or `None` if the branch never reaches its end.
+    fn gen_branch_end(&mut self, branch: &'gen Expr, dest: &str) -> Option<String> {
+        if self.ctx.get_type(branch.id).is_never() {
+            println!("\tunreachable");
+            None
         } else {
-            let reg_name = self.peek_frame_mut().get_fresh_reg();
-            println!(
-                "\t{} = phi {} [{}, %{}]",
-                reg_name,
-                then_result.llty().to_string(),
-                then_result.to_string(),
-                then_label,
-            );
-            Ok((LLReg::new(reg_name, then_result.llty()), endif_label))
+            // The branch may consist of several bbs, so jump via a bb whose label is known.
+            let label = self.get_fresh_label_name();
+            println!("\tbr label %{}", label);
+            println!("{}:", label);
+            println!("\tbr label %{}", dest);
+            Some(label)
         }
     }
 
+    /// Generate code for loop expression.
+    /// Returns the pointer to the value of `break` if the loop has a value.
+    pub fn gen_loop_expr(
+        &mut self,
+        node_id: NodeId,
+        body: &'gen Block,
+        label: &Option<Ident>,
+    ) -> Result<Option<Rc<LLReg>>, ()> {
+        let loop_label = self.get_fresh_label_name();
+        let end_label = self.get_fresh_label_name();
+        let result = self.peek_frame().get_ptr_to_temporary(node_id);
+
+        println!("\tbr label %{}", loop_label);
+        println!("{}:\t; Loop", loop_label);
+        self.peek_frame_mut().push_loop(LoopLabels {
+            label: label.as_ref().map(|l| l.symbol),
+            continue_label: loop_label.clone(),
+            break_label: end_label.clone(),
+            result: result.clone(),
+        });
+        self.gen_block(body)?;
+        self.peek_frame_mut().pop_loop();
+        println!("\tbr label %{}", loop_label);
+        println!("{}:\t; EndLoop", end_label);
+        Ok(result)
+    }
+
+    /// Start a new bb after a terminator such as `ret` and `br`.
+    /// Code following `return`, `break`, or `continue` is put in the bb.
+    fn start_unreachable_bb(&mut self) {
+        let label = self.get_fresh_label_name();
+        println!("{}:\t; Unreachable", label);
+    }
+
     pub fn gen_call_expr(
         &mut self,
         // node id of this call expression
@@ -278,17 +487,21 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
         func: &'gen Expr,
         args: &'gen [Expr],
     ) -> Result<LLValue, ()> {
+        let mut func = func;
+        while let ExprKind::Paren(inner) = &func.kind {
+            func = inner;
+        }
         let ExprKind::Path(path) = &func.kind else {
-            // TODO:
-            todo!();
+            panic!("ICE: non-path callee is rejected by typeck");
         };
 
         let mut arg_vals = vec![];
         for arg in args {
             let arg_ty = &self.ctx.get_type(arg.id);
             let llty = self.ty_to_llty(arg_ty);
+            // unit arguments (e.g. `f(x = 1)`) are evaluated but not passed
+            let arg_val = self.eval_expr(arg)?;
             if !llty.is_void() {
-                let arg_val = self.eval_expr(arg)?;
                 arg_vals.push(arg_val);
             }
         }
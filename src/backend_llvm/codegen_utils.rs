@@ -47,14 +47,20 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                 let struct_ptr = self.gen_lval(strct)?;
                 self.gen_field_lval(&struct_ptr, field)
             }
-            ExprKind::Struct(_, _) | ExprKind::Array(_) => {
+            ExprKind::Struct(..) | ExprKind::Array(_) => {
                 let ptr = self.peek_frame().get_ptr_to_temporary(expr.id).unwrap();
                 self.initialize_memory_with_value(&ptr, expr)?;
                 Ok(ptr)
             }
             ExprKind::If(cond, then, els) => {
-                let res = self.gen_if_expr(cond, then, els)?;
-                Ok(res.0)
+                let LLValue::Reg(res) = self.gen_if_expr(cond, then, els)? else {
+                    panic!("ICE");
+                };
+                Ok(res)
+            }
+            ExprKind::Loop(body, label) => {
+                let result = self.gen_loop_expr(expr.id, body, label)?;
+                Ok(result.unwrap())
             }
             ExprKind::Block(block) => {
                 let llval = self.gen_block(block)?;
@@ -64,7 +70,8 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
                     panic!("ICE");
                 }
             }
-            ExprKind::Call(func, args) => {
+            ExprKind::Paren(inner) => self.gen_lval(inner),
+            ExprKind::Call(func, _, args) => {
                 let must_be_void = self.gen_call_expr(expr.id, func, args)?;
                 assert_eq!(*must_be_void.llty(), LLTy::Void);
                 let temp = self.peek_frame().get_ptr_to_temporary(expr.id).unwrap();
@@ -87,7 +94,19 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
             .get_adt_cpath()
             .unwrap();
         let lladt = self.get_lladt(&adt_name).unwrap();
-        let field_index = lladt.get_field_index(&field.symbol).unwrap();
+        let field_index = lladt.get_field_index(field.symbol).unwrap();
+        Ok(self.gen_field_lval_at(struct_ptr_reg, field_index))
+    }
+
+    // struct_ptr_reg: STRUCT*, index of s.field: FIELD_LLTY -> returns FIELD_LLTY*
+    fn gen_field_lval_at(&mut self, struct_ptr_reg: &Rc<LLReg>, field_index: usize) -> Rc<LLReg> {
+        let adt_name = struct_ptr_reg
+            .llty
+            .peel_ptr()
+            .unwrap()
+            .get_adt_cpath()
+            .unwrap();
+        let lladt = self.get_lladt(&adt_name).unwrap();
         // `type { T1, T2, T3 }*` => `Tn*`
         let ret_llty = LLTy::Ptr(Rc::clone(&lladt.fields[field_index].1));
 
@@ -100,7 +119,7 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
             field_index
         );
 
-        Ok(LLReg::new(new_reg, Rc::new(ret_llty)))
+        LLReg::new(new_reg, Rc::new(ret_llty))
     }
 
     // ident: LLTY* (i.e. LocalKind::Ptr) -> LLTY*
@@ -140,6 +159,17 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
         Ok(LLReg::new(new_reg, derefed_ty))
     }
 
+    /// bools are stored as i8 => truncate it to i1
+    /// other values (including i1) are returned as they are
+    pub fn bool_to_i1(&mut self, val: LLValue) -> LLValue {
+        if *val.llty() != LLTy::I8 {
+            return val;
+        }
+        let new_reg = self.peek_frame_mut().get_fresh_reg();
+        println!("\t{} = trunc {} to i1", new_reg, val.to_string_with_type());
+        LLValue::Reg(LLReg::new(new_reg, Rc::new(LLTy::I1)))
+    }
+
     /// initializer of let statement
     pub fn initialize_memory_with_value(
         &mut self,
@@ -150,20 +180,34 @@ impl<'gen, 'ctx> Codegen<'gen, 'ctx> {
         assert_eq!(*ptr.llty.peel_ptr().unwrap(), init_llty);
 
         match &init.kind {
-            ExprKind::Struct(path, fields) => {
+            ExprKind::Struct(path, fields, base) => {
                 let binding = self.ctx.resolve_path(path).unwrap();
                 let lladt = self.get_lladt(&binding.cpath).unwrap();
                 for (field, fd_expr) in fields {
-                    if lladt.get_field_index(&field.symbol).is_none() {
+                    if lladt.get_field_index(field.symbol).is_none() {
                         continue;
                     }
                     let fd_ptr = self.gen_field_lval(ptr, field)?;
                     self.initialize_memory_with_value(&fd_ptr, fd_expr)?
                 }
+                // as in Rust, the base is evaluated after the fields given explicitly,
+                // and only the rest of the fields are copied from it
+                if let Some(base) = base {
+                    let base_ptr = self.gen_lval(base)?;
+                    for (i, (name, _)) in lladt.fields.iter().enumerate() {
+                        if fields.iter().any(|(field, _)| field.symbol == *name) {
+                            continue;
+                        }
+                        let dist = self.gen_field_lval_at(ptr, i);
+                        let src = self.gen_field_lval_at(&base_ptr, i);
+                        self.memcpy(&dist, &src);
+                    }
+                }
             }
             ExprKind::Array(_) => {
                 todo!()
             }
+            ExprKind::Paren(inner) => self.initialize_memory_with_value(ptr, inner)?,
             _ => {
                 if init_llty.eval_to_ptr() {
                     // TODO:
@@ -1,9 +1,9 @@
 use super::{Codegen, LLReg, LLTy};
 use crate::{
-    ast::{self, StmtKind},
+    ast::{self, PatternKind, StmtKind},
     middle::ty::Ty,
     resolve::{Binding, BindingKind},
-    span::Ident,
+    span::{Ident, Symbol},
 };
 use std::{collections::HashMap, rc::Rc};
 
@@ -23,10 +23,24 @@ pub struct Frame {
     /// Can be used only for non-lvalue array and structs
     temporary_regs: HashMap<ast::NodeId, Rc<LLReg>>,
     sret_reg: Option<Rc<LLReg>>,
+    /// Stack of loops enclosing the current expression
+    loops: Vec<Rc<LoopLabels>>,
     next_reg: usize,
     next_tmp_reg: usize,
 }
 
+/// Basic blocks `break` and `continue` jump to
+#[derive(Debug)]
+pub struct LoopLabels {
+    pub label: Option<Symbol>,
+    /// Label of bb `continue` jumps to
+    pub continue_label: String,
+    /// Label of bb `break` jumps to
+    pub break_label: String,
+    /// Memory where the value of `break` is stored
+    pub result: Option<Rc<LLReg>>,
+}
+
 #[derive(Debug)]
 pub struct Local {
     pub kind: LocalKind,
@@ -53,6 +67,7 @@ impl Frame {
             locals: HashMap::new(),
             temporary_regs: HashMap::new(),
             sret_reg: None,
+            loops: vec![],
             next_reg: 0,
             next_tmp_reg: 0,
         }
@@ -82,6 +97,27 @@ impl Frame {
         &self.temporary_regs
     }
 
+    pub fn push_loop(&mut self, labels: LoopLabels) {
+        self.loops.push(Rc::new(labels));
+    }
+
+    pub fn pop_loop(&mut self) {
+        self.loops.pop();
+    }
+
+    /// Find the loop which `break` or `continue` with the label refers to
+    pub fn find_loop(&self, label: &Option<Ident>) -> Option<Rc<LoopLabels>> {
+        let found = if let Some(label) = label {
+            self.loops
+                .iter()
+                .rev()
+                .find(|l| l.label == Some(label.symbol))
+        } else {
+            self.loops.last()
+        };
+        found.map(Rc::clone)
+    }
+
     pub fn get_fresh_reg(&mut self) -> String {
         let i = self.next_reg;
         self.next_reg += 1;
@@ -160,14 +196,20 @@ impl<'ctx: 'a, 'a> ast::visitor::Visitor<'ctx> for VisitFrame<'_, '_, '_> {
     fn visit_stmt(&mut self, stmt: &'ctx ast::Stmt) {
         match &stmt.kind {
             StmtKind::Let(let_stmt) => {
-                let binding = self.codegen.ctx.get_binding(&let_stmt.ident).unwrap();
+                let ident = match &let_stmt.pat.kind {
+                    PatternKind::Ident(ident) => ident,
+                    // `let _` does not allocate a local variable
+                    PatternKind::Wild => return,
+                    _ => panic!("ICE: patterns in let statements are rejected by typeck"),
+                };
+                let binding = self.codegen.ctx.get_binding(ident).unwrap();
                 let var_ty = self.codegen.ctx.lookup_name_type(&binding).unwrap();
 
                 if self.codegen.ty_to_llty(&var_ty).is_void() {
                     // cannot `alloca void` so register void-like (i.e. `()`) local variables as `LocalKind::Value`
-                    self.add_local(&let_stmt.ident, &var_ty, binding.kind, LocalKind::Value);
+                    self.add_local(ident, &var_ty, binding.kind, LocalKind::Value);
                 } else {
-                    self.add_local(&let_stmt.ident, &var_ty, binding.kind, LocalKind::Ptr);
+                    self.add_local(ident, &var_ty, binding.kind, LocalKind::Ptr);
                 }
             }
             _ => (),
@@ -177,12 +219,18 @@ impl<'ctx: 'a, 'a> ast::visitor::Visitor<'ctx> for VisitFrame<'_, '_, '_> {
     fn visit_expr(&mut self, expr: &'ctx ast::Expr) {
         if matches!(
             &expr.kind,
-            ast::ExprKind::Array(_) | ast::ExprKind::Struct(_, _)
-        ) || (matches!(&expr.kind, ast::ExprKind::Call(_, _))
+            ast::ExprKind::Array(_) | ast::ExprKind::Struct(..)
+        ) || (matches!(&expr.kind, ast::ExprKind::Call(..))
             && self
                 .codegen
                 .ty_to_llty(&self.codegen.ctx.get_type(expr.id))
                 .eval_to_ptr())
+            // memory to store values of `break`
+            || (matches!(&expr.kind, ast::ExprKind::Loop(_, _))
+                && !self
+                    .codegen
+                    .ty_to_llty(&self.codegen.ctx.get_type(expr.id))
+                    .is_void())
         {
             let ty = self.codegen.ctx.get_type(expr.id);
             self.add_temporary(expr.id, &ty);
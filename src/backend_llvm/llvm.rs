@@ -1,5 +1,6 @@
 use std::rc::Rc;
 use crate::resolve::CanonicalPath;
+use crate::span::Symbol;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum LLTy {
@@ -147,16 +148,16 @@ impl LLImm {
 }
 
 pub struct LLAdtDef {
-    pub fields: Vec<(Rc<String>, Rc<LLTy>)>,
+    pub fields: Vec<(Symbol, Rc<LLTy>)>,
 }
 
 impl LLAdtDef {
-    pub fn get_field_index(&self, field: &String) -> Option<usize> {
+    pub fn get_field_index(&self, field: Symbol) -> Option<usize> {
         let f = self
             .fields
             .iter()
             .enumerate()
-            .find(|(_, (fd, _))| **fd == *field);
+            .find(|(_, (fd, _))| *fd == field);
         f.map(|i| i.0)
     }
 }
@@ -166,3 +167,19 @@ pub struct LLConst {
     pub llty: Rc<LLTy>,
     pub string_lit: String,
 }
+
+impl LLConst {
+    /// Escape the string literal so that it can be printed in `c"..."`.
+    /// `"`, `\` and non-printable bytes are written as `\XX`.
+    pub fn escaped_string_lit(&self) -> String {
+        let mut s = String::new();
+        for b in self.string_lit.bytes() {
+            if b == b'"' || b == b'\\' || !(0x20..0x7f).contains(&b) {
+                s.push_str(&format!("\\{:02X}", b));
+            } else {
+                s.push(b as char);
+            }
+        }
+        s
+    }
+}
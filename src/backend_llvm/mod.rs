@@ -56,7 +56,7 @@ impl<'ctx, 'gen> Codegen<'ctx, 'gen> {
     fn ty_to_llty(&self, ty: &Ty) -> LLTy {
         match &ty.kind {
             TyKind::Unit => LLTy::Void,
-            TyKind::I32 => LLTy::I32,
+            TyKind::I32 | TyKind::Char => LLTy::I32,
             TyKind::Bool => LLTy::I8,
             TyKind::Array(elem_ty, n) => LLTy::Array(Rc::new(self.ty_to_llty(elem_ty)), *n),
             TyKind::Adt(name) => LLTy::Adt(Rc::clone(name)),
@@ -75,7 +75,7 @@ impl<'ctx, 'gen> Codegen<'ctx, 'gen> {
     fn construct_lladt(&self, adt: &AdtDef) -> LLAdtDef {
         let mut fields = vec![];
         for (fd, fd_ty) in &adt.fields {
-            fields.push((Rc::clone(fd), Rc::new(self.ty_to_llty(fd_ty))))
+            fields.push((*fd, Rc::new(self.ty_to_llty(fd_ty))))
         }
         LLAdtDef { fields }
     }
@@ -141,7 +141,7 @@ impl<'ctx, 'gen> Codegen<'ctx, 'gen> {
                 "{} = constant {} c\"{}\\00\"",
                 cons.name,
                 cons.llty.to_string(),
-                cons.string_lit
+                cons.escaped_string_lit()
             );
         }
 
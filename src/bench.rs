@@ -0,0 +1,95 @@
+//! Lexer benchmark (enabled by the `bench` feature)
+//! Run with `cargo run --release --features bench -- --bench-lex`
+use crate::lexer::{BinOp, Lexer, TokenKind};
+#[cfg(feature = "bench")]
+use std::time::{Duration, Instant};
+
+/// Generate a source with `n_funcs` functions and the kinds of its tokens (EOF is not included)
+pub fn generate_source(n_funcs: usize) -> (String, Vec<TokenKind>) {
+    let mut src = String::new();
+    let mut kinds = vec![];
+    let mut push = |text: &str, kind: TokenKind| {
+        src += text;
+        src.push(' ');
+        kinds.push(kind);
+    };
+    for i in 0..n_funcs {
+        let func = format!("func_{}", i);
+        let param = format!("x{}", i);
+        push("fn", TokenKind::Fn);
+        push(&func, TokenKind::Ident(func.clone()));
+        push("(", TokenKind::OpenParen);
+        push(&param, TokenKind::Ident(param.clone()));
+        push(":", TokenKind::Colon);
+        push("i32", TokenKind::I32);
+        push(")", TokenKind::CloseParen);
+        push("->", TokenKind::Arrow);
+        push("i32", TokenKind::I32);
+        push("{", TokenKind::OpenBrace);
+        push("let", TokenKind::Let);
+        push("_y", TokenKind::Ident("_y".to_string()));
+        push("=", TokenKind::Eq);
+        push(&param, TokenKind::Ident(param.clone()));
+        push("*", TokenKind::BinOp(BinOp::Star));
+        push(
+            &format!("{}u32", i),
+            TokenKind::NumLit(i as u128, Some("u32".to_string())),
+        );
+        push("+", TokenKind::BinOp(BinOp::Plus));
+        push("1_000_000", TokenKind::NumLit(1_000_000, None));
+        push(";", TokenKind::Semi);
+        push("if", TokenKind::If);
+        push("2.5f64", TokenKind::FloatLit(2.5, Some("f64".to_string())));
+        push("..", TokenKind::DotDot);
+        push(&format!("{}", i), TokenKind::NumLit(i as u128, None));
+        push("{", TokenKind::OpenBrace);
+        push("}", TokenKind::CloseBrace);
+        push("// comment\n", TokenKind::Comment("// comment".to_string()));
+        push("'a", TokenKind::Lifetime("a".to_string()));
+        push("_y", TokenKind::Ident("_y".to_string()));
+        push("}", TokenKind::CloseBrace);
+    }
+    (src, kinds)
+}
+
+/// Lex `src` `iterations` times after a warm-up and return the mean time per iteration
+#[cfg(feature = "bench")]
+pub fn bench_lex(src: &str, iterations: u32) -> Duration {
+    let lex = || {
+        let mut lexer = Lexer::new(src.to_string());
+        while lexer.skip_token().kind != TokenKind::Eof {}
+    };
+    // warm up
+    lex();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        lex();
+    }
+    start.elapsed() / iterations
+}
+
+#[cfg(feature = "bench")]
+pub fn run() {
+    let (src, _) = generate_source(10_000);
+    let mean = bench_lex(&src, 20);
+    println!(
+        "lex {} bytes: {:?}/iter ({:.1} MB/s)",
+        src.len(),
+        mean,
+        src.len() as f64 / mean.as_secs_f64() / 1e6
+    );
+}
+
+/// The single-pass scanning is compared with the old char-by-char one in
+/// `lexer::test_lex_golden`
+#[test]
+fn test_lex_large_source() {
+    let (src, kinds) = generate_source(1_000);
+    let mut lexer = Lexer::new(src);
+    lexer.set_keep_trivia(true);
+    for kind in kinds {
+        assert_eq!(lexer.skip_token().kind, kind);
+    }
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+    assert!(lexer.take_errors().is_empty());
+}
@@ -0,0 +1,88 @@
+use crate::ast::{BinOp, Expr, ExprKind, StmtKind, UnOp};
+
+/// Value known at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Int(i128),
+    Bool(bool),
+}
+
+/// Evaluate an expression consisting only of literals and arithmetic/comparison
+/// operators (e.g. initializers of `const` items).
+/// Returns None if the expression is not a constant (e.g. refers to a variable)
+/// or the evaluation fails (e.g. overflow).
+/// Note that values are not truncated to their types since types are not known here.
+/// NOTE: `const` items are not folded by the compiler yet, so this is only used by tests
+#[allow(dead_code)]
+pub fn const_eval(expr: &Expr) -> Option<Value> {
+    match &expr.kind {
+        ExprKind::NumLit(n, _) => Some(Value::Int(i128::try_from(*n).ok()?)),
+        ExprKind::BoolLit(b) => Some(Value::Bool(*b)),
+        ExprKind::Paren(inner) => const_eval(inner),
+        ExprKind::Unary(op, operand) => match (op, const_eval(operand)?) {
+            (UnOp::Plus, Value::Int(n)) => Some(Value::Int(n)),
+            (UnOp::Minus, Value::Int(n)) => Some(Value::Int(n.checked_neg()?)),
+            (UnOp::Not, Value::Int(n)) => Some(Value::Int(!n)),
+            (UnOp::Not, Value::Bool(b)) => Some(Value::Bool(!b)),
+            _ => None,
+        },
+        ExprKind::Binary(op, lhs, rhs) => {
+            let lhs = const_eval(lhs)?;
+            let rhs = const_eval(rhs)?;
+            eval_binary(op, lhs, rhs)
+        }
+        // `{ expr }`
+        ExprKind::Block(block) => match &block.stmts[..] {
+            [stmt] => {
+                let StmtKind::Expr(expr) = &stmt.kind else {
+                    return None;
+                };
+                const_eval(expr)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn eval_binary(op: &BinOp, lhs: Value, rhs: Value) -> Option<Value> {
+    match (lhs, rhs) {
+        (Value::Int(l), Value::Int(r)) => match op {
+            BinOp::Add => Some(Value::Int(l.checked_add(r)?)),
+            BinOp::Sub => Some(Value::Int(l.checked_sub(r)?)),
+            BinOp::Mul => Some(Value::Int(l.checked_mul(r)?)),
+            BinOp::Eq => Some(Value::Bool(l == r)),
+            BinOp::Ne => Some(Value::Bool(l != r)),
+            BinOp::Gt => Some(Value::Bool(l > r)),
+            BinOp::Lt => Some(Value::Bool(l < r)),
+            BinOp::And => None,
+        },
+        (Value::Bool(l), Value::Bool(r)) => match op {
+            BinOp::And => Some(Value::Bool(l && r)),
+            BinOp::Eq => Some(Value::Bool(l == r)),
+            BinOp::Ne => Some(Value::Bool(l != r)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn test_const_eval() {
+    use crate::parse::Parser;
+
+    let eval = |src: &str| const_eval(&Parser::from_source(src).parse_expr().unwrap());
+
+    assert_eq!(eval("2 * 3 + 1"), Some(Value::Int(7)));
+    assert_eq!(eval("-(1 - 4)"), Some(Value::Int(3)));
+    assert_eq!(eval("{ 2 * 3 } == 6"), Some(Value::Bool(true)));
+    assert_eq!(eval("true != false"), Some(Value::Bool(true)));
+    assert_eq!(eval("!(1 == 2)"), Some(Value::Bool(true)));
+    // not a constant
+    assert_eq!(eval("x + 1"), None);
+    assert_eq!(eval("f()"), None);
+    // type mismatch
+    assert_eq!(eval("true + 1"), None);
+    // statements in block
+    assert_eq!(eval("{ 1; 2 }"), None);
+}
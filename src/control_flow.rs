@@ -0,0 +1,127 @@
+use crate::{
+    ast::{self, Crate, ExprKind},
+    parse::{ParseError, Severity},
+};
+
+/// Check that `return`, `break` and `continue` appear in valid contexts
+/// (i.e. `return` in functions, and `break` and `continue` in loops).
+/// Labels are checked by typeck.
+pub fn check(krate: &Crate) -> Vec<ParseError> {
+    let mut checker = Checker {
+        contexts: vec![],
+        errors: vec![],
+    };
+    ast::visitor::go(&mut checker, krate);
+    checker.errors
+}
+
+#[derive(PartialEq, Eq)]
+enum Context {
+    Func,
+    Loop,
+    /// `'a: { ... }`, which only labeled `break` can exit
+    LabeledBlock,
+}
+
+struct Checker {
+    /// Contexts enclosing the node being visited (innermost last)
+    contexts: Vec<Context>,
+    errors: Vec<ParseError>,
+}
+
+impl Checker {
+    /// Find the innermost context satisfying `pred` in the current function
+    fn in_context(&self, pred: impl Fn(&Context) -> bool) -> bool {
+        for context in self.contexts.iter().rev() {
+            if pred(context) {
+                return true;
+            }
+            if *context == Context::Func {
+                break;
+            }
+        }
+        false
+    }
+}
+
+impl<'ctx> ast::visitor::Visitor<'ctx> for Checker {
+    fn visit_func(&mut self, _func: &'ctx ast::Func) {
+        self.contexts.push(Context::Func);
+    }
+
+    fn visit_func_post(&mut self, _func: &'ctx ast::Func) {
+        self.contexts.pop();
+    }
+
+    fn visit_expr(&mut self, expr: &'ctx ast::Expr) {
+        let ok = match &expr.kind {
+            ExprKind::Loop(..) | ExprKind::While(..) | ExprKind::For(..) => {
+                self.contexts.push(Context::Loop);
+                true
+            }
+            ExprKind::LabeledBlock(..) => {
+                self.contexts.push(Context::LabeledBlock);
+                true
+            }
+            ExprKind::Return(_) => self.contexts.contains(&Context::Func),
+            ExprKind::Break(Some(_), _) => {
+                self.in_context(|c| matches!(c, Context::Loop | Context::LabeledBlock))
+            }
+            ExprKind::Break(None, _) | ExprKind::Continue(_) => {
+                self.in_context(|c| *c == Context::Loop)
+            }
+            _ => true,
+        };
+        if !ok {
+            let keyword = match &expr.kind {
+                ExprKind::Return(_) => "return",
+                ExprKind::Break(..) => "break",
+                _ => "continue",
+            };
+            let place = if keyword == "return" {
+                "function"
+            } else {
+                "loop"
+            };
+            self.errors.push(ParseError {
+                msg: format!("`{}` outside of a {}", keyword, place),
+                span: expr.span.clone(),
+                severity: Severity::Error,
+            });
+        }
+    }
+
+    fn visit_expr_post(&mut self, expr: &'ctx ast::Expr) {
+        if matches!(
+            expr.kind,
+            ExprKind::Loop(..)
+                | ExprKind::While(..)
+                | ExprKind::For(..)
+                | ExprKind::LabeledBlock(..)
+        ) {
+            self.contexts.pop();
+        }
+    }
+}
+
+#[test]
+fn test_jump_outside_of_loop() {
+    use crate::parse::Parser;
+
+    let check_src = |src: &str| check(&Parser::from_source(src).parse_crate());
+
+    let errors = check_src("fn f() { break; }");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].msg, "`break` outside of a loop");
+    assert_eq!(errors[0].span.to_snippet(), "break");
+    let errors = check_src("fn f() { if true { continue } }");
+    assert_eq!(errors[0].msg, "`continue` outside of a loop");
+    // unlabeled `break` cannot exit labeled blocks
+    assert_eq!(check_src("fn f() { 'a: { break; } }").len(), 1);
+    assert_eq!(check_src("fn f() { 'a: { continue 'a; } }").len(), 1);
+
+    assert!(check_src("fn f() { loop { break; } }").is_empty());
+    assert!(check_src("fn f() { while true { if true { continue; } break } }").is_empty());
+    assert!(check_src("fn f() { for i in 0..3 { 'a: { break 'a; } break; } }").is_empty());
+    assert!(check_src("fn f() -> i32 { loop { return 1; } }").is_empty());
+}
@@ -1,13 +1,21 @@
 use crate::span::Span;
-use std::{collections::VecDeque, iter::Peekable, rc::Rc, vec::IntoIter};
+use crate::unescape::{unescape, unescape_bytes, UnescapeError};
+use alloc::{collections::VecDeque, rc::Rc};
+#[cfg(feature = "no_std")]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     // keywords
     I32,
@@ -26,15 +34,28 @@ pub enum TokenKind {
     Mod,
     Loop,
     While,
+    For,
+    In,
     Break,
     Continue,
     As,
     Mut,
     Const,
+    Use,
+    Static,
+    Match,
+    Where,
+    Impl,
+    Dyn,
+    Await,
     /// ->
     Arrow,
+    /// =>
+    FatArrow,
     /// !
     Bang,
+    /// ?
+    Question,
     Eq,
     /// ;
     Semi,
@@ -44,6 +65,10 @@ pub enum TokenKind {
     ColCol,
     Comma,
     Dot,
+    /// ..
+    DotDot,
+    /// ..=
+    DotDotEq,
     /// (
     OpenParen,
     /// )
@@ -57,20 +82,124 @@ pub enum TokenKind {
     /// }
     CloseBrace,
     BinOp(BinOp),
+    /// Compound assignment operator (e.g. `+=`)
+    BinOpEq(BinOp),
     /// Identifier
     Ident(String),
     Lifetime(String),
-    /// Number
-    NumLit(u32),
+    /// Number and its type suffix (e.g. `5u8`)
+    NumLit(u128, Option<String>),
+    /// Float and its type suffix (e.g. `1.0f32`)
+    FloatLit(f64, Option<String>),
     /// String literal
     StrLit(String),
+    /// Character literal
+    CharLit(char),
+    /// Byte literal (e.g. `b'a'`)
+    ByteLit(u8),
+    /// Byte string literal (e.g. `b"abc"`)
+    ByteStrLit(Vec<u8>),
+    /// Line or block comment including its delimiters (only if `Lexer::keep_trivia` is set)
+    Comment(String),
     /// EOF
     Eof,
     /// Unknown character
     Unknown,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Tokens are displayed as they appear in the source (e.g. `+`, `5`)
+impl core::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            TokenKind::I32 => "i32",
+            TokenKind::Str => "str",
+            TokenKind::Let => "let",
+            TokenKind::Return => "return",
+            TokenKind::Fn => "fn",
+            TokenKind::Bool => "bool",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::Struct => "struct",
+            TokenKind::Extern => "extern",
+            TokenKind::Unsafe => "unsafe",
+            TokenKind::Mod => "mod",
+            TokenKind::Loop => "loop",
+            TokenKind::While => "while",
+            TokenKind::For => "for",
+            TokenKind::In => "in",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::As => "as",
+            TokenKind::Mut => "mut",
+            TokenKind::Const => "const",
+            TokenKind::Use => "use",
+            TokenKind::Static => "static",
+            TokenKind::Match => "match",
+            TokenKind::Where => "where",
+            TokenKind::Impl => "impl",
+            TokenKind::Dyn => "dyn",
+            TokenKind::Await => "await",
+            TokenKind::Arrow => "->",
+            TokenKind::FatArrow => "=>",
+            TokenKind::Bang => "!",
+            TokenKind::Question => "?",
+            TokenKind::Eq => "=",
+            TokenKind::Semi => ";",
+            TokenKind::Colon => ":",
+            TokenKind::ColCol => "::",
+            TokenKind::Comma => ",",
+            TokenKind::Dot => ".",
+            TokenKind::DotDot => "..",
+            TokenKind::DotDotEq => "..=",
+            TokenKind::OpenParen => "(",
+            TokenKind::CloseParen => ")",
+            TokenKind::OpenBracket => "[",
+            TokenKind::CloseBracket => "]",
+            TokenKind::OpenBrace => "{",
+            TokenKind::CloseBrace => "}",
+            TokenKind::BinOp(op) => match op {
+                BinOp::Plus => "+",
+                BinOp::Minus => "-",
+                BinOp::Star => "*",
+                BinOp::Eq => "==",
+                BinOp::Ne => "!=",
+                BinOp::Gt => ">",
+                BinOp::Lt => "<",
+                BinOp::And => "&",
+                BinOp::AndAnd => "&&",
+            },
+            TokenKind::BinOpEq(op) => return write!(f, "{}=", TokenKind::BinOp(*op)),
+            // raw identifier (e.g. `r#fn`)
+            TokenKind::Ident(name) if keyword(name).is_some() => return write!(f, "r#{}", name),
+            TokenKind::Ident(name) => return write!(f, "{}", name),
+            TokenKind::Lifetime(name) => return write!(f, "'{}", name),
+            TokenKind::NumLit(n, suffix) => {
+                return write!(f, "{}{}", n, suffix.as_deref().unwrap_or(""));
+            }
+            TokenKind::FloatLit(n, suffix) => {
+                return write!(f, "{:?}{}", n, suffix.as_deref().unwrap_or(""));
+            }
+            TokenKind::StrLit(s) => return write!(f, "{:?}", s),
+            TokenKind::CharLit(c) => return write!(f, "{:?}", c),
+            TokenKind::ByteLit(b) => return write!(f, "b'{}'", b.escape_ascii()),
+            TokenKind::ByteStrLit(s) => return write!(f, "b\"{}\"", s.escape_ascii()),
+            TokenKind::Comment(comment) => return write!(f, "{}", comment),
+            TokenKind::Eof => "<eof>",
+            TokenKind::Unknown => "<unknown>",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl core::fmt::Display for Token {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinOp {
     Plus,
     Minus,
@@ -80,49 +209,190 @@ pub enum BinOp {
     Gt,
     Lt,
     And,
+    /// `&&`
+    AndAnd,
+}
+
+/// Error found while lexing. Collected by the lexer instead of being printed.
+#[derive(Debug, Clone)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub msg: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// Integer literal which does not fit in u128
+    IntTooLarge,
+    /// Any other invalid token
+    Invalid,
+}
+
+impl core::fmt::Display for LexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.msg)
+    }
 }
 
 fn is_space(c: char) -> bool {
     matches!(c, ' ' | '\r' | '\n')
 }
 
+/// Token kind of the keyword `s`, or `None` if `s` is not a keyword
+fn keyword(s: &str) -> Option<TokenKind> {
+    let kind = match s {
+        "i32" => TokenKind::I32,
+        "str" => TokenKind::Str,
+        "bool" => TokenKind::Bool,
+        "true" => TokenKind::True,
+        "false" => TokenKind::False,
+        "let" => TokenKind::Let,
+        "return" => TokenKind::Return,
+        "fn" => TokenKind::Fn,
+        "if" => TokenKind::If,
+        "else" => TokenKind::Else,
+        "struct" => TokenKind::Struct,
+        "extern" => TokenKind::Extern,
+        "unsafe" => TokenKind::Unsafe,
+        "mod" => TokenKind::Mod,
+        "loop" => TokenKind::Loop,
+        "while" => TokenKind::While,
+        "for" => TokenKind::For,
+        "in" => TokenKind::In,
+        "break" => TokenKind::Break,
+        "continue" => TokenKind::Continue,
+        "as" => TokenKind::As,
+        "mut" => TokenKind::Mut,
+        "const" => TokenKind::Const,
+        "use" => TokenKind::Use,
+        "static" => TokenKind::Static,
+        "match" => TokenKind::Match,
+        "where" => TokenKind::Where,
+        "impl" => TokenKind::Impl,
+        "dyn" => TokenKind::Dyn,
+        "await" => TokenKind::Await,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Type suffixes allowed on integer literals
+const INT_SUFFIXES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Type suffixes allowed on float literals
+const FLOAT_SUFFIXES: [&str; 2] = ["f32", "f64"];
+
 pub struct Lexer {
+    /// Byte offset where the current token starts
     token_start_pos: usize,
+    /// Index of the next char in `chars`
     current_pos: usize,
-    char_stream: Peekable<IntoIter<char>>,
+    /// Byte offset of the next char in `src`. Spans are byte offsets.
+    byte_pos: usize,
+    /// Chars of the source. Lookahead is done by indexing instead of cloning an iterator.
+    chars: Vec<char>,
     buffered_tokens: VecDeque<Token>,
     src: Rc<String>,
+    /// End of the last skipped token. Tokens buffered by peeking are not counted.
+    consumed_pos: usize,
+    /// Emit comments as `TokenKind::Comment` instead of discarding them
+    keep_trivia: bool,
+    /// Errors not taken by `take_errors` yet
+    errors: Vec<LexError>,
 }
 
 impl Lexer {
     pub fn new(src: String) -> Self {
-        let char_stream = src.chars().collect::<Vec<char>>().into_iter().peekable();
-        Lexer {
+        let chars = src.chars().collect::<Vec<char>>();
+        let mut lexer = Lexer {
             token_start_pos: 0,
             current_pos: 0,
-            char_stream,
+            byte_pos: 0,
+            chars,
             buffered_tokens: VecDeque::new(),
             src: Rc::new(src),
-        }
+            consumed_pos: 0,
+            keep_trivia: false,
+            errors: vec![],
+        };
+        lexer.skip_shebang();
+        lexer.consumed_pos = lexer.byte_pos;
+        lexer
+    }
+
+    pub fn set_keep_trivia(&mut self, keep_trivia: bool) {
+        self.keep_trivia = keep_trivia;
+    }
+
+    /// Byte offset in the source up to which tokens have been skipped
+    pub fn position(&self) -> usize {
+        self.consumed_pos
+    }
+
+    /// Source not consumed yet, including tokens which are only peeked
+    pub fn remaining(&self) -> &str {
+        &self.src[self.consumed_pos..]
+    }
+
+    /// Take errors found so far. An `Unknown` token is returned for each of them.
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        core::mem::take(&mut self.errors)
+    }
+
+    /// Report an error on the current token
+    fn error(&mut self, msg: String) {
+        self.error_with_kind(LexErrorKind::Invalid, msg);
+    }
+
+    /// Report an error on an escape in the current literal, whose content starts
+    /// `prefix_len` bytes after the start of the token (e.g. 2 for `b"`)
+    fn escape_error(&mut self, prefix_len: usize, err: UnescapeError) {
+        let content_start = self.token_start_pos + prefix_len;
+        self.errors.push(LexError {
+            kind: LexErrorKind::Invalid,
+            msg: err.msg,
+            span: Span::new(
+                content_start + err.pos,
+                content_start + err.end,
+                Rc::clone(&self.src),
+            ),
+        });
+    }
+
+    fn error_with_kind(&mut self, kind: LexErrorKind, msg: String) {
+        self.errors.push(LexError {
+            kind,
+            msg,
+            span: Span::new(self.token_start_pos, self.byte_pos, Rc::clone(&self.src)),
+        });
     }
 
     fn new_token(&mut self, kind: TokenKind) -> Token {
         let t = Token {
             kind,
-            span: Span::new(self.token_start_pos, self.current_pos, Rc::clone(&self.src)),
+            span: Span::new(self.token_start_pos, self.byte_pos, Rc::clone(&self.src)),
         };
-        self.token_start_pos = self.current_pos;
+        self.token_start_pos = self.byte_pos;
         t
     }
 
-    fn peek_input(&mut self) -> Option<&char> {
-        self.char_stream.peek()
+    fn peek_input(&self) -> Option<&char> {
+        self.chars.get(self.current_pos)
+    }
+
+    /// Look ahead `n` chars without consuming them (i.e. `peek_nth_input(0)` is the next char)
+    fn peek_nth_input(&self, n: usize) -> Option<char> {
+        self.chars.get(self.current_pos + n).copied()
     }
 
     fn skip_input(&mut self) -> Option<char> {
-        let c = self.char_stream.next();
-        if c.is_some() {
+        let c = self.peek_input().copied();
+        if let Some(c) = c {
             self.current_pos += 1;
+            self.byte_pos += c.len_utf8();
         }
         c
     }
@@ -130,17 +400,30 @@ impl Lexer {
     /*
     fn skip_input_by(&mut self, n: usize) {
         for _ in 0..n {
-            self.char_stream.next();
+            self.skip_input();
         }
     }
     */
 
     fn skip_whitespaces(&mut self) {
         while let Some(c) = self.peek_input()
-            && is_space(*c) {
+            && is_space(*c)
+        {
             self.skip_input();
         }
-        self.token_start_pos = self.current_pos;
+        self.token_start_pos = self.byte_pos;
+    }
+
+    /// Skip shebang line (e.g. `#!/usr/bin/env mini-rustc`) at the beginning of the source.
+    /// `#![...]` is an inner attribute, not a shebang.
+    fn skip_shebang(&mut self) {
+        if !self.src.starts_with("#!") || self.src[2..].trim_start().starts_with('[') {
+            return;
+        }
+        while let Some(c) = self.skip_input()
+            && c != '\n'
+        {}
+        self.token_start_pos = self.byte_pos;
     }
 
     /// Tokenize current token and set it to buffer
@@ -151,27 +434,33 @@ impl Lexer {
         let tokenize_res = if let Some(c) = self.peek_input() {
             match c {
                 'A'..='Z' | 'a'..='z' | '_' => self.parse_keyword_or_ident(),
-                '\'' => self.parse_lifetime(),
+                '\'' => {
+                    if self.is_char_lit_start() {
+                        self.parse_char_lit()
+                    } else {
+                        self.parse_lifetime()
+                    }
+                }
                 '0'..='9' => self.parse_number_lit(),
-                // skip comments
                 // TODO: div `/`
                 '/' => {
                     // skip first '/'
                     self.skip_input().unwrap();
-                    let c = self.skip_input();
-                    if c == Some('/') {
-                        loop {
-                            let c = self.peek_input();
-                            if matches!(c, Some('\n') | None) {
-                                self.skip_input();
-                                break;
-                            } else {
-                                self.skip_input();
-                            }
+                    let comment = match self.peek_input() {
+                        Some('/') => Some(self.parse_line_comment()),
+                        Some('*') => self.parse_block_comment(),
+                        _ => {
+                            self.skip_input();
+                            None
                         }
-                        return self.tokenize();
-                    } else {
-                        self.new_token(TokenKind::Unknown)
+                    };
+                    match comment {
+                        Some(_) if !self.keep_trivia => {
+                            // skip comments
+                            return self.tokenize();
+                        }
+                        Some(comment) => self.new_token(TokenKind::Comment(comment)),
+                        None => self.new_token(TokenKind::Unknown),
                     }
                 }
                 '=' => {
@@ -179,6 +468,9 @@ impl Lexer {
                     if self.peek_input() == Some(&'=') {
                         self.skip_input();
                         self.new_token(TokenKind::BinOp(BinOp::Eq))
+                    } else if self.peek_input() == Some(&'>') {
+                        self.skip_input();
+                        self.new_token(TokenKind::FatArrow)
                     } else {
                         self.new_token(TokenKind::Eq)
                     }
@@ -197,6 +489,9 @@ impl Lexer {
                     if self.peek_input() == Some(&'>') {
                         self.skip_input();
                         self.new_token(TokenKind::Arrow)
+                    } else if self.peek_input() == Some(&'=') {
+                        self.skip_input();
+                        self.new_token(TokenKind::BinOpEq(BinOp::Minus))
                     } else {
                         self.new_token(TokenKind::BinOp(BinOp::Minus))
                     }
@@ -211,7 +506,16 @@ impl Lexer {
                 }
                 '&' => {
                     self.skip_input();
-                    self.new_token(TokenKind::BinOp(BinOp::And))
+                    if self.peek_input() == Some(&'&') {
+                        self.skip_input();
+                        self.new_token(TokenKind::BinOp(BinOp::AndAnd))
+                    } else {
+                        self.new_token(TokenKind::BinOp(BinOp::And))
+                    }
+                }
+                '?' => {
+                    self.skip_input();
+                    self.new_token(TokenKind::Question)
                 }
                 ';' => {
                     self.skip_input();
@@ -232,7 +536,17 @@ impl Lexer {
                 }
                 '.' => {
                     self.skip_input();
-                    self.new_token(TokenKind::Dot)
+                    if self.peek_input() == Some(&'.') {
+                        self.skip_input();
+                        if self.peek_input() == Some(&'=') {
+                            self.skip_input();
+                            self.new_token(TokenKind::DotDotEq)
+                        } else {
+                            self.new_token(TokenKind::DotDot)
+                        }
+                    } else {
+                        self.new_token(TokenKind::Dot)
+                    }
                 }
                 '(' => {
                     self.skip_input();
@@ -260,17 +574,28 @@ impl Lexer {
                 }
                 '+' => {
                     self.skip_input();
-                    self.new_token(TokenKind::BinOp(BinOp::Plus))
+                    if self.peek_input() == Some(&'=') {
+                        self.skip_input();
+                        self.new_token(TokenKind::BinOpEq(BinOp::Plus))
+                    } else {
+                        self.new_token(TokenKind::BinOp(BinOp::Plus))
+                    }
                 }
                 '*' => {
                     self.skip_input();
-                    self.new_token(TokenKind::BinOp(BinOp::Star))
+                    if self.peek_input() == Some(&'=') {
+                        self.skip_input();
+                        self.new_token(TokenKind::BinOpEq(BinOp::Star))
+                    } else {
+                        self.new_token(TokenKind::BinOp(BinOp::Star))
+                    }
                 }
                 '\"' => self.parse_string_lit(),
                 // Unknown token
                 _ => {
-                    eprintln!("Unknwon token starting with: {:?}", c);
+                    let msg = format!("Unknown token starting with: {:?}", c);
                     self.skip_input();
+                    self.error(msg);
                     self.new_token(TokenKind::Unknown)
                 }
             }
@@ -286,129 +611,324 @@ impl Lexer {
     }
 
     fn parse_keyword_or_ident(&mut self) -> Token {
-        let mut chars = vec![];
-        while let Some(c) = &self.peek_input() {
-            match c {
-                'A'..='Z' | 'a'..='z' | '_' | '0'..='9' => {
-                    chars.push(**c);
-                    self.skip_input();
-                }
-                _ => break,
-            };
+        let s = self.parse_ident_chars();
+        // raw identifier: r#ident (e.g. `r#return` is an identifier, not a keyword)
+        if s == "r" && self.is_raw_ident_start() {
+            // skip '#'
+            self.skip_input();
+            let name = self.parse_ident_chars();
+            return self.new_token(TokenKind::Ident(name));
         }
-        let s: String = chars.into_iter().collect();
-        match s.as_str() {
-            "i32" => self.new_token(TokenKind::I32),
-            "str" => self.new_token(TokenKind::Str),
-            "bool" => self.new_token(TokenKind::Bool),
-            "true" => self.new_token(TokenKind::True),
-            "false" => self.new_token(TokenKind::False),
-            "let" => self.new_token(TokenKind::Let),
-            "return" => self.new_token(TokenKind::Return),
-            "fn" => self.new_token(TokenKind::Fn),
-            "if" => self.new_token(TokenKind::If),
-            "else" => self.new_token(TokenKind::Else),
-            "struct" => self.new_token(TokenKind::Struct),
-            "extern" => self.new_token(TokenKind::Extern),
-            "unsafe" => self.new_token(TokenKind::Unsafe),
-            "mod" => self.new_token(TokenKind::Mod),
-            "loop" => self.new_token(TokenKind::Loop),
-            "while" => self.new_token(TokenKind::While),
-            "break" => self.new_token(TokenKind::Break),
-            "continue" => self.new_token(TokenKind::Continue),
-            "as" => self.new_token(TokenKind::As),
-            "mut" => self.new_token(TokenKind::Mut),
-            "const" => self.new_token(TokenKind::Const),
-            _ => self.new_token(TokenKind::Ident(s)),
+        // raw string literal: r"..." or r#"..."#
+        if s == "r" && matches!(self.peek_input(), Some('"' | '#')) {
+            return self.parse_raw_string_lit();
+        }
+        // byte literal: b'a', or byte string literal: b"abc"
+        if s == "b" && matches!(self.peek_input(), Some('\'' | '"')) {
+            return self.parse_byte_lit();
+        }
+        match keyword(&s) {
+            Some(kind) => self.new_token(kind),
+            None => self.new_token(TokenKind::Ident(s)),
         }
     }
 
-    fn parse_lifetime(&mut self) -> Token {
+    /// Skip identifier chars and return them
+    fn parse_ident_chars(&mut self) -> String {
+        self.skip_input_while(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Skip chars while `pred` holds and return them
+    fn skip_input_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.current_pos;
+        let len = self.chars[start..].iter().take_while(|c| pred(**c)).count();
+        self.current_pos += len;
+        let s: String = self.chars[start..self.current_pos].iter().collect();
+        self.byte_pos += s.len();
+        s
+    }
+
+    /// Check if `#` followed by an identifier comes next (i.e. `r` is the prefix of a raw identifier)
+    fn is_raw_ident_start(&self) -> bool {
+        self.peek_nth_input(0) == Some('#')
+            && matches!(self.peek_nth_input(1), Some('A'..='Z' | 'a'..='z' | '_'))
+    }
+
+    /// Check if `'` starts a char literal (e.g. `'a'`, `'\n'`) rather than a lifetime (e.g. `'a`)
+    fn is_char_lit_start(&self) -> bool {
+        // first char is '\''
+        match self.peek_nth_input(1) {
+            Some('\\') => true,
+            Some(_) => self.peek_nth_input(2) == Some('\''),
+            None => false,
+        }
+    }
+
+    /// charLit ::= "'" (char | escape) "'"
+    /// See `unescape::unescape` for escapes
+    fn parse_char_lit(&mut self) -> Token {
         // skip '\''
         self.skip_input();
+        let Some(raw) = self.parse_escaped_chars('\'') else {
+            self.error("Unterminated character literal".to_string());
+            return self.new_token(TokenKind::Unknown);
+        };
+        let chars = match unescape(&raw) {
+            Ok(chars) => chars.chars().collect::<Vec<_>>(),
+            Err(err) => {
+                self.escape_error(1, err);
+                return self.new_token(TokenKind::Unknown);
+            }
+        };
+        let [c] = chars[..] else {
+            self.error("Character literal must contain exactly one character".to_string());
+            return self.new_token(TokenKind::Unknown);
+        };
+        self.new_token(TokenKind::CharLit(c))
+    }
+
+    /// byteLit ::= "b'" (asciiChar | byteEscape) "'"
+    /// byteStrLit ::= "b\"" (asciiChar | byteEscape)* "\""
+    /// See `unescape::unescape_bytes` for escapes
+    /// NOTE: first `b` is already skipped
+    fn parse_byte_lit(&mut self) -> Token {
+        let quote = self.skip_input().unwrap();
+        let Some(raw) = self.parse_escaped_chars(quote) else {
+            self.error("Unterminated byte literal".to_string());
+            return self.new_token(TokenKind::Unknown);
+        };
+        let bytes = match unescape_bytes(&raw) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.escape_error(2, err);
+                return self.new_token(TokenKind::Unknown);
+            }
+        };
+        if quote == '"' {
+            return self.new_token(TokenKind::ByteStrLit(bytes));
+        }
+        let [b] = bytes[..] else {
+            self.error("Byte literal must contain exactly one byte".to_string());
+            return self.new_token(TokenKind::Unknown);
+        };
+        self.new_token(TokenKind::ByteLit(b))
+    }
+
+    /// Skip chars until `quote` which is not escaped, and return them with escapes
+    /// left as they are. `quote` is skipped as well.
+    /// Returns None if a newline or EOF comes before `quote`.
+    fn parse_escaped_chars(&mut self, quote: char) -> Option<String> {
         let mut chars = vec![];
-        while let Some(c) = &self.peek_input() {
-            match c {
-                'A'..='Z' | 'a'..='z' | '_' | '0'..='9' => {
-                    chars.push(**c);
-                    self.skip_input();
+        loop {
+            match self.skip_input()? {
+                '\n' => return None,
+                '\\' => {
+                    chars.push('\\');
+                    chars.push(self.skip_input()?);
                 }
-                _ => break,
-            };
+                c if c == quote => break,
+                c => chars.push(c),
+            }
         }
-        if chars.is_empty() {
-            eprintln!(
+        Some(chars.into_iter().collect())
+    }
+
+    fn parse_lifetime(&mut self) -> Token {
+        // skip '\''
+        self.skip_input();
+        let s = self.parse_ident_chars();
+        if s.is_empty() {
+            let msg = format!(
                 "Expected lifetime identifier, but found {:?}",
                 self.peek_input()
             );
+            self.error(msg);
             self.new_token(TokenKind::Unknown)
         } else {
-            let s: String = chars.into_iter().collect();
             self.new_token(TokenKind::Lifetime(s))
         }
     }
 
-    fn parse_number_lit(&mut self) -> Token {
-        let mut chars = vec![];
-        while let Some(c) = &self.peek_input() {
-            match c {
-                '0'..='9' => {
-                    chars.push(**c);
-                    self.skip_input();
-                }
-                '_' => {
-                    self.skip_input();
-                    continue;
-                }
-                _ => break,
+    /// lineComment ::= "//" chars (until newline)
+    /// NOTE: first `/` is already skipped
+    fn parse_line_comment(&mut self) -> String {
+        let mut chars = vec!['/'];
+        while let Some(c) = self.peek_input()
+            && *c != '\n'
+        {
+            chars.push(*c);
+            self.skip_input();
+        }
+        chars.into_iter().collect()
+    }
+
+    /// blockComment ::= "/*" (chars | blockComment) "*/"
+    /// Block comments can be nested.
+    /// NOTE: first `/` is already skipped
+    fn parse_block_comment(&mut self) -> Option<String> {
+        let mut chars = vec!['/'];
+        let mut depth = 0;
+        let mut prev = '/';
+        loop {
+            let Some(c) = self.skip_input() else {
+                self.error("Unterminated block comment".to_string());
+                return None;
             };
+            chars.push(c);
+            if prev == '/' && c == '*' {
+                depth += 1;
+                // `/*/` does not close the comment
+                prev = ' ';
+                continue;
+            }
+            if prev == '*' && c == '/' {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                prev = ' ';
+                continue;
+            }
+            prev = c;
         }
+        Some(chars.into_iter().collect())
+    }
 
-        let s: String = chars.into_iter().collect();
-        let n = s.parse::<u32>().unwrap();
-        self.new_token(TokenKind::NumLit(n))
+    /// numberLit ::= digits suffix?
+    /// suffix ::= "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+    ///          | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+    ///          | "f32" | "f64"
+    /// floatLit ::= digits "." digits suffix? | digits ("f32" | "f64")
+    fn parse_number_lit(&mut self) -> Token {
+        let mut s = self.parse_digits();
+        // `1.5` is a float, but `1..2` and `1.foo()` are not
+        let mut is_float = false;
+        if self.peek_nth_input(0) == Some('.')
+            && self.peek_nth_input(1).is_some_and(|c| c.is_ascii_digit())
+        {
+            self.skip_input();
+            s.push('.');
+            s += &self.parse_digits();
+            is_float = true;
+        }
+
+        let mut suffix = None;
+        if let Some(c) = self.peek_input()
+            && c.is_ascii_alphabetic()
+        {
+            let s = self.parse_ident_chars();
+            let is_float_suffix = FLOAT_SUFFIXES.contains(&s.as_str());
+            if !is_float_suffix && (is_float || !INT_SUFFIXES.contains(&s.as_str())) {
+                self.error(format!("Invalid suffix `{}` for number literal", s));
+                return self.new_token(TokenKind::Unknown);
+            }
+            is_float |= is_float_suffix;
+            suffix = Some(s);
+        }
+
+        if is_float {
+            // digits with an optional fraction always parse as f64
+            return self.new_token(TokenKind::FloatLit(s.parse().unwrap(), suffix));
+        }
+        // range of the literal's type is checked by typeck
+        if let Ok(n) = s.parse::<u128>() {
+            self.new_token(TokenKind::NumLit(n, suffix))
+        } else {
+            self.error_with_kind(
+                LexErrorKind::IntTooLarge,
+                format!("Integer literal is too large: `{}`", s),
+            );
+            self.new_token(TokenKind::Unknown)
+        }
+    }
+
+    /// Skip digits and `_`s, and return the digits
+    fn parse_digits(&mut self) -> String {
+        let mut s = self.skip_input_while(|c| c.is_ascii_digit() || c == '_');
+        s.retain(|c| c != '_');
+        s
     }
 
+    /// stringLit ::= "\"" (char | escape)* "\""
+    /// See `unescape::unescape` for escapes
     fn parse_string_lit(&mut self) -> Token {
         // skip '"'
         self.skip_input();
+        let Some(raw) = self.parse_escaped_chars('"') else {
+            self.error("Unterminated string literal".to_string());
+            return self.new_token(TokenKind::Unknown);
+        };
+        match unescape(&raw) {
+            Ok(s) => self.new_token(TokenKind::StrLit(s)),
+            Err(err) => {
+                self.escape_error(1, err);
+                self.new_token(TokenKind::Unknown)
+            }
+        }
+    }
+
+    /// rawStringLit ::= "r" "#"* "\"" chars "\"" "#"*
+    /// The number of `#`s must match. No escape is processed.
+    /// NOTE: first `r` is already skipped
+    fn parse_raw_string_lit(&mut self) -> Token {
+        let mut n_hashes = 0;
+        while self.peek_input() == Some(&'#') {
+            self.skip_input();
+            n_hashes += 1;
+        }
+        if self.skip_input() != Some('"') {
+            self.error("Expected '\"' for raw string literal".to_string());
+            return self.new_token(TokenKind::Unknown);
+        }
 
         let mut chars = vec![];
-        while let Some(c) = &self.peek_input() {
-            match c {
-                '"' => {
-                    self.skip_input();
-                    break;
-                }
-                /*
-                TODO: escape
-                '\\' => {
-                    self.skip_input();
-                    let escp = match self.skip_input().unwrap() {
-                        'n' => '\n',
-                        c => {
-                            eprintln!("Escape \"\\{c}\" is not supported");
-                            return Err(());
-                        }
-                    };
-                    chars.push(escp);
+        loop {
+            match self.skip_input() {
+                Some('"') => {
+                    // count `#`s following the quote
+                    let mut n = 0;
+                    while n < n_hashes && self.peek_input() == Some(&'#') {
+                        self.skip_input();
+                        n += 1;
+                    }
+                    if n == n_hashes {
+                        break;
+                    }
+                    // not a terminator
+                    chars.push('"');
+                    chars.extend(core::iter::repeat_n('#', n));
                 }
-                */
-                '\n' => {
-                    eprintln!("Unexpected newline in string literal");
+                Some(c) => chars.push(c),
+                None => {
+                    self.error("Unterminated raw string literal".to_string());
                     return self.new_token(TokenKind::Unknown);
                 }
-                _ => {
-                    chars.push(**c);
-                    self.skip_input();
-                }
-            };
+            }
         }
 
         let s: String = chars.into_iter().collect();
         self.new_token(TokenKind::StrLit(s))
     }
 
+    /// Tokenize the whole source at once. The last token is always EOF.
+    /// Returns the error of the first invalid token.
+    pub fn tokenize_all(mut self) -> Result<Vec<Token>, LexError> {
+        let mut tokens = vec![];
+        loop {
+            self.tokenize();
+            let t = self.buffered_tokens.pop_front().unwrap();
+            match t.kind {
+                // an error is reported for each `Unknown` token
+                TokenKind::Unknown => return Err(self.errors.remove(0)),
+                TokenKind::Eof => {
+                    tokens.push(t);
+                    return Ok(tokens);
+                }
+                _ => tokens.push(t),
+            }
+        }
+    }
+
     pub fn peek_token(&mut self) -> &Token {
         // do tokenize if the current token is not buffered
         if self.buffered_tokens.is_empty() {
@@ -417,16 +937,47 @@ impl Lexer {
         &self.buffered_tokens[0]
     }
 
+    /// Look ahead `n` tokens without consuming them (i.e. `peek_nth(0)` is the next token)
+    pub fn peek_nth(&mut self, n: usize) -> &Token {
+        while self.buffered_tokens.len() <= n {
+            self.tokenize();
+        }
+        &self.buffered_tokens[n]
+    }
+
     /// Skip the current token. Keep returning EOF after lexer reached EOF
     pub fn skip_token(&mut self) -> Token {
         // make sure that the current token is buffered
         if self.buffered_tokens.is_empty() {
             self.tokenize();
         }
-        self.buffered_tokens.pop_front().unwrap()
+        let t = self.buffered_tokens.pop_front().unwrap();
+        self.consumed_pos = t.span.hi();
+        t
     }
 }
 
+/// Reconstruct source from tokens with a single space between tokens (e.g. `1+2` => `1 + 2`).
+/// Tokenizing the result gives the same tokens. EOF is ignored.
+pub fn tokens_to_string(tokens: &[Token]) -> String {
+    let mut s = String::new();
+    for t in tokens {
+        if t.kind == TokenKind::Eof {
+            continue;
+        }
+        if !s.is_empty() && !s.ends_with('\n') {
+            s.push(' ');
+        }
+        s.push_str(&t.to_string());
+        // line comments continue to the end of the line
+        if matches!(&t.kind, TokenKind::Comment(comment) if comment.starts_with("//")) {
+            s.push('\n');
+        }
+    }
+    s.truncate(s.trim_end().len());
+    s
+}
+
 #[test]
 fn test_peek() {
     let mut lexer = Lexer::new("123456".to_string());
@@ -446,17 +997,92 @@ fn test_peek() {
 #[test]
 fn test_tokenize() {
     let mut lexer = Lexer::new("123".to_string());
-    assert_eq!(&lexer.peek_token().kind, &TokenKind::NumLit(123));
+    assert_eq!(&lexer.peek_token().kind, &TokenKind::NumLit(123, None));
     let mut lexer = Lexer::new("987_654_321".to_string());
-    assert_eq!(lexer.peek_token().kind, TokenKind::NumLit(987654321));
+    assert_eq!(lexer.peek_token().kind, TokenKind::NumLit(987654321, None));
+}
+
+#[test]
+fn test_large_number_lit() {
+    let mut lexer = Lexer::new("99999999999999999999".to_string());
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::NumLit(99999999999999999999, None)
+    );
+    let mut lexer = Lexer::new(u128::MAX.to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(u128::MAX, None));
+    // overflows u128
+    let mut lexer = Lexer::new("340282366920938463463374607431768211456 1".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    let errors = lexer.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::IntTooLarge);
+    assert_eq!(
+        errors[0].span.to_snippet(),
+        "340282366920938463463374607431768211456"
+    );
+}
+
+#[test]
+fn test_number_lit_suffix() {
+    let mut lexer = Lexer::new("1i32 255u8 1_000_usize".to_string());
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::NumLit(1, Some("i32".to_string()))
+    );
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::NumLit(255, Some("u8".to_string()))
+    );
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::NumLit(1000, Some("usize".to_string()))
+    );
+    assert_eq!(
+        TokenKind::NumLit(255, Some("u8".to_string())).to_string(),
+        "255u8"
+    );
+    // invalid suffix
+    let mut lexer = Lexer::new("1z32 2".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+}
+
+#[test]
+fn test_float_lit() {
+    let mut lexer = Lexer::new("1.5 1.0f32 2f64 1_0.2_5".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::FloatLit(1.5, None));
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::FloatLit(1.0, Some("f32".to_string()))
+    );
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::FloatLit(2.0, Some("f64".to_string()))
+    );
+    assert_eq!(lexer.skip_token().kind, TokenKind::FloatLit(10.25, None));
+    assert_eq!(TokenKind::FloatLit(2.0, None).to_string(), "2.0");
+    // invalid suffixes
+    let mut lexer = Lexer::new("1.0f16 1.0i32 2".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    // `.` not followed by a digit
+    let mut lexer = Lexer::new("1..2 1.max".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::DotDot);
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Dot);
 }
 
 #[test]
 fn test_lexer() {
     let mut lexer = Lexer::new("123 + 456 ".to_string());
-    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(123));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(123, None));
     assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Plus));
-    assert_eq!(lexer.peek_token().kind, TokenKind::NumLit(456));
+    assert_eq!(lexer.peek_token().kind, TokenKind::NumLit(456, None));
     let _ = lexer.skip_token();
     assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
     assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
@@ -481,3 +1107,344 @@ fn test_empty() {
     assert_eq!(t.span.to_snippet(), "");
     assert_eq!(t.kind, TokenKind::Eof);
 }
+
+#[test]
+fn test_shebang() {
+    let mut lexer = Lexer::new("#!/usr/bin/env mini-rustc\n1 + 2".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Plus));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+    // `#!` is a shebang only at the very beginning
+    let mut lexer = Lexer::new("1 #!".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    // inner attribute
+    let mut lexer = Lexer::new("#![no_std]".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+}
+
+#[test]
+fn test_raw_string_lit() {
+    let str_lit = |s: &str| TokenKind::StrLit(s.to_string());
+
+    let mut lexer = Lexer::new(r#"r"a\n""#.to_string());
+    assert_eq!(lexer.skip_token().kind, str_lit("a\\n"));
+    let mut lexer = Lexer::new(r##"r#"say "hi""#"##.to_string());
+    assert_eq!(lexer.skip_token().kind, str_lit(r#"say "hi""#));
+    let mut lexer = Lexer::new(r###"r##"a"#b"## r"#;" "###.to_string());
+    assert_eq!(lexer.skip_token().kind, str_lit(r##"a"#b"##));
+    assert_eq!(lexer.skip_token().kind, str_lit("#;"));
+    // `r` is still an identifier
+    let mut lexer = Lexer::new("r".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("r".to_string()));
+    // unterminated
+    let mut lexer = Lexer::new(r##"r#"abc"##.to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    let mut lexer = Lexer::new(r###"r##"abc"#"###.to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+}
+
+#[test]
+fn test_remaining() {
+    let mut lexer = Lexer::new("1 + 2".to_string());
+    assert_eq!(lexer.remaining(), "1 + 2");
+    lexer.skip_token();
+    lexer.skip_token();
+    assert_eq!(lexer.position(), 3);
+    assert_eq!(lexer.remaining(), " 2");
+    // peeking does not consume input
+    lexer.peek_token();
+    assert_eq!(lexer.remaining(), " 2");
+    lexer.skip_token();
+    assert_eq!(lexer.remaining(), "");
+    // EOF
+    lexer.skip_token();
+    assert_eq!(lexer.remaining(), "");
+
+    let mut lexer = Lexer::new("#!/bin/mini-rustc\nfn".to_string());
+    assert_eq!(lexer.remaining(), "fn");
+    assert_eq!(lexer.skip_token().kind, TokenKind::Fn);
+
+    // positions are byte offsets
+    let mut lexer = Lexer::new("'é' x".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('é'));
+    assert_eq!(lexer.position(), 4);
+    assert_eq!(lexer.remaining(), " x");
+    let t = lexer.skip_token();
+    assert_eq!((t.span.lo(), t.span.hi()), (5, 6));
+    assert_eq!(t.span.to_snippet(), "x");
+}
+
+#[test]
+fn test_comments() {
+    let mut lexer = Lexer::new("1 /* a /* nested */ b */ + // line\n 2".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Plus));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+
+    let mut lexer = Lexer::new("1 /* never closed".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+}
+
+#[test]
+fn test_keep_trivia() {
+    let mut lexer = Lexer::new("1 /*c*/ + 2 // end".to_string());
+    lexer.set_keep_trivia(true);
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    let t = lexer.skip_token();
+    assert_eq!(t.kind, TokenKind::Comment("/*c*/".to_string()));
+    assert_eq!(t.span.to_snippet(), "/*c*/");
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Plus));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::Comment("// end".to_string())
+    );
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+}
+
+#[test]
+fn test_compound_assign_op() {
+    let mut lexer = Lexer::new("x += 1; y-=2 *= *p == ->".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("x".to_string()));
+    let t = lexer.skip_token();
+    assert_eq!(t.kind, TokenKind::BinOpEq(BinOp::Plus));
+    assert_eq!(t.span.to_snippet(), "+=");
+    assert_eq!(t.to_string(), "+=");
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(1, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Semi);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("y".to_string()));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOpEq(BinOp::Minus));
+    assert_eq!(lexer.skip_token().kind, TokenKind::NumLit(2, None));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOpEq(BinOp::Star));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Star));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("p".to_string()));
+    assert_eq!(lexer.skip_token().kind, TokenKind::BinOp(BinOp::Eq));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Arrow);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+}
+
+#[test]
+fn test_char_lit() {
+    let mut lexer = Lexer::new("'a' '\\n' '\\'' 'a 'b'..='z' =>".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('a'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('\n'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('\''));
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::Lifetime("a".to_string())
+    );
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('b'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::DotDotEq);
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('z'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::FatArrow);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+}
+
+#[test]
+fn test_escapes() {
+    let src = r#""a\nb\x41" '\u{1F600}' '\x41' "\q" '\q'"#;
+    let mut lexer = Lexer::new(src.to_string());
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::StrLit("a\nbA".to_string())
+    );
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('😀'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('A'));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+
+    // errors point at the bad escape
+    let mut lexer = Lexer::new(r#""ab\qc" '\u{D800}' b"a\xZ1" b'\u{41}'"#.to_string());
+    while lexer.skip_token().kind != TokenKind::Eof {}
+    let errors = lexer.take_errors();
+    let snippets: Vec<_> = errors.iter().map(|e| e.span.to_snippet()).collect();
+    assert_eq!(snippets, [r"\q", r"\u{D800}", r"\xZ", r"\u"]);
+
+    // escaped quotes do not terminate literals
+    let mut lexer = Lexer::new(r#""say \"hi\"""#.to_string());
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::StrLit("say \"hi\"".to_string())
+    );
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+}
+
+#[test]
+fn test_hex_and_unicode_escapes() {
+    let lex = |src: &str| Lexer::new(src.to_string()).skip_token().kind;
+    assert_eq!(lex(r#""\x41""#), TokenKind::StrLit("A".to_string()));
+    assert_eq!(lex(r#""\u{1F600}""#), TokenKind::StrLit("😀".to_string()));
+    assert_eq!(lex(r"'\u{3042}'"), TokenKind::CharLit('あ'));
+    // out of ASCII
+    assert_eq!(lex(r#""\xFF""#), TokenKind::Unknown);
+    assert_eq!(lex(r"'\x80'"), TokenKind::Unknown);
+    // not unicode scalar values
+    assert_eq!(lex(r#""\u{110000}""#), TokenKind::Unknown);
+    assert_eq!(lex(r#""\u{DFFF}""#), TokenKind::Unknown);
+}
+
+#[test]
+fn test_byte_lit() {
+    let mut lexer = Lexer::new(r#"b'A' b'\xff' b"a\n" b 'a'"#.to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::ByteLit(65));
+    assert_eq!(lexer.skip_token().kind, TokenKind::ByteLit(0xff));
+    assert_eq!(
+        lexer.skip_token().kind,
+        TokenKind::ByteStrLit(b"a\n".to_vec())
+    );
+    // `b` followed by a space is an identifier
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("b".to_string()));
+    assert_eq!(lexer.skip_token().kind, TokenKind::CharLit('a'));
+
+    // only ASCII
+    let mut lexer = Lexer::new("b'\u{e9}'".to_string());
+    assert_eq!(lexer.skip_token().kind, TokenKind::Unknown);
+    let [error] = &lexer.take_errors()[..] else {
+        panic!()
+    };
+    assert_eq!(error.msg, "Non-ASCII character in byte literal: `\u{e9}`");
+    assert_eq!(error.span.to_snippet(), "\u{e9}");
+    let lex = |src: &str| Lexer::new(src.to_string()).skip_token().kind;
+    assert_eq!(lex(r"b'\u{41}'"), TokenKind::Unknown);
+    assert_eq!(lex("b'ab'"), TokenKind::Unknown);
+
+    assert_eq!(TokenKind::ByteLit(b'\'').to_string(), r"b'\''");
+    assert_eq!(
+        TokenKind::ByteStrLit(b"a\x80".to_vec()).to_string(),
+        r#"b"a\x80""#
+    );
+}
+
+#[test]
+fn test_display_token() {
+    assert_eq!(TokenKind::BinOp(BinOp::Plus).to_string(), "+");
+    assert_eq!(TokenKind::NumLit(5, None).to_string(), "5");
+    assert_eq!(TokenKind::Eof.to_string(), "<eof>");
+    assert_eq!(TokenKind::Ident("foo".to_string()).to_string(), "foo");
+    assert_eq!(TokenKind::StrLit("a b".to_string()).to_string(), "\"a b\"");
+    assert_eq!(TokenKind::Lifetime("a".to_string()).to_string(), "'a");
+    assert_eq!(TokenKind::DotDotEq.to_string(), "..=");
+
+    let mut lexer = Lexer::new("fn".to_string());
+    assert_eq!(lexer.skip_token().to_string(), "fn");
+}
+
+#[test]
+fn test_tokenize_all() {
+    let src = "1 + 2 * 3";
+    let tokens = Lexer::new(src.to_string()).tokenize_all().unwrap();
+    let mut lexer = Lexer::new(src.to_string());
+    for t in &tokens {
+        let streamed = lexer.skip_token();
+        assert_eq!(t.kind, streamed.kind);
+        assert_eq!(
+            (t.span.lo(), t.span.hi()),
+            (streamed.span.lo(), streamed.span.hi())
+        );
+    }
+    assert_eq!(tokens.len(), 6);
+    assert_eq!(tokens.last().unwrap().kind, TokenKind::Eof);
+
+    let err = Lexer::new("1 + $ 2 #".to_string())
+        .tokenize_all()
+        .unwrap_err();
+    assert_eq!(err.msg, "Unknown token starting with: '$'");
+    assert_eq!((err.span.lo(), err.span.hi()), (4, 5));
+    let err = Lexer::new("1 + 2z8 $".to_string())
+        .tokenize_all()
+        .unwrap_err();
+    assert_eq!(err.msg, "Invalid suffix `z8` for number literal");
+    assert_eq!(err.span.to_snippet(), "2z8");
+}
+
+#[test]
+fn test_collect_errors() {
+    let mut lexer = Lexer::new("1 $ 2z8 \"a".to_string());
+    while lexer.skip_token().kind != TokenKind::Eof {}
+    let errors = lexer.take_errors();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].msg, "Unknown token starting with: '$'");
+    assert_eq!(errors[0].span.to_snippet(), "$");
+    assert_eq!(errors[1].msg, "Invalid suffix `z8` for number literal");
+    assert_eq!(errors[1].span.to_snippet(), "2z8");
+    assert_eq!(errors[2].msg, "Unterminated string literal");
+    assert_eq!(errors[2].span.to_snippet(), "\"a");
+    assert!(lexer.take_errors().is_empty());
+}
+
+#[test]
+fn test_raw_ident() {
+    let mut lexer = Lexer::new("r#return return r#foo r#\"s\"#".to_string());
+    let t = lexer.skip_token();
+    assert_eq!(t.kind, TokenKind::Ident("return".to_string()));
+    assert_eq!(t.span.to_snippet(), "r#return");
+    assert_eq!(lexer.skip_token().kind, TokenKind::Return);
+    assert_eq!(lexer.skip_token().kind, TokenKind::Ident("foo".to_string()));
+    // raw string literal
+    assert_eq!(lexer.skip_token().kind, TokenKind::StrLit("s".to_string()));
+    assert_eq!(lexer.skip_token().kind, TokenKind::Eof);
+}
+
+#[test]
+fn test_tokens_to_string() {
+    let normalize =
+        |src: &str| tokens_to_string(&Lexer::new(src.to_string()).tokenize_all().unwrap());
+    assert_eq!(normalize("1+2*3"), "1 + 2 * 3");
+    assert_eq!(
+        normalize("fn f(a:i32)->i32{a}"),
+        "fn f ( a : i32 ) -> i32 { a }"
+    );
+    assert_eq!(normalize("  "), "");
+
+    let mut lexer = Lexer::new("let s = \"a b\"; // c\n'a' 1.5".to_string());
+    lexer.set_keep_trivia(true);
+    let src = tokens_to_string(&lexer.tokenize_all().unwrap());
+    assert_eq!(src, "let s = \"a b\" ; // c\n'a' 1.5");
+
+    // round trip
+    let kinds = |src: &str| {
+        let mut lexer = Lexer::new(src.to_string());
+        lexer.set_keep_trivia(true);
+        let tokens = lexer.tokenize_all().unwrap();
+        tokens.into_iter().map(|t| t.kind).collect::<Vec<_>>()
+    };
+    assert_eq!(kinds(&src), kinds("let s = \"a b\"; // c\n'a' 1.5"));
+
+    // raw identifiers which are keywords keep the prefix
+    let src = "fn r#fn(r#match: i32) -> bool { r#true } r#foo";
+    let normalized = normalize(src);
+    assert_eq!(
+        normalized,
+        "fn r#fn ( r#match : i32 ) -> bool { r#true } foo"
+    );
+    assert_eq!(kinds(&normalized), kinds(src));
+}
+
+/// Token streams of the programs in `tests/*.sh` and `examples`, recorded
+/// with the char-by-char scanning the lexer used to do
+#[test]
+fn test_lex_golden() {
+    let corpus = include_str!("../tests/lexer/corpus.txt");
+    let golden = include_str!("../tests/lexer/tokens.txt");
+    let programs: Vec<_> = corpus.split("\n// ----\n").collect();
+    let expected: Vec<_> = golden.split("\n// ----\n").collect();
+    assert_eq!(programs.len(), expected.len());
+    for (src, expected) in programs.into_iter().zip(expected) {
+        let mut lexer = Lexer::new(src.to_string());
+        lexer.set_keep_trivia(true);
+        let mut lines = vec![];
+        loop {
+            let t = lexer.skip_token();
+            if t.kind == TokenKind::Eof {
+                break;
+            }
+            lines.push(format!("{:?} {}..{}", t.kind, t.span.lo(), t.span.hi()));
+        }
+        assert_eq!(lines.join("\n"), expected.trim_end(), "{}", src);
+    }
+}
@@ -0,0 +1,17 @@
+//! Lexer, AST and parser of mini-rustc as a library.
+//! Name resolution, type checking and code generation are in the binary target.
+//! With the `no_std` feature, only the lexer and spans are built, depending on
+//! `core` and `alloc` alone (e.g. for WASM): `cargo build --lib --features no_std`.
+//! The AST and the parser still need `std`.
+#![cfg_attr(all(feature = "no_std", not(test)), no_std)]
+#![feature(let_chains)]
+
+extern crate alloc;
+
+#[cfg(not(feature = "no_std"))]
+pub mod ast;
+pub mod lexer;
+#[cfg(not(feature = "no_std"))]
+pub mod parse;
+pub mod span;
+mod unescape;
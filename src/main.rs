@@ -1,16 +1,26 @@
 #![feature(let_chains)]
-mod ast;
 mod backend_llvm;
-mod lexer;
+#[cfg(any(test, feature = "bench"))]
+mod bench;
+mod const_eval;
+mod control_flow;
 //mod lvalue;
 mod middle;
-mod parse;
 mod resolve;
-mod span;
+mod self_assign;
+mod shadowing;
 mod typeck;
 
+use mini_rustc::{ast, lexer, parse, span};
+use parse::DiagnosticSink;
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    #[cfg(feature = "bench")]
+    if args.get(1).is_some_and(|arg| arg == "--bench-lex") {
+        bench::run();
+        return;
+    }
     if args.len() < 2 {
         eprintln!("Usage: mini-rustc file/source [options]");
         println!("Options:");
@@ -36,19 +46,38 @@ fn main() {
     };
 
     // Parse stage
-    let lexer = lexer::Lexer::new(src);
+    let lexer = lexer::Lexer::new(src.clone());
     let mut parser = parse::Parser::new(lexer);
-    let parse_result = parser.parse_crate();
-
-    let Some(krate) = parse_result else {
+    parser.set_sink(Box::new(parse::EprintlnSink));
+    let krate = parser.parse_crate();
+    if parser.has_errors() {
         eprintln!("Failed to parse source code");
         std::process::exit(1);
-    };
+    }
+    let mut sink = parse::EprintlnSink;
+    for warning in shadowing::check(&krate) {
+        sink.emit(warning);
+    }
+    for warning in self_assign::check(&krate) {
+        sink.emit(warning);
+    }
+    let errors = control_flow::check(&krate);
+    for e in &errors {
+        sink.emit(e.clone());
+    }
+    if !errors.is_empty() {
+        eprintln!("Failed to check control flow");
+        std::process::exit(1);
+    }
 
     let mut ctx = middle::Ctxt::new(dump_enabled);
 
     if ctx.dump_enabled {
         dbg!(&krate);
+        // Parentheses are kept only in this separate parse so that the compiled AST is unaffected
+        let mut parser = parse::Parser::from_source(&src);
+        parser.set_preserve_parens(true);
+        eprintln!("{}", ast::pretty::print_crate(&parser.parse_crate()));
     }
 
     // Name resolution stage
@@ -58,6 +58,11 @@ impl<'ctx> Ctxt<'ctx> {
         ast::visitor::go(&mut self.resolver, krate);
     }
 
+    #[cfg(test)]
+    pub fn run_resolver_on_expr(&mut self, expr: &ast::Expr) {
+        self.resolver.resolve_expr(expr);
+    }
+
     /// Resolve identifiers in var decls (func params or local variables) to canonical paths
     pub fn get_binding(&mut self, ident: &Ident) -> Option<Rc<Binding>> {
         self.resolver.get_binding(ident)
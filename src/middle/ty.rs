@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use crate::resolve::CanonicalPath;
+use crate::span::Symbol;
 
 #[derive(PartialEq, Eq)]
 pub struct Ty {
@@ -12,6 +13,7 @@ pub enum TyKind {
     Unit,
     Bool,
     I32,
+    Char,
     Str,
     Array(Rc<Ty>, usize),
     Fn(Rc<Vec<Rc<Ty>>>, Rc<Ty>),
@@ -72,7 +74,7 @@ impl Ty {
 
 #[derive(Debug)]
 pub struct AdtDef {
-    pub fields: Vec<(Rc<String>, Rc<Ty>)>,
+    pub fields: Vec<(Symbol, Rc<Ty>)>,
 }
 
 impl std::fmt::Debug for Ty {
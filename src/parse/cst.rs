@@ -0,0 +1,218 @@
+use crate::ast::{self, visitor::Visitor, NodeId};
+use crate::lexer::Token;
+use crate::span::Span;
+
+/// Node of the concrete syntax tree, which is parallel to the AST but keeps
+/// every token including comments and whitespace, so that the source can be
+/// reproduced exactly (e.g. for formatters).
+/// Built by `Parser::crate_cst` or `Parser::expr_cst` in CST mode.
+#[derive(Debug)]
+pub struct CstNode {
+    pub kind: CstNodeKind,
+    pub span: Span,
+    pub children: Vec<CstElement>,
+}
+
+/// AST node which a CST node corresponds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CstNodeKind {
+    /// Everything parsed
+    Root,
+    Stmt(NodeId),
+    Expr(NodeId),
+    Block(NodeId),
+    Pattern,
+    Type,
+}
+
+#[derive(Debug)]
+pub enum CstElement {
+    Node(CstNode),
+    /// Token including comments
+    Token(Token),
+    /// Whitespace between tokens
+    Whitespace(Span),
+}
+
+impl CstElement {
+    pub fn span(&self) -> &Span {
+        match self {
+            CstElement::Node(node) => &node.span,
+            CstElement::Token(token) => &token.span,
+            CstElement::Whitespace(span) => span,
+        }
+    }
+}
+
+impl CstNode {
+    /// Reproduce the source covered by the node
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        self.write_source(&mut out);
+        out
+    }
+
+    fn write_source(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.write_source(out),
+                // `Span::to_snippet` is not used since it replaces newlines
+                _ => {
+                    let span = child.span();
+                    out.push_str(&span.src()[span.lo()..span.hi()]);
+                }
+            }
+        }
+    }
+}
+
+/// Collects AST nodes which CST nodes are built for in pre-order
+#[derive(Default)]
+pub struct NodeCollector {
+    pub nodes: Vec<(CstNodeKind, Span)>,
+}
+
+impl NodeCollector {
+    fn push(&mut self, kind: CstNodeKind, span: &Span) {
+        self.nodes.push((kind, span.clone()));
+    }
+}
+
+impl<'ctx> Visitor<'ctx> for NodeCollector {
+    fn visit_stmt(&mut self, stmt: &'ctx ast::Stmt) {
+        self.push(CstNodeKind::Stmt(stmt.id), &stmt.span);
+    }
+
+    fn visit_expr(&mut self, expr: &'ctx ast::Expr) {
+        self.push(CstNodeKind::Expr(expr.id), &expr.span);
+    }
+
+    fn visit_block(&mut self, block: &'ctx ast::Block) {
+        self.push(CstNodeKind::Block(block.id), &block.span);
+    }
+
+    fn visit_pattern(&mut self, pat: &'ctx ast::Pattern) {
+        self.push(CstNodeKind::Pattern, &pat.span);
+    }
+
+    fn visit_type(&mut self, ty: &'ctx ast::Ty) {
+        self.push(CstNodeKind::Type, &ty.span);
+    }
+}
+
+/// Build a CST from the tokens consumed by the parser and the AST nodes collected
+/// by `NodeCollector`. Gaps between tokens are filled with whitespace up to `end`
+/// (i.e. the start of the next token).
+pub fn build(tokens: Vec<Token>, mut nodes: Vec<(CstNodeKind, Span)>, end: &Span) -> CstNode {
+    // outer nodes come first among nodes starting at the same position.
+    // nodes with the same span are kept in pre-order since the sort is stable.
+    nodes.sort_by_key(|(_, span)| (span.lo(), std::cmp::Reverse(span.hi())));
+    let mut nodes = nodes.into_iter().peekable();
+
+    let mut elems = vec![];
+    let mut pos = 0;
+    for token in tokens {
+        let lo = token.span.lo();
+        if pos < lo {
+            elems.push(CstElement::Whitespace(Span::new(pos, lo, end.src())));
+        }
+        pos = token.span.hi();
+        elems.push(CstElement::Token(token));
+    }
+    if pos < end.lo() {
+        elems.push(CstElement::Whitespace(Span::new(pos, end.lo(), end.src())));
+    }
+
+    let mut stack = vec![CstNode {
+        kind: CstNodeKind::Root,
+        span: Span::new(0, end.lo(), end.src()),
+        children: vec![],
+    }];
+    for elem in elems {
+        let lo = elem.span().lo();
+        close_nodes(&mut stack, lo);
+        // whitespace before a node belongs to the outer node
+        while let Some((_, node_span)) = nodes.peek()
+            && node_span.lo() <= lo
+            && matches!(elem, CstElement::Token(_))
+        {
+            let (kind, node_span) = nodes.next().unwrap();
+            stack.push(CstNode {
+                kind,
+                span: node_span,
+                children: vec![],
+            });
+            // empty nodes
+            close_nodes(&mut stack, lo);
+        }
+        stack.last_mut().unwrap().children.push(elem);
+    }
+    close_nodes(&mut stack, usize::MAX);
+    stack.pop().unwrap()
+}
+
+/// Pop nodes ending at or before `pos` (except for the root) and append them to their parents
+fn close_nodes(stack: &mut Vec<CstNode>, pos: usize) {
+    while stack.len() > 1 && stack.last().unwrap().span.hi() <= pos {
+        let node = stack.pop().unwrap();
+        let parent = stack.last_mut().unwrap();
+        parent.children.push(CstElement::Node(node));
+    }
+}
+
+#[test]
+fn test_cst_preserves_whitespace() {
+    use crate::parse::Parser;
+
+    let mut parser = Parser::from_source("1 +  2");
+    parser.set_cst(true);
+    let expr = parser.parse_expr().unwrap();
+    let cst = parser.expr_cst(&expr).unwrap();
+    assert_eq!(cst.to_source(), "1 +  2");
+
+    // root -> `1 +  2` -> [`1`, ` `, `+`, `  `, `2`]
+    let [CstElement::Node(binary)] = &cst.children[..] else {
+        panic!()
+    };
+    assert_eq!(binary.kind, CstNodeKind::Expr(expr.id));
+    assert_eq!(binary.children.len(), 5);
+    let CstElement::Whitespace(space) = &binary.children[3] else {
+        panic!()
+    };
+    assert_eq!(space.to_snippet(), "  ");
+
+    // not in CST mode
+    let mut parser = Parser::from_source("1 +  2");
+    let expr = parser.parse_expr().unwrap();
+    assert!(parser.expr_cst(&expr).is_none());
+}
+
+#[test]
+fn test_cst_preserves_comments() {
+    use crate::parse::Parser;
+
+    let src = "// hello\nfn main() -> i32 {\n    let x = /* one */ 1;\n    x  }\n/* bye */ ";
+    let mut parser = Parser::from_source(src);
+    parser.set_cst(true);
+    let krate = parser.parse_crate();
+    assert!(!parser.has_errors());
+    let cst = parser.crate_cst(&krate).unwrap();
+    assert_eq!(cst.to_source(), src);
+}
+
+#[test]
+fn test_cst_non_ascii() {
+    use crate::parse::Parser;
+
+    for src in [
+        "// héllo\nfn main() -> i32 { 1 }",
+        "fn f() -> () { let s = \"日本語\"; /* ü */ let c = 'é'; }",
+    ] {
+        let mut parser = Parser::from_source(src);
+        parser.set_cst(true);
+        let krate = parser.parse_crate();
+        assert!(!parser.has_errors());
+        let cst = parser.crate_cst(&krate).unwrap();
+        assert_eq!(cst.to_source(), src);
+    }
+}
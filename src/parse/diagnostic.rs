@@ -0,0 +1,74 @@
+use super::ParseError;
+use crate::span::{LineCol, Locator};
+
+/// Destination of errors and warnings reported by `Parser`
+pub trait DiagnosticSink {
+    fn emit(&mut self, diag: ParseError);
+
+    /// Diagnostics kept by the sink. Empty if the sink does not keep them.
+    fn diagnostics(&self) -> &[ParseError] {
+        &[]
+    }
+
+    /// Remove the kept diagnostics and return them
+    fn take(&mut self) -> Vec<ParseError> {
+        vec![]
+    }
+}
+
+/// Collect diagnostics so that they can be reported all at once (default)
+#[derive(Default)]
+pub struct VecSink(Vec<ParseError>);
+
+impl DiagnosticSink for VecSink {
+    fn emit(&mut self, diag: ParseError) {
+        self.0.push(diag);
+    }
+
+    fn diagnostics(&self) -> &[ParseError] {
+        &self.0
+    }
+
+    fn take(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+/// Print diagnostics to stderr as soon as they are reported, prefixed with their line and column
+pub struct EprintlnSink;
+
+impl DiagnosticSink for EprintlnSink {
+    fn emit(&mut self, diag: ParseError) {
+        let LineCol { line, col } = Locator::default().line_col(&diag.span);
+        eprintln!("{}:{}: {}", line, col, diag);
+    }
+}
+
+#[test]
+fn test_custom_sink() {
+    use super::{Parser, Severity};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Count errors and warnings without keeping them
+    struct CountingSink(Rc<Cell<(usize, usize)>>);
+
+    impl DiagnosticSink for CountingSink {
+        fn emit(&mut self, diag: ParseError) {
+            let (errors, warnings) = self.0.get();
+            match diag.severity {
+                Severity::Error => self.0.set((errors + 1, warnings)),
+                Severity::Warning => self.0.set((errors, warnings + 1)),
+            }
+        }
+    }
+
+    let count = Rc::new(Cell::new((0, 0)));
+    let mut parser = Parser::from_source("fn f() -> () { 1;; } fn g() -> () { let a: i32 = ; }");
+    parser.set_sink(Box::new(CountingSink(Rc::clone(&count))));
+    parser.parse_crate();
+    assert_eq!(count.get(), (1, 1));
+    assert!(parser.has_errors());
+    // the sink does not keep diagnostics
+    assert!(parser.errors().is_empty());
+}
@@ -1,73 +1,449 @@
+pub mod cst;
+mod diagnostic;
 mod parse_expr;
 mod parse_item;
+mod parse_pat;
 mod parse_stmt;
+pub mod session;
 
+use self::cst::{CstNode, NodeCollector};
+pub use self::diagnostic::{DiagnosticSink, EprintlnSink, VecSink};
+pub use self::parse_expr::PrecedenceTable;
 use self::parse_item::is_item_start;
-use crate::ast::{Crate, Item, NodeId, Path};
-use crate::lexer::{Lexer, Token, TokenKind};
-use crate::span::Ident;
-use std::rc::Rc;
+use crate::ast::{self, Crate, Expr, Item, NodeId, Path};
+use crate::lexer::{LexError, Lexer, Token, TokenKind};
+use crate::span::{Ident, Span, Symbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    /// Parsing can continue without affecting the result
+    Warning,
+}
+
+/// Diagnostic reported while parsing
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub msg: String,
+    pub span: Span,
+    pub severity: Severity,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "{}", self.msg),
+            Severity::Warning => write!(f, "warning: {}", self.msg),
+        }
+    }
+}
+
+impl ParseError {
+    /// Error on the token `found` which is none of the `expected` tokens
+    /// e.g. "Expected `)` or `,`, but found `;`"
+    pub fn expected(expected: &[TokenKind], found: &Token) -> Self {
+        let kinds: Vec<String> = expected.iter().map(|kind| format!("`{}`", kind)).collect();
+        let expected = match kinds.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, init)) => format!("{} or {}", init.join(", "), last),
+            None => "nothing".to_string(),
+        };
+        ParseError {
+            msg: format!("Expected {}, but found `{}`", expected, found),
+            span: found.span.clone(),
+            severity: Severity::Error,
+        }
+    }
+}
+
+impl From<LexError> for ParseError {
+    fn from(err: LexError) -> Self {
+        ParseError {
+            msg: err.msg,
+            span: err.span,
+            severity: Severity::Error,
+        }
+    }
+}
+
+impl From<Lexer> for Parser {
+    fn from(lexer: Lexer) -> Self {
+        Parser::new(lexer)
+    }
+}
+
+/// Where the parser reads tokens from
+enum TokenSource {
+    /// Tokenize the source lazily
+    Stream(Lexer),
+    /// Pre-lexed tokens without comments, ending with EOF
+    Buffered { tokens: Vec<Token>, pos: usize },
+}
 
 pub struct Parser {
-    lexer: Lexer,
+    tokens: TokenSource,
     next_node_id: u32,
+    /// Keep parentheses as `ExprKind::Paren` instead of discarding them
+    preserve_parens: bool,
+    /// Destination of errors and warnings
+    sink: Box<dyn DiagnosticSink>,
+    /// Number of errors (not warnings) reported so far
+    n_errors: usize,
+    /// First error reported since `parse_stmt` started
+    first_error: Option<ParseError>,
+    /// Struct literals are not allowed in the current expression
+    /// (e.g. `match x { ... }` is not a struct literal `x { ... }`)
+    no_struct_literal: bool,
+    /// Precedences of binary operators
+    precedences: PrecedenceTable,
+    /// Tokens consumed so far including comments (only in CST mode)
+    cst_tokens: Option<Vec<Token>>,
+    /// End of the last consumed token
+    prev_token_hi: usize,
 }
 
 impl Parser {
     pub fn new(lexer: Lexer) -> Self {
         Parser {
-            lexer,
+            tokens: TokenSource::Stream(lexer),
             next_node_id: 0,
+            preserve_parens: false,
+            sink: Box::new(VecSink::default()),
+            n_errors: 0,
+            first_error: None,
+            no_struct_literal: false,
+            precedences: PrecedenceTable::default(),
+            cst_tokens: None,
+            prev_token_hi: 0,
         }
     }
 
+    /// Errors and warnings kept by the sink (by default, all of them reported so far).
+    /// They are kept after parsing so that callers can report them all at once.
+    pub fn errors(&self) -> &[ParseError] {
+        self.sink.diagnostics()
+    }
+
+    /// Take errors and warnings kept by the sink. The error count is reset as well.
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        self.n_errors = 0;
+        self.sink.take()
+    }
+
+    /// Check if any error (not warning) has been reported
+    pub fn has_errors(&self) -> bool {
+        self.n_errors > 0
+    }
+
+    /// Send diagnostics to `sink` instead of the default `VecSink`
+    pub fn set_sink(&mut self, sink: Box<dyn DiagnosticSink>) {
+        self.sink = sink;
+    }
+
+    fn emit(&mut self, diag: ParseError) {
+        if diag.severity == Severity::Error {
+            self.n_errors += 1;
+            if self.first_error.is_none() {
+                self.first_error = Some(diag.clone());
+            }
+        }
+        self.sink.emit(diag);
+    }
+
+    fn error(&mut self, msg: String, span: Span) {
+        self.emit(ParseError {
+            msg,
+            span,
+            severity: Severity::Error,
+        });
+    }
+
+    /// Report that the next token is not the expected one
+    /// e.g. `self.error_expected("';'")` reports "Expected ';', but found `}`"
+    fn error_expected(&mut self, expected: &str) {
+        let t = self.peek_token();
+        let msg = format!("Expected {}, but found `{}`", expected, t);
+        let span = t.span.clone();
+        self.error(msg, span);
+    }
+
+    /// Report a non-fatal diagnostic
+    fn warn(&mut self, msg: String, span: Span) {
+        self.emit(ParseError {
+            msg,
+            span,
+            severity: Severity::Warning,
+        });
+    }
+
+    /// Shortcut for `Parser::new(Lexer::new(src))`
+    pub fn from_source(src: &str) -> Self {
+        Parser::new(Lexer::new(src.to_string()))
+    }
+
+    /// Parse pre-lexed tokens (e.g. by `Lexer::tokenize_all`), which allows arbitrary lookahead.
+    /// EOF is appended if `tokens` does not end with it.
+    pub fn from_tokens(tokens: Vec<Token>) -> Self {
+        let mut tokens: Vec<Token> = tokens
+            .into_iter()
+            .filter(|t| !matches!(t.kind, TokenKind::Comment(_)))
+            .collect();
+        if let Some(last) = tokens.last()
+            && last.kind != TokenKind::Eof
+        {
+            let pos = last.span.hi();
+            let eof = Token {
+                kind: TokenKind::Eof,
+                span: Span::new(pos, pos, last.span.src()),
+            };
+            tokens.push(eof);
+        }
+        let mut parser = Parser::new(Lexer::new(String::new()));
+        if !tokens.is_empty() {
+            parser.tokens = TokenSource::Buffered { tokens, pos: 0 };
+        }
+        parser
+    }
+
+    /// Start parsing another source.
+    /// Node ids are kept unique across sources, and errors reported so far are discarded.
+    pub fn set_source(&mut self, src: &str) {
+        self.tokens = TokenSource::Stream(Lexer::new(src.to_string()));
+        self.take_errors();
+    }
+
+    pub fn set_precedence_table(&mut self, precedences: PrecedenceTable) {
+        self.precedences = precedences;
+    }
+
+    pub fn set_preserve_parens(&mut self, preserve_parens: bool) {
+        self.preserve_parens = preserve_parens;
+    }
+
+    /// Record every token consumed including comments so that a lossless CST can be built
+    /// by `Parser::crate_cst` or `Parser::expr_cst`.
+    /// Call before parsing. Comments are lost if the parser is made by `Parser::from_tokens`.
+    pub fn set_cst(&mut self, cst: bool) {
+        if let TokenSource::Stream(lexer) = &mut self.tokens {
+            lexer.set_keep_trivia(cst);
+        }
+        self.cst_tokens = if cst { Some(vec![]) } else { None };
+    }
+
+    /// CST of the crate parsed in CST mode. Returns None if not in CST mode.
+    pub fn crate_cst(&mut self, krate: &Crate) -> Option<CstNode> {
+        let mut collector = NodeCollector::default();
+        ast::visitor::go(&mut collector, krate);
+        self.build_cst(collector)
+    }
+
+    /// CST of the expression parsed in CST mode. Returns None if not in CST mode.
+    pub fn expr_cst(&mut self, expr: &Expr) -> Option<CstNode> {
+        let mut collector = NodeCollector::default();
+        ast::visitor::go_expr(&mut collector, expr);
+        self.build_cst(collector)
+    }
+
+    fn build_cst(&mut self, collector: NodeCollector) -> Option<CstNode> {
+        // trailing comments are consumed here
+        let end = self.peek_token().span.clone();
+        let tokens = self.cst_tokens.clone()?;
+        Some(cst::build(tokens, collector.nodes, &end))
+    }
+
     pub fn get_next_id(&mut self) -> NodeId {
         let id = self.next_node_id;
         self.next_node_id += 1;
         NodeId::new(id)
     }
 
+    /// Comments are skipped even if the lexer keeps trivia
     fn peek_token(&mut self) -> &Token {
-        self.lexer.peek_token()
+        if let TokenSource::Stream(lexer) = &mut self.tokens {
+            while let TokenKind::Comment(_) = lexer.peek_token().kind {
+                let comment = lexer.skip_token();
+                if let Some(tokens) = &mut self.cst_tokens {
+                    tokens.push(comment);
+                }
+            }
+            // errors on tokens are reported once they are lexed
+            for e in lexer.take_errors() {
+                self.emit(e.into());
+            }
+        }
+        match &mut self.tokens {
+            TokenSource::Stream(lexer) => lexer.peek_token(),
+            TokenSource::Buffered { tokens, pos } => &tokens[*pos],
+        }
+    }
+
+    /// Look ahead `n` tokens (i.e. `peek_nth(0)` is the same as `peek_token()`).
+    /// EOF is returned beyond the end of the source.
+    fn peek_nth(&mut self, n: usize) -> &Token {
+        match &mut self.tokens {
+            TokenSource::Stream(lexer) => {
+                // skip comments in the middle
+                let mut i = 0;
+                let mut found = 0;
+                loop {
+                    let kind = &lexer.peek_nth(i).kind;
+                    if *kind == TokenKind::Eof {
+                        break;
+                    }
+                    if !matches!(kind, TokenKind::Comment(_)) {
+                        if found == n {
+                            break;
+                        }
+                        found += 1;
+                    }
+                    i += 1;
+                }
+                lexer.peek_nth(i)
+            }
+            TokenSource::Buffered { tokens, pos } => {
+                &tokens[std::cmp::min(*pos + n, tokens.len() - 1)]
+            }
+        }
     }
 
+    /// Keep returning EOF after reaching EOF
     fn skip_token(&mut self) -> Token {
-        self.lexer.skip_token()
+        self.peek_token();
+        let t = match &mut self.tokens {
+            TokenSource::Stream(lexer) => lexer.skip_token(),
+            TokenSource::Buffered { tokens, pos } => {
+                let t = tokens[*pos].clone();
+                if *pos + 1 < tokens.len() {
+                    *pos += 1;
+                }
+                t
+            }
+        };
+        if t.kind != TokenKind::Eof {
+            self.prev_token_hi = t.span.hi();
+            if let Some(tokens) = &mut self.cst_tokens {
+                tokens.push(t.clone());
+            }
+        }
+        t
+    }
+
+    /// Check if the next token is of the given kind without consuming it.
+    fn peek_is(&mut self, kind: &TokenKind) -> bool {
+        self.peek_token().kind == *kind
+    }
+
+    /// Same as `peek_is`, but the contents of the token are ignored.
+    /// e.g. `at(&TokenKind::Ident(String::new()))` is true for any identifier.
+    fn at(&mut self, kind: &TokenKind) -> bool {
+        std::mem::discriminant(&self.peek_token().kind) == std::mem::discriminant(kind)
+    }
+
+    /// Skip the token if it is of `kind`, and return it.
+    /// Otherwise nothing is skipped and the error is returned.
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, ParseError> {
+        if self.peek_is(&kind) {
+            Ok(self.skip_token())
+        } else {
+            Err(ParseError::expected(&[kind], self.peek_token()))
+        }
+    }
+
+    /// Same as `expect`, but the error is reported
+    fn expect_token(&mut self, kind: TokenKind) -> Option<Token> {
+        match self.expect(kind) {
+            Ok(t) => Some(t),
+            Err(e) => {
+                self.emit(e);
+                None
+            }
+        }
     }
 
     /// Skip token only when bumping into the expected token.
     fn skip_expected_token(&mut self, kind: TokenKind) -> bool {
-        let t = self.peek_token();
-        if t.kind == kind {
-            self.lexer.skip_token();
+        if self.peek_is(&kind) {
+            self.skip_token();
             true
         } else {
             false
         }
     }
 
+    /// Span from the start of `start` to the end of the last consumed token
+    fn span_since(&self, start: &Span) -> Span {
+        Span::new(start.lo(), self.prev_token_hi, start.src())
+    }
+
     fn at_eof(&mut self) -> bool {
-        matches!(
-            self.peek_token(),
-            &Token {
-                kind: TokenKind::Eof,
-                ..
+        self.peek_is(&TokenKind::Eof)
+    }
+
+    /// Skip tokens until reaching one of `sync_tokens` or EOF for error recovery.
+    /// The token found is not consumed.
+    /// Groups enclosed by delimiters (e.g. `( ... )`) are skipped as a whole,
+    /// and unmatched closing delimiters also stop skipping.
+    fn recover_to(&mut self, sync_tokens: &[TokenKind]) {
+        let mut depth = 0;
+        loop {
+            let t = self.peek_token();
+            if t.kind == TokenKind::Eof || (depth == 0 && sync_tokens.contains(&t.kind)) {
+                return;
+            }
+            match t.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                _ => (),
             }
-        )
+            self.skip_token();
+        }
     }
 
     /// crate ::= item*
-    pub fn parse_crate(&mut self) -> Option<Crate> {
-        let items = self.parse_items()?;
-        if !self.at_eof() {
-            eprintln!(
-                "Expected crate item but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
+    /// Broken items are skipped, so a partial tree is returned even if
+    /// parsing fails. Use `Parser::has_errors` to check the result.
+    pub fn parse_crate(&mut self) -> Crate {
+        let mut items = vec![];
+        while !self.at_eof() {
+            if is_item_start(self.peek_token()) {
+                if let Some(item) = self.parse_item() {
+                    items.push(item);
+                    continue;
+                }
+            } else {
+                let t = self.skip_token();
+                let span = t.span.clone();
+                self.error(format!("Expected crate item, but found `{}`", t), span);
+            }
+            self.recover_to_item();
         }
         let id = self.get_next_id();
-        Some(Crate { items, id })
+        Crate { items, id }
+    }
+
+    /// Skip tokens until the start of the next item or EOF for error recovery.
+    /// Unlike `recover_to`, unmatched closing delimiters are skipped too.
+    fn recover_to_item(&mut self) {
+        let mut depth = 0;
+        loop {
+            let t = self.peek_token();
+            if t.kind == TokenKind::Eof || (depth == 0 && is_item_start(t)) {
+                return;
+            }
+            match t.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                    depth = std::cmp::max(depth - 1, 0);
+                }
+                _ => (),
+            }
+            self.skip_token();
+        }
     }
 
     fn parse_items(&mut self) -> Option<Vec<Item>> {
@@ -83,11 +459,12 @@ impl Parser {
         let t = self.skip_token();
         if let TokenKind::Ident(symbol) = t.kind {
             Some(Ident {
-                symbol: Rc::new(symbol),
+                symbol: Symbol::intern(&symbol),
                 span: t.span,
             })
         } else {
-            eprintln!("Expected ident, but found `{}`", t.span.to_snippet());
+            let span = t.span.clone();
+            self.error(format!("Expected ident, but found `{}`", t), span);
             None
         }
     }
@@ -100,7 +477,7 @@ impl Parser {
         let mut span = ident.span.clone();
         let mut segs = vec![ident];
 
-        while self.peek_token().kind == TokenKind::ColCol {
+        while self.peek_is(&TokenKind::ColCol) {
             self.skip_token();
             let new_seg = self.parse_ident()?;
             span = span.concat(&new_seg.span);
@@ -113,3 +490,186 @@ impl Parser {
         })
     }
 }
+
+#[test]
+fn test_ident_interned() {
+    let mut parser = Parser::from_source("x x y");
+    let x1 = parser.parse_ident().unwrap();
+    let x2 = parser.parse_ident().unwrap();
+    let y = parser.parse_ident().unwrap();
+    assert_eq!(x1.symbol, x2.symbol);
+    assert_ne!(x1.symbol, y.symbol);
+    assert_eq!(x1.as_str(), "x");
+}
+
+#[test]
+fn test_peek_is() {
+    let mut parser = Parser::from_source("x");
+    assert!(parser.peek_is(&TokenKind::Ident("x".to_string())));
+    assert!(!parser.peek_is(&TokenKind::Eof));
+    parser.skip_token();
+    // at EOF
+    assert!(!parser.peek_is(&TokenKind::Ident("x".to_string())));
+    assert!(!parser.peek_is(&TokenKind::Semi));
+    assert!(parser.peek_is(&TokenKind::Eof));
+    // EOF is not consumed
+    assert!(parser.at_eof());
+}
+
+#[test]
+fn test_at() {
+    let mut parser = Parser::from_source("x 1");
+    assert!(parser.at(&TokenKind::Ident(String::new())));
+    assert!(!parser.peek_is(&TokenKind::Ident(String::new())));
+    assert!(!parser.at(&TokenKind::NumLit(0, None)));
+    parser.skip_token();
+    assert!(parser.at(&TokenKind::NumLit(0, None)));
+    parser.skip_token();
+    // at EOF
+    assert!(!parser.at(&TokenKind::Ident(String::new())));
+    assert!(parser.at(&TokenKind::Eof));
+}
+
+#[test]
+fn test_expect() {
+    let mut parser = Parser::from_source(") ;");
+    let t = parser.expect(TokenKind::CloseParen).unwrap();
+    assert_eq!(t.kind, TokenKind::CloseParen);
+    assert_eq!(t.span.to_snippet(), ")");
+
+    let err = parser.expect(TokenKind::CloseParen).unwrap_err();
+    assert_eq!(err.msg, "Expected `)`, but found `;`");
+    assert_eq!(err.span.to_snippet(), ";");
+    // nothing is skipped on error
+    assert!(parser.peek_is(&TokenKind::Semi));
+    assert!(!parser.has_errors());
+
+    assert!(parser.expect_token(TokenKind::Comma).is_none());
+    assert_eq!(parser.errors().len(), 1);
+}
+
+#[test]
+fn test_recover_to() {
+    let mut parser = Parser::from_source("a + (b; c) ; d");
+    parser.skip_token();
+    parser.recover_to(&[TokenKind::Semi]);
+    // `;` in parentheses is skipped
+    let semi = parser.skip_token();
+    assert_eq!(semi.kind, TokenKind::Semi);
+    assert_eq!(semi.span.lo(), 11);
+    assert!(parser.peek_is(&TokenKind::Ident("d".to_string())));
+
+    // stops at unmatched closing delimiter
+    let mut parser = Parser::from_source("a b } ;");
+    parser.recover_to(&[TokenKind::Semi]);
+    assert!(parser.peek_is(&TokenKind::CloseBrace));
+
+    // stops at EOF
+    let mut parser = Parser::from_source("a b");
+    parser.recover_to(&[TokenKind::Semi]);
+    assert!(parser.at_eof());
+}
+
+#[test]
+fn test_from_source() {
+    let krate = Parser::from_source("fn main() -> i32 { 1 + 2 }").parse_crate();
+    assert_eq!(krate.items.len(), 1);
+    let expr = Parser::from_source("1 + 2").parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), "1 + 2");
+    // expression is not a crate item
+    let mut parser = Parser::from_source("1 + 2");
+    assert!(parser.parse_crate().items.is_empty());
+    assert!(parser.has_errors());
+
+    let mut parser: Parser = Lexer::new("x".to_string()).into();
+    assert_eq!(parser.parse_ident().unwrap().as_str(), "x");
+}
+
+#[test]
+fn test_errors_with_partial_tree() {
+    let mut parser =
+        Parser::from_source("fn f() -> i32 { 1 + } } fn g() {} 42 struct S { a: i32 }");
+    let krate = parser.parse_crate();
+    // `f` is broken, but the following items are still parsed
+    assert_eq!(krate.items.len(), 2);
+    assert!(parser.has_errors());
+    let errors = parser.errors();
+    assert!(errors.len() >= 2);
+    assert!(errors.iter().any(|e| e.span.to_snippet() == "42"));
+
+    let mut parser = Parser::from_source("fn f() {}");
+    parser.parse_crate();
+    assert!(parser.errors().is_empty());
+}
+
+#[test]
+fn test_lex_errors_are_reported() {
+    let mut parser = Parser::from_source("fn f() { \"\\q\"; }");
+    parser.parse_crate();
+    assert!(parser.has_errors());
+    let error = &parser.errors()[0];
+    assert_eq!(error.msg, "Unknown character escape: `\\q`");
+    assert_eq!(error.span.to_snippet(), "\\q");
+}
+
+#[test]
+fn test_expected_one_of() {
+    let found = Lexer::new(";".to_string()).skip_token();
+    let expected = |kinds: &[TokenKind]| ParseError::expected(kinds, &found).msg;
+    assert_eq!(
+        expected(&[TokenKind::CloseParen]),
+        "Expected `)`, but found `;`"
+    );
+    assert_eq!(
+        expected(&[TokenKind::CloseParen, TokenKind::Comma]),
+        "Expected `)` or `,`, but found `;`"
+    );
+    assert_eq!(
+        expected(&[TokenKind::CloseParen, TokenKind::Comma, TokenKind::Eq]),
+        "Expected `)`, `,` or `=`, but found `;`"
+    );
+
+    let mut parser = Parser::from_source("f(1, 2;");
+    assert!(parser.parse_expr().is_none());
+    let errors = parser.errors();
+    assert_eq!(errors[0].msg, "Expected `)` or `,`, but found `;`");
+    assert_eq!(errors[0].span.to_snippet(), ";");
+
+    let mut parser = Parser::from_source("{ 1 2 }");
+    assert!(parser.parse_block().is_none());
+    assert_eq!(parser.errors()[0].msg, "Expected `;` or `}`, but found `2`");
+}
+
+#[test]
+fn test_skip_trivia() {
+    let mut lexer = Lexer::new("/* a */ 1 /*c*/ + // b\n 2".to_string());
+    lexer.set_keep_trivia(true);
+    let mut parser = Parser::new(lexer);
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), "1 /*c*/ + // b 2");
+    assert!(parser.at_eof());
+}
+
+#[test]
+fn test_buffered_parser() {
+    let src = "a.b[f(1, -x)] = match c { 'a'..='z' => 1 + 2 * 3, _ => { g::<i32>() as i32 } }";
+    let streamed = Parser::from_source(src).parse_expr().unwrap();
+    let tokens = Lexer::new(src.to_string()).tokenize_all().unwrap();
+    let mut parser = Parser::from_tokens(tokens);
+    let buffered = parser.parse_expr().unwrap();
+    assert!(parser.at_eof());
+    assert_eq!(format!("{:?}", streamed), format!("{:?}", buffered));
+
+    // lookahead skips comments and stops at EOF
+    let mut lexer = Lexer::new("a /* b */ + // c\n d".to_string());
+    lexer.set_keep_trivia(true);
+    let mut streaming = Parser::new(lexer);
+    let mut buffered = Parser::from_tokens(Lexer::new("a + d".to_string()).tokenize_all().unwrap());
+    for parser in [&mut streaming, &mut buffered] {
+        assert_eq!(parser.peek_nth(1).to_string(), "+");
+        assert_eq!(parser.peek_nth(2).to_string(), "d");
+        assert_eq!(parser.peek_nth(5).kind, TokenKind::Eof);
+        parser.skip_token();
+        assert_eq!(parser.peek_nth(0).to_string(), "+");
+    }
+}
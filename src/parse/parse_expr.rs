@@ -1,43 +1,267 @@
-use super::Parser;
-use crate::ast::{self, Expr, ExprKind, Path, UnOp};
+use super::{ParseError, Parser};
+use crate::ast::{self, Delim, Expr, ExprKind, MatchArm, Path, Ty, UnOp};
 use crate::lexer::{self, Token, TokenKind};
-use crate::span::Ident;
+use crate::span::{Ident, Span, Symbol};
 
 pub fn is_expr_start(token: &Token) -> bool {
     matches!(
         token.kind,
-        TokenKind::NumLit(_)
+        TokenKind::NumLit(..)
+            | TokenKind::FloatLit(..)
             | TokenKind::StrLit(_)
+            | TokenKind::CharLit(_)
+            | TokenKind::ByteLit(_)
+            | TokenKind::ByteStrLit(_)
             | TokenKind::Ident(_)
             | TokenKind::OpenParen
             | TokenKind::OpenBrace
             | TokenKind::OpenBracket
-            | TokenKind::BinOp(lexer::BinOp::Plus | lexer::BinOp::Minus)
+            | TokenKind::BinOp(
+                lexer::BinOp::Plus | lexer::BinOp::Minus | lexer::BinOp::Star | lexer::BinOp::And
+            )
+            | TokenKind::Bang
             | TokenKind::Return
             | TokenKind::True
             | TokenKind::False
             | TokenKind::If
             | TokenKind::Unsafe
+            | TokenKind::Loop
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::Match
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::Lifetime(_)
+            | TokenKind::DotDot
     )
 }
 
+/// Converts a binary operator token into the binary operator of expressions.
+/// Returns None if the token is not used as a binary operator (e.g. `&`).
+pub fn to_ast_binop(op: lexer::BinOp) -> Option<ast::BinOp> {
+    match op {
+        lexer::BinOp::Plus => Some(ast::BinOp::Add),
+        lexer::BinOp::Minus => Some(ast::BinOp::Sub),
+        lexer::BinOp::Star => Some(ast::BinOp::Mul),
+        lexer::BinOp::Eq => Some(ast::BinOp::Eq),
+        lexer::BinOp::Ne => Some(ast::BinOp::Ne),
+        lexer::BinOp::Gt => Some(ast::BinOp::Gt),
+        lexer::BinOp::Lt => Some(ast::BinOp::Lt),
+        lexer::BinOp::AndAnd => Some(ast::BinOp::And),
+        lexer::BinOp::And => None,
+    }
+}
+
+/// Returns an error message if the expression cannot be assigned to.
+/// Only place expressions (e.g. `x`, `a[0]`, `s.f`, `*p`) are valid targets.
+/// ref: https://doc.rust-lang.org/reference/expressions.html#place-expressions-and-value-expressions
+fn invalid_assign_target_message(expr: &Expr) -> Option<&'static str> {
+    match &expr.kind {
+        ExprKind::Path(_)
+        | ExprKind::Index(..)
+        | ExprKind::Field(..)
+        | ExprKind::Unary(UnOp::Deref, _) => None,
+        ExprKind::Paren(inner) => invalid_assign_target_message(inner),
+        ExprKind::NumLit(..)
+        | ExprKind::FloatLit(..)
+        | ExprKind::BoolLit(_)
+        | ExprKind::StrLit(_)
+        | ExprKind::CharLit(_)
+        | ExprKind::ByteLit(_)
+        | ExprKind::ByteStrLit(_)
+        | ExprKind::Unit => Some("Cannot assign to a literal"),
+        ExprKind::Binary(..)
+        | ExprKind::Unary(..)
+        | ExprKind::Cast(..)
+        | ExprKind::Call(..)
+        | ExprKind::Range(..)
+        | ExprKind::Try(_)
+        | ExprKind::Await(_) => Some("Cannot assign to the result of an expression"),
+        _ => Some("Invalid left-hand side of assignment"),
+    }
+}
+
+/// Fields of a struct expression and its base (e.g. `..base`) if any
+type StructExprFields = (Vec<(Ident, Box<Expr>)>, Option<Box<Expr>>);
+
+/// Binding powers of operators other than binary ones.
+/// Operators with higher binding power bind tighter.
+/// Binary operators use `ast::BinOp::precedence` (1 to 4).
+/// ref: https://doc.rust-lang.org/reference/expressions.html#expression-precedence
+struct Bp;
+
+impl Bp {
+    const CAST: u8 = 5;
+    const PREFIX: u8 = 6;
+    const POSTFIX: u8 = 7;
+}
+
+/// Precedences of binary operators used by the Pratt parser, which can be
+/// customized for experimentation (see `Parser::set_precedence_table`).
+/// Defaults to `ast::BinOp::precedence`.
+pub struct PrecedenceTable {
+    precedences: Vec<(ast::BinOp, u8)>,
+}
+
+impl Default for PrecedenceTable {
+    fn default() -> Self {
+        let precedences = ast::BinOp::ALL
+            .iter()
+            .map(|op| (op.clone(), op.precedence()))
+            .collect();
+        PrecedenceTable { precedences }
+    }
+}
+
+impl PrecedenceTable {
+    /// Override the default precedences of the given operators.
+    /// Precedences must be between 1 and `Bp::CAST - 1` (i.e. looser than `as`), and
+    /// non-associative operators (comparisons) cannot share a precedence with associative ones.
+    pub fn new(overrides: &[(ast::BinOp, u8)]) -> Result<Self, String> {
+        let mut table = PrecedenceTable::default();
+        for (i, (op, prec)) in overrides.iter().enumerate() {
+            if overrides[..i].iter().any(|(prev, _)| prev == op) {
+                return Err(format!("Duplicate precedence of {:?}", op));
+            }
+            if !(1..Bp::CAST).contains(prec) {
+                return Err(format!(
+                    "Precedence of {:?} must be between 1 and {}",
+                    op,
+                    Bp::CAST - 1
+                ));
+            }
+            for entry in &mut table.precedences {
+                if entry.0 == *op {
+                    entry.1 = *prec;
+                }
+            }
+        }
+        for (op, prec) in &table.precedences {
+            let conflict = table
+                .precedences
+                .iter()
+                .find(|(other, p)| p == prec && other.is_left_assoc() != op.is_left_assoc());
+            if let Some((other, _)) = conflict {
+                return Err(format!(
+                    "{:?} and {:?} have the same precedence but different associativity",
+                    op, other
+                ));
+            }
+        }
+        Ok(table)
+    }
+
+    fn precedence(&self, op: &ast::BinOp) -> u8 {
+        self.precedences.iter().find(|(o, _)| o == op).unwrap().1
+    }
+}
+
+/// Converts a token into the prefix operator.
+/// Returns None if the token is not a prefix operator.
+fn to_prefix_op(kind: &TokenKind) -> Option<UnOp> {
+    match kind {
+        TokenKind::BinOp(lexer::BinOp::Plus) => Some(UnOp::Plus),
+        TokenKind::BinOp(lexer::BinOp::Minus) => Some(UnOp::Minus),
+        TokenKind::BinOp(lexer::BinOp::Star) => Some(UnOp::Deref),
+        TokenKind::BinOp(lexer::BinOp::And) => Some(UnOp::Ref),
+        TokenKind::Bang => Some(UnOp::Not),
+        _ => None,
+    }
+}
+
 impl Parser {
     /// expr ::= assign
     pub fn parse_expr(&mut self) -> Option<Expr> {
-        self.parse_assign()
+        // struct literals are allowed in nested expressions (e.g. `match (S { a: 1 }) {}`)
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let expr = self.parse_assign();
+        self.no_struct_literal = no_struct_literal;
+        expr
+    }
+
+    /// Parse a whole source as an expression.
+    /// Tokens left after the expression are reported as an error.
+    pub fn parse_expr_to_eof(&mut self) -> Option<Expr> {
+        let expr = self.parse_expr()?;
+        if !self.at_eof() {
+            let t = self.peek_token().clone();
+            self.error(
+                format!("Unexpected token after expression: `{}`", t),
+                t.span,
+            );
+            return None;
+        }
+        Some(expr)
+    }
+
+    /// Parse an expression followed by a block, where struct literals are not allowed
+    fn parse_expr_no_struct(&mut self) -> Option<Expr> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+        let expr = self.parse_assign();
+        self.no_struct_literal = no_struct_literal;
+        expr
+    }
+
+    /// matchExpr ::= "match" expr "{" (matchArm ",")* matchArm? "}"
+    /// matchArm ::= pattern ("if" expr)? "=>" expr
+    /// NOTE: comma can be omitted only after an arm whose body is block-like
+    /// ref: https://doc.rust-lang.org/reference/expressions/match-expr.html
+    fn parse_match_expr(&mut self) -> Option<Expr> {
+        // skip "match"
+        let mut span = self.skip_token().span;
+        let scrutinee = self.parse_expr_no_struct()?;
+
+        if !self.skip_expected_token(TokenKind::OpenBrace) {
+            self.error_expected("'{' for match expression");
+            return None;
+        }
+
+        let mut arms = vec![];
+        while !self.peek_is(&TokenKind::CloseBrace) {
+            let pat = self.parse_pattern()?;
+            let guard = if self.skip_expected_token(TokenKind::If) {
+                Some(Box::new(self.parse_expr()?))
+            } else {
+                None
+            };
+            self.expect_token(TokenKind::FatArrow)?;
+            let body = self.parse_expr()?;
+            // e.g. `0 => { 1 } _ => 2`
+            let needs_comma = !body.is_block_like();
+            arms.push(MatchArm {
+                span: pat.span.concat(&body.span),
+                pat,
+                guard,
+                body: Box::new(body),
+            });
+            // comma can be omitted after the last arm
+            if !self.skip_expected_token(TokenKind::Comma) && needs_comma {
+                break;
+            }
+        }
+
+        span = span.concat(&self.peek_token().span);
+        if !self.skip_expected_token(TokenKind::CloseBrace) {
+            self.error_expected("'}' for match expression");
+            return None;
+        }
+
+        Some(Expr {
+            kind: ExprKind::Match(Box::new(scrutinee), arms),
+            id: self.get_next_id(),
+            span,
+        })
     }
 
     /// ifExpr ::= "if" expr  block ("else" (block | ifExpr))?
     fn parse_if_expr(&mut self) -> Option<Expr> {
         let mut span = self.peek_token().span.clone();
         if !self.skip_expected_token(TokenKind::If) {
-            eprintln!(
-                "Expected \"if\", but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("\"if\"");
             return None;
         }
-        let cond = self.parse_expr()?;
+        // `if x {}` is not `if (x {})`
+        let cond = self.parse_expr_no_struct()?;
 
         // parse then block
         let then_block = self.parse_block()?;
@@ -77,168 +301,329 @@ impl Parser {
         })
     }
 
-    /// assign ::= equality ("=" assign)?
-    fn parse_assign(&mut self) -> Option<Expr> {
-        let lhs = self.parse_binary_equality()?;
-        let t = self.lexer.peek_token();
-        if t.kind != TokenKind::Eq {
-            return Some(lhs);
+    /// loopExpr ::= (label ":")? ("loop" block | "while" expr block | forExpr) | labeledBlock
+    /// forExpr ::= "for" pattern "in" expr block
+    /// labeledBlock ::= label ":" block
+    /// ref: https://doc.rust-lang.org/reference/expressions/loop-expr.html
+    fn parse_loop_expr(&mut self) -> Option<Expr> {
+        let mut span = self.peek_token().span.clone();
+        let label = if let TokenKind::Lifetime(_) = self.peek_token().kind {
+            let label = self.parse_label()?;
+            if !self.skip_expected_token(TokenKind::Colon) {
+                self.error_expected("':' after label");
+                return None;
+            }
+            Some(label)
+        } else {
+            None
+        };
+
+        if let Some(label) = &label
+            && self.peek_is(&TokenKind::OpenBrace)
+        {
+            let body = self.parse_block()?;
+            return Some(Expr {
+                span: span.concat(&body.span),
+                kind: ExprKind::LabeledBlock(body, label.clone()),
+                id: self.get_next_id(),
+            });
         }
-        self.skip_token();
-        let rhs = self.parse_assign()?;
-        Some(Expr {
-            span: lhs.span.concat(&rhs.span),
-            kind: ExprKind::Assign(Box::new(lhs), Box::new(rhs)),
-            id: self.get_next_id(),
-        })
-    }
 
-    /// equality ::= relational (("=="|"!=") equality)?
-    fn parse_binary_equality(&mut self) -> Option<Expr> {
-        let lhs = self.parse_binary_relational()?;
-        let t = self.lexer.peek_token();
-        let binop = match t.kind {
-            TokenKind::BinOp(lexer::BinOp::Eq) => ast::BinOp::Eq,
-            TokenKind::BinOp(lexer::BinOp::Ne) => ast::BinOp::Ne,
+        let t = self.skip_token();
+        let kind = match t.kind {
+            TokenKind::Loop => {
+                let body = self.parse_block()?;
+                span = span.concat(&body.span);
+                ExprKind::Loop(body, label)
+            }
+            TokenKind::While => {
+                // `while x {}` is not `while (x {})`
+                let cond = self.parse_expr_no_struct()?;
+                let body = self.parse_block()?;
+                span = span.concat(&body.span);
+                ExprKind::While(Box::new(cond), body, label)
+            }
+            TokenKind::For => {
+                let pat = self.parse_pattern()?;
+                if !self.skip_expected_token(TokenKind::In) {
+                    self.error_expected("\"in\"");
+                    return None;
+                }
+                // `for x in s {}` is not `for x in (s {})`
+                let iter = self.parse_expr_no_struct()?;
+                let body = self.parse_block()?;
+                span = span.concat(&body.span);
+                ExprKind::For(pat, Box::new(iter), body, label)
+            }
             _ => {
-                return Some(lhs);
+                let span = t.span.clone();
+                self.error(
+                    format!("Expected \"loop\", \"while\" or \"for\", but found `{}`", t),
+                    span,
+                );
+                return None;
             }
         };
-        self.lexer.skip_token();
-
-        let rhs = self.parse_binary_equality()?;
 
         Some(Expr {
-            span: lhs.span.concat(&rhs.span),
-            kind: ExprKind::Binary(binop, Box::new(lhs), Box::new(rhs)),
+            kind,
             id: self.get_next_id(),
+            span,
         })
     }
 
-    /// relational ::= add (("=="|"!=") relational)?
-    fn parse_binary_relational(&mut self) -> Option<Expr> {
-        let lhs = self.parse_binary_add()?;
-        let t = self.lexer.peek_token();
-        let binop = match t.kind {
-            TokenKind::BinOp(lexer::BinOp::Lt) => ast::BinOp::Lt,
-            TokenKind::BinOp(lexer::BinOp::Gt) => ast::BinOp::Gt,
-            _ => {
-                return Some(lhs);
-            }
+    /// breakExpr ::= "break" label? expr?
+    /// continueExpr ::= "continue" label?
+    fn parse_break_or_continue_expr(&mut self) -> Option<Expr> {
+        let t = self.skip_token();
+        let mut span = t.span;
+        let label = if let TokenKind::Lifetime(_) = self.peek_token().kind {
+            let label = self.parse_label()?;
+            span = span.concat(&label.span);
+            Some(label)
+        } else {
+            None
         };
-        self.lexer.skip_token();
 
-        let rhs = self.parse_binary_relational()?;
+        let kind = if t.kind == TokenKind::Break {
+            let value = if is_expr_start(self.peek_token()) {
+                let value = self.parse_expr()?;
+                span = span.concat(&value.span);
+                Some(Box::new(value))
+            } else {
+                None
+            };
+            ExprKind::Break(label, value)
+        } else {
+            ExprKind::Continue(label)
+        };
 
         Some(Expr {
-            span: lhs.span.concat(&rhs.span),
-            kind: ExprKind::Binary(binop, Box::new(lhs), Box::new(rhs)),
+            kind,
             id: self.get_next_id(),
+            span,
         })
     }
 
-    /// add ::= mul (("+"|"-") add)?
-    fn parse_binary_add(&mut self) -> Option<Expr> {
-        let lhs = self.parse_binary_mul()?;
-        let t = self.lexer.peek_token();
-        let binop = match t.kind {
-            TokenKind::BinOp(lexer::BinOp::Plus) => ast::BinOp::Add,
-            TokenKind::BinOp(lexer::BinOp::Minus) => ast::BinOp::Sub,
-            _ => {
-                return Some(lhs);
-            }
-        };
-        self.lexer.skip_token();
-
-        let rhs = self.parse_binary_add()?;
+    /// label ::= lifetime
+    /// NOTE: `'` is not included in the symbol
+    fn parse_label(&mut self) -> Option<Ident> {
+        let t = self.skip_token();
+        if let TokenKind::Lifetime(name) = t.kind {
+            Some(Ident {
+                symbol: Symbol::intern(&name),
+                span: t.span,
+            })
+        } else {
+            let span = t.span.clone();
+            self.error(format!("Expected label, but found `{}`", t), span);
+            None
+        }
+    }
 
+    /// assign ::= range (("=" | "+=" | "-=" | "*=") assign)?
+    fn parse_assign(&mut self) -> Option<Expr> {
+        let lhs = self.parse_range()?;
+        let op = match self.peek_token().kind {
+            TokenKind::Eq => None,
+            TokenKind::BinOpEq(op) => to_ast_binop(op),
+            _ => return Some(lhs),
+        };
+        let eq_span = self.skip_token().span;
+        // report invalid targets, but keep the tree for later passes
+        if let Some(msg) = invalid_assign_target_message(&lhs) {
+            self.error(msg.to_string(), eq_span.clone());
+        }
+        let rhs = self.parse_assign()?;
+        let span = lhs.span.concat(&rhs.span);
+        let kind = match op {
+            Some(op) => ExprKind::AssignOp(op, Box::new(lhs), Box::new(rhs), eq_span),
+            None => ExprKind::Assign(Box::new(lhs), Box::new(rhs), eq_span),
+        };
         Some(Expr {
-            span: lhs.span.concat(&rhs.span),
-            kind: ExprKind::Binary(binop, Box::new(lhs), Box::new(rhs)),
+            span,
+            kind,
             id: self.get_next_id(),
         })
     }
 
-    /// mul ::= typeCastExpr ("*" mul)?
-    fn parse_binary_mul(&mut self) -> Option<Expr> {
-        let lhs = self.parse_binary_cast()?;
-        let t = self.lexer.peek_token();
-        let binop = match t.kind {
-            TokenKind::BinOp(lexer::BinOp::Star) => ast::BinOp::Mul,
-            _ => {
-                return Some(lhs);
+    /// range ::= binary | binary? ".." binary?
+    /// https://doc.rust-lang.org/reference/expressions/range-expr.html
+    fn parse_range(&mut self) -> Option<Expr> {
+        let start = if self.peek_is(&TokenKind::DotDot) {
+            None
+        } else {
+            let start = self.parse_binary(0)?;
+            if !self.peek_is(&TokenKind::DotDot) {
+                return Some(start);
             }
+            Some(start)
         };
-        self.lexer.skip_token();
 
-        let rhs = self.parse_binary_mul()?;
+        // skip `..`
+        let mut span = self.skip_token().span;
+        if let Some(start) = &start {
+            span = span.concat(&start.span);
+        }
+        let end = if is_expr_start(self.peek_token()) {
+            let end = self.parse_binary(0)?;
+            span = span.concat(&end.span);
+            Some(end)
+        } else {
+            None
+        };
 
         Some(Expr {
-            span: lhs.span.concat(&rhs.span),
-            kind: ExprKind::Binary(binop, Box::new(lhs), Box::new(rhs)),
+            kind: ExprKind::Range(start.map(Box::new), end.map(Box::new)),
             id: self.get_next_id(),
+            span,
         })
     }
 
-    /// typeCastExpr ::= unary ("as" type)*
-    fn parse_binary_cast(&mut self) -> Option<Expr> {
-        let mut ret = self.parse_binary_unary()?;
+    /// binary ::= prefixOp* primary (postfixOp | "as" type | binOp binary)*
+    /// prefixOp ::= "+" | "-" | "!" | "*" | "&"
+    /// postfixOp ::= "?" | callExpr | indexExpr | fieldExpr
+    /// binOp ::= "&&" | "==" | "!=" | "<" | ">" | "+" | "-" | "*"
+    /// Parses operators whose binding power is `min_bp` or higher by Pratt parsing.
+    /// Prefix, postfix, and binary operators share the binding power table (see `Bp`).
+    fn parse_binary(&mut self, min_bp: u8) -> Option<Expr> {
+        // Prefix operators are collected iteratively so that a long sequence of them does not overflow the stack.
+        let mut prefix_ops = vec![];
+        while let Some(unop) = to_prefix_op(&self.peek_token().kind) {
+            // skip prefix op token
+            let span = self.skip_token().span;
+            prefix_ops.push((unop, span));
+        }
+        let mut lhs = if prefix_ops.is_empty() {
+            self.parse_primary()?
+        } else {
+            // postfix operators bind tighter than prefix ones, e.g. `-a.b()` is `-(a.b())`
+            let mut expr = self.parse_binary(Bp::PREFIX)?;
+            // apply from the innermost operator
+            while let Some((unop, span)) = prefix_ops.pop() {
+                expr = Expr {
+                    span: span.concat(&expr.span),
+                    kind: ExprKind::Unary(unop, Box::new(expr)),
+                    id: self.get_next_id(),
+                };
+            }
+            expr
+        };
+        // the last operator if it is non-associative
+        let mut non_assoc: Option<ast::BinOp> = None;
 
-        while self.peek_token().kind == TokenKind::As {
+        loop {
+            let t = self.peek_token();
+            match &t.kind {
+                // postfix operators are applied from left to right, e.g. `a.b()[0].c` is `((a.b)())[0].c`.
+                // FIXME: disambiguity: () () => FuncCall or ExprStmt ExprStmt
+                TokenKind::OpenParen
+                | TokenKind::OpenBracket
+                | TokenKind::Dot
+                | TokenKind::Question
+                    if Bp::POSTFIX >= min_bp =>
+                {
+                    lhs = match t.kind {
+                        TokenKind::OpenParen => self.parse_call_expr(lhs, vec![])?,
+                        TokenKind::OpenBracket => self.parse_index_expr(lhs)?,
+                        TokenKind::Dot => self.parse_field_expr(lhs)?,
+                        _ => {
+                            let span = lhs.span.concat(&self.skip_token().span);
+                            Expr {
+                                kind: ExprKind::Try(Box::new(lhs)),
+                                id: self.get_next_id(),
+                                span,
+                            }
+                        }
+                    };
+                    continue;
+                }
+                // typeCastExpr ::= binary "as" type
+                TokenKind::As if Bp::CAST >= min_bp => {
+                    self.skip_token();
+                    let ty = self.parse_type()?;
+                    lhs = Expr {
+                        span: lhs.span.concat(&ty.span),
+                        kind: ExprKind::Cast(Box::new(lhs), ty),
+                        id: self.get_next_id(),
+                    };
+                    continue;
+                }
+                _ => (),
+            }
+
+            let TokenKind::BinOp(op) = self.peek_token().kind else {
+                break;
+            };
+            let Some(binop) = to_ast_binop(op) else { break };
+            let prec = self.precedences.precedence(&binop);
+            if prec < min_bp {
+                break;
+            }
+            if let Some(prev) = &non_assoc
+                && self.precedences.precedence(prev) == prec
+            {
+                let span = self.peek_token().span.clone();
+                self.error(
+                    format!(
+                        "Comparison operators cannot be chained: `{}`",
+                        span.to_snippet()
+                    ),
+                    span,
+                );
+                return None;
+            }
             self.skip_token();
-            let mut span = ret.span.clone();
-            let ty = self.parse_type()?;
-            span = span.concat(&ty.span);
-            ret = Expr {
-                kind: ExprKind::Cast(Box::new(ret), ty),
+
+            // operands of the rhs must bind tighter than this operator
+            let rhs = self.parse_binary(prec + 1)?;
+
+            non_assoc = if binop.is_left_assoc() {
+                None
+            } else {
+                Some(binop.clone())
+            };
+            lhs = Expr {
+                span: lhs.span.concat(&rhs.span),
+                kind: ExprKind::Binary(binop, Box::new(lhs), Box::new(rhs)),
                 id: self.get_next_id(),
-                span,
             };
         }
-        Some(ret)
+        Some(lhs)
     }
 
-    /// unary ::= ("+"|"-")? primary
-    fn parse_binary_unary(&mut self) -> Option<Expr> {
-        let span = self.peek_token().span.clone();
-        let t = self.lexer.peek_token();
-        let unup = match &t.kind {
-            TokenKind::BinOp(lexer::BinOp::Plus) => UnOp::Plus,
-            TokenKind::BinOp(lexer::BinOp::Minus) => UnOp::Minus,
-            _ => {
-                return self.parse_binary_primary();
-            }
-        };
-        // skip unary op token
-        self.skip_token();
-        // parse primary
-        let primary = self.parse_binary_primary()?;
-
-        Some(Expr {
-            span: span.concat(&primary.span),
-            kind: ExprKind::Unary(unup, Box::new(primary)),
-            id: self.get_next_id(),
-        })
-    }
-
-    /// primary ::= num | true | false | stringLit
-    ///     | ident | callExpr | indexExpr | ifExpr
+    /// primary ::= num | true | false | stringLit | charLit | byteLit | byteStrLit
+    ///     | ident | ifExpr | matchExpr | loopExpr
+    ///     | breakExpr | continueExpr
     ///     | returnExpr | "(" expr ")"
     ///     | unsafeBlock | block
     ///     | arrayExpr
-    ///     | fieldExpr | structExpr
-    ///     | typeCastExpr
+    ///     | structExpr
     /// returnExpr ::= "return" expr
     /// ref: https://doc.rust-lang.org/reference/expressions.html#expression-precedence
-    fn parse_binary_primary(&mut self) -> Option<Expr> {
-        let t = &self.lexer.peek_token();
-        let mut expr = match t.kind {
-            TokenKind::NumLit(n) => {
-                let span = self.skip_token().span;
+    fn parse_primary(&mut self) -> Option<Expr> {
+        let t = &self.peek_token();
+        let expr = match t.kind {
+            TokenKind::NumLit(..) => {
+                let t = self.skip_token();
+                let TokenKind::NumLit(n, suffix) = t.kind else {
+                    unreachable!()
+                };
                 Expr {
-                    kind: ExprKind::NumLit(n),
+                    kind: ExprKind::NumLit(n, suffix),
                     id: self.get_next_id(),
-                    span,
+                    span: t.span,
+                }
+            }
+            TokenKind::FloatLit(..) => {
+                let t = self.skip_token();
+                let TokenKind::FloatLit(n, suffix) = t.kind else {
+                    unreachable!()
+                };
+                Expr {
+                    kind: ExprKind::FloatLit(n, suffix),
+                    id: self.get_next_id(),
+                    span: t.span,
                 }
             }
             TokenKind::True => {
@@ -259,21 +644,61 @@ impl Parser {
             }
             TokenKind::StrLit(_) => {
                 let t = self.skip_token();
-                let TokenKind::StrLit(s) = t.kind else { unreachable!() };
+                let TokenKind::StrLit(s) = t.kind else {
+                    unreachable!()
+                };
                 Expr {
                     kind: ExprKind::StrLit(s),
                     id: self.get_next_id(),
                     span: t.span,
                 }
             }
+            TokenKind::CharLit(c) => {
+                let span = self.skip_token().span;
+                Expr {
+                    kind: ExprKind::CharLit(c),
+                    id: self.get_next_id(),
+                    span,
+                }
+            }
+            TokenKind::ByteLit(b) => {
+                let span = self.skip_token().span;
+                Expr {
+                    kind: ExprKind::ByteLit(b),
+                    id: self.get_next_id(),
+                    span,
+                }
+            }
+            TokenKind::ByteStrLit(_) => {
+                let t = self.skip_token();
+                let TokenKind::ByteStrLit(s) = t.kind else {
+                    unreachable!()
+                };
+                Expr {
+                    kind: ExprKind::ByteStrLit(s),
+                    id: self.get_next_id(),
+                    span: t.span,
+                }
+            }
             TokenKind::If => self.parse_if_expr()?,
+            TokenKind::Match => self.parse_match_expr()?,
+            TokenKind::Loop | TokenKind::While | TokenKind::For | TokenKind::Lifetime(_) => {
+                self.parse_loop_expr()?
+            }
+            TokenKind::Break | TokenKind::Continue => self.parse_break_or_continue_expr()?,
             TokenKind::Return => {
-                // TODO: parse `return;`
-                let span = self.skip_token().span;
-                let e = self.parse_expr()?;
+                let mut span = self.skip_token().span;
+                // e.g. `return;`, `{ return }`
+                let value = if is_expr_start(self.peek_token()) {
+                    let value = self.parse_expr()?;
+                    span = span.concat(&value.span);
+                    Some(Box::new(value))
+                } else {
+                    None
+                };
                 Expr {
-                    span: span.concat(&e.span),
-                    kind: ExprKind::Return(Box::new(e)),
+                    span,
+                    kind: ExprKind::Return(value),
                     id: self.get_next_id(),
                 }
             }
@@ -297,20 +722,28 @@ impl Parser {
                     }
                 } else {
                     let expr = self.parse_expr()?;
-                    span = span.concat(&self.peek_token().span);
                     // skip ')'
-                    if !self.skip_expected_token(TokenKind::CloseParen) {
-                        eprintln!(
-                            "Expected ')', but found `{}`",
-                            self.peek_token().span.to_snippet()
-                        );
-                        return None;
+                    match self.expect(TokenKind::CloseParen) {
+                        Ok(t) => span = span.concat(&t.span),
+                        Err(e) => {
+                            // recover as if `)` were there
+                            self.emit(e);
+                            span = span.concat(&expr.span);
+                        }
                     }
-                    // just expand span
-                    Expr {
-                        kind: expr.kind,
-                        span,
-                        id: expr.id,
+                    if self.preserve_parens {
+                        Expr {
+                            kind: ExprKind::Paren(Box::new(expr)),
+                            id: self.get_next_id(),
+                            span,
+                        }
+                    } else {
+                        // just expand span
+                        Expr {
+                            kind: expr.kind,
+                            span,
+                            id: expr.id,
+                        }
                     }
                 }
             }
@@ -336,37 +769,50 @@ impl Parser {
                 }
             }
             _ => {
-                eprintln!(
-                    "Expected num or (expr), but found `{}`",
-                    t.span.to_snippet()
-                );
+                self.error_expected("num or (expr)");
                 return None;
             }
         };
-
-        // deal with tailing `(...)` (func call), `[...]` (indexing), .ident (field access)
-        // FIXME: disambiguity: () () => FuncCall or ExprStmt ExprStmt
-        loop {
-            let t = self.peek_token();
-            match &t.kind {
-                TokenKind::OpenParen => {
-                    expr = self.parse_call_expr(expr)?;
-                }
-                TokenKind::OpenBracket => expr = self.parse_index_expr(expr)?,
-                TokenKind::Dot => expr = self.parse_field_expr(expr)?,
-                _ => break,
-            }
-        }
-
         Some(expr)
     }
 
-    /// ident | structExpr
+    /// ident | structExpr | turbofishCallExpr | macroCallExpr
+    /// turbofishCallExpr ::= path "::" genericArgs callExpr
     fn parse_ident_or_struct_expr(&mut self) -> Option<Expr> {
-        let path = self.parse_path()?;
+        let (path, type_args) = self.parse_expr_path()?;
+        if let Some(type_args) = type_args {
+            // `<` is not a relational operator here because it follows `::`
+            if !self.peek_is(&TokenKind::OpenParen) {
+                self.error_expected("'(' after generic arguments");
+                return None;
+            }
+            let callee = Expr {
+                span: path.span.clone(),
+                kind: ExprKind::Path(path),
+                id: self.get_next_id(),
+            };
+            return self.parse_call_expr(callee, type_args);
+        }
+
         let t = self.peek_token();
-        if let TokenKind::OpenBrace = t.kind {
+        if let TokenKind::Bang = t.kind {
+            self.parse_macro_call_expr(path)
+        } else if let TokenKind::OpenBrace = t.kind
+            && !self.no_struct_literal
+        {
             self.parse_struct_expr(path)
+        } else if self.peek_is(&TokenKind::OpenBrace)
+            && matches!(self.peek_nth(1).kind, TokenKind::Ident(_))
+            && self.peek_nth(2).kind == TokenKind::Colon
+        {
+            // `if P { x: 1 } {}` is likely to be a struct literal, so parse it as one to recover
+            let path_span = path.span.clone();
+            let expr = self.parse_struct_expr(path)?;
+            self.error(
+                "Struct literals are not allowed here without parentheses".to_string(),
+                path_span.concat(&expr.span),
+            );
+            Some(expr)
         } else {
             Some(Expr {
                 span: path.span.clone(),
@@ -376,64 +822,165 @@ impl Parser {
         }
     }
 
+    /// macroCallExpr ::= ident "!" delimTokenTree
+    /// delimTokenTree ::= "(" token* ")" | "[" token* "]" | "{" token* "}"
+    /// Tokens are captured as is. Delimiters in them must be balanced.
+    /// NOTE: first ident is already parsed
+    fn parse_macro_call_expr(&mut self, path: Path) -> Option<Expr> {
+        // `!`
+        self.skip_token();
+        let [name] = &path.segments[..] else {
+            let span = path.span.clone();
+            self.error(
+                format!("Macro paths are not supported: `{}`", span.to_snippet()),
+                span,
+            );
+            return None;
+        };
+        let name = name.clone();
+
+        let open = self.skip_token();
+        let delim = match open.kind {
+            TokenKind::OpenParen => Delim::Paren,
+            TokenKind::OpenBracket => Delim::Bracket,
+            TokenKind::OpenBrace => Delim::Brace,
+            _ => {
+                let span = open.span.clone();
+                self.error(
+                    format!(
+                        "Expected '(', '[' or '{{' for macro invocation, but found `{}`",
+                        open
+                    ),
+                    span,
+                );
+                return None;
+            }
+        };
+
+        let mut tokens = vec![];
+        let mut depth = 0;
+        let close = loop {
+            let t = self.skip_token();
+            match t.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                    if depth == 0 {
+                        break t;
+                    }
+                    depth -= 1;
+                }
+                TokenKind::Eof => {
+                    let span = open.span;
+                    self.error(
+                        format!(
+                            "Unclosed delimiter `{}` of macro invocation",
+                            span.to_snippet()
+                        ),
+                        span,
+                    );
+                    return None;
+                }
+                _ => (),
+            }
+            tokens.push(t);
+        };
+
+        let expected_close = match delim {
+            Delim::Paren => TokenKind::CloseParen,
+            Delim::Bracket => TokenKind::CloseBracket,
+            Delim::Brace => TokenKind::CloseBrace,
+        };
+        if close.kind != expected_close {
+            let span = close.span;
+            self.error(
+                format!("Mismatched closing delimiter `{}`", span.to_snippet()),
+                span,
+            );
+            return None;
+        }
+
+        Some(Expr {
+            span: path.span.concat(&close.span),
+            kind: ExprKind::MacroCall(name, delim, tokens),
+            id: self.get_next_id(),
+        })
+    }
+
     /// structExpr ::= ident "{" structExprFields? "}"
     /// NOTE: first ident is already parsed
     fn parse_struct_expr(&mut self, path: Path) -> Option<Expr> {
         let mut span = self.peek_token().span.clone();
 
         if !self.skip_expected_token(TokenKind::OpenBrace) {
-            eprintln!(
-                "Expected '{{ for struct expr', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'{ for struct expr'");
             return None;
         }
 
-        let fields = if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+        let (fields, base) = if self.at(&TokenKind::Ident(String::new())) {
             self.parse_struct_expr_fields()?
+        } else if self.peek_is(&TokenKind::DotDot) {
+            (vec![], Some(self.parse_struct_base()?))
         } else {
-            vec![]
+            (vec![], None)
         };
 
         span = span.concat(&self.peek_token().span);
         if !self.skip_expected_token(TokenKind::CloseBrace) {
-            eprintln!(
-                "Expected '}}' for struct expr, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'}' for struct expr");
             return None;
         }
         Some(Expr {
-            kind: ExprKind::Struct(path, fields),
+            kind: ExprKind::Struct(path, fields, base),
             id: self.get_next_id(),
             span,
         })
     }
 
-    /// structExprFields ::= structExprField ("," structExprField)* ","?
-    fn parse_struct_expr_fields(&mut self) -> Option<Vec<(Ident, Box<Expr>)>> {
+    /// structExprFields ::= structExprField ("," structExprField)* ("," structBase | ","?)
+    /// Returns the fields and the base if any
+    fn parse_struct_expr_fields(&mut self) -> Option<StructExprFields> {
         let mut fds = vec![];
         fds.push(self.parse_struct_expr_field()?);
 
-        while matches!(self.peek_token().kind, TokenKind::Comma) {
+        while self.peek_is(&TokenKind::Comma) {
             self.skip_token();
-            if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+            if self.at(&TokenKind::Ident(String::new())) {
                 fds.push(self.parse_struct_expr_field()?);
+            } else if self.peek_is(&TokenKind::DotDot) {
+                // base must be the last element
+                let base = self.parse_struct_base()?;
+                return Some((fds, Some(base)));
             }
         }
-        Some(fds)
+        Some((fds, None))
+    }
+
+    /// structBase ::= ".." expr
+    fn parse_struct_base(&mut self) -> Option<Box<Expr>> {
+        self.expect_token(TokenKind::DotDot)?;
+        Some(Box::new(self.parse_expr()?))
     }
 
-    /// structExprField ::= ident ":" expr
+    /// structExprField ::= ident (":" expr)?
+    /// `ident` alone is a shorthand for `ident: ident`
     /// https://doc.rust-lang.org/reference/expressions/struct-expr.html
     fn parse_struct_expr_field(&mut self) -> Option<(Ident, Box<Expr>)> {
         let ident = self.parse_ident()?;
         if !self.skip_expected_token(TokenKind::Colon) {
-            eprintln!(
-                "Expected ':', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
+            let t = self.peek_token();
+            if !matches!(t.kind, TokenKind::Comma | TokenKind::CloseBrace) {
+                self.error_expected("':', ',' or '}'");
+                return None;
+            }
+            let expr = Expr {
+                span: ident.span.clone(),
+                kind: ExprKind::Path(Path {
+                    span: ident.span.clone(),
+                    segments: vec![ident.clone()],
+                }),
+                id: self.get_next_id(),
+            };
+            return Some((ident, Box::new(expr)));
         }
         let expr = self.parse_expr()?;
         Some((ident, Box::new(expr)))
@@ -446,21 +993,14 @@ impl Parser {
 
         // skip '['
         self.skip_token();
-        let elems = if self.peek_token().kind == TokenKind::CloseBracket {
+        let elems = if self.peek_is(&TokenKind::CloseBracket) {
             vec![]
         } else {
             self.parse_array_elements()?
         };
 
         // skip ']'
-        span = span.concat(&self.peek_token().span);
-        if !self.skip_expected_token(TokenKind::CloseBracket) {
-            eprintln!(
-                "Expected ']', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        span = span.concat(&self.expect_token(TokenKind::CloseBracket)?.span);
         Some(Expr {
             kind: ExprKind::Array(elems),
             id: self.get_next_id(),
@@ -475,7 +1015,7 @@ impl Parser {
         let mut elems = vec![];
         elems.push(self.parse_expr()?);
 
-        while matches!(self.peek_token().kind, TokenKind::Comma) {
+        while self.peek_is(&TokenKind::Comma) {
             self.skip_token();
             if is_expr_start(self.peek_token()) {
                 elems.push(self.parse_expr()?);
@@ -486,44 +1026,90 @@ impl Parser {
 
     /// callExpr ::= primary "(" callParams? ")"
     /// NOTE: first primary is already parsed
-    fn parse_call_expr(&mut self, fn_expr: Expr) -> Option<Expr> {
+    fn parse_call_expr(&mut self, fn_expr: Expr, type_args: Vec<Ty>) -> Option<Expr> {
         let mut span = fn_expr.span.clone();
 
         // skip '('
         self.skip_token();
-        let args = if self.peek_token().kind == TokenKind::CloseParen {
+        let args = if self.peek_is(&TokenKind::CloseParen) {
             vec![]
         } else {
             self.parse_call_params()?
         };
 
-        span = span.concat(&self.peek_token().span);
-        if !self.skip_expected_token(TokenKind::CloseParen) {
-            eprintln!(
-                "Expected ')', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        span = span.concat(&self.expect_token(TokenKind::CloseParen)?.span);
         Some(Expr {
-            kind: ExprKind::Call(Box::new(fn_expr), args),
+            kind: ExprKind::Call(Box::new(fn_expr), type_args, args),
             id: self.get_next_id(),
             span,
         })
     }
 
+    /// exprPath ::= ident ("::" ident)* ("::" genericArgs)?
+    /// Returns the path and type arguments given by turbofish if any
+    fn parse_expr_path(&mut self) -> Option<(Path, Option<Vec<Ty>>)> {
+        let ident = self.parse_ident()?;
+        let mut span = ident.span.clone();
+        let mut segs = vec![ident];
+        let mut type_args = None;
+
+        while self.peek_is(&TokenKind::ColCol) {
+            self.skip_token();
+            if self.peek_is(&TokenKind::BinOp(lexer::BinOp::Lt)) {
+                type_args = Some(self.parse_generic_args()?.0);
+                break;
+            }
+            let new_seg = self.parse_ident()?;
+            span = span.concat(&new_seg.span);
+            segs.push(new_seg);
+        }
+
+        let path = Path {
+            span,
+            segments: segs,
+        };
+        Some((path, type_args))
+    }
+
+    /// genericArgs ::= "<" (type ("," type)* ","?)? ">"
+    /// NOTE: `>>` is lexed as two `>` tokens, so nested arguments like `Vec<Vec<i32>>` are closed
+    /// without splitting tokens.
+    /// Returns the arguments and the span of the closing `>`
+    pub fn parse_generic_args(&mut self) -> Option<(Vec<Ty>, Span)> {
+        self.expect_token(TokenKind::BinOp(lexer::BinOp::Lt))?;
+        let mut type_args = vec![];
+        while !self.peek_is(&TokenKind::BinOp(lexer::BinOp::Gt)) {
+            type_args.push(self.parse_type()?);
+            if !self.peek_is(&TokenKind::Comma) {
+                break;
+            }
+            self.skip_token();
+        }
+        let close_span = self.expect_token(TokenKind::BinOp(lexer::BinOp::Gt))?.span;
+        Some((type_args, close_span))
+    }
+
     /// callParams ::= callParam ("," callParam)* ","?
     /// callParam = expr
+    /// NOTE: As in Rust, an assignment is a valid argument (e.g. `f(x = 1)` passes `()`)
     fn parse_call_params(&mut self) -> Option<Vec<Expr>> {
         let mut args = vec![];
         args.push(self.parse_expr()?);
 
-        while matches!(self.peek_token().kind, TokenKind::Comma) {
+        while self.peek_is(&TokenKind::Comma) {
             self.skip_token();
             if is_expr_start(self.peek_token()) {
                 args.push(self.parse_expr()?);
             }
         }
+        if !self.peek_is(&TokenKind::CloseParen) {
+            let err = ParseError::expected(
+                &[TokenKind::CloseParen, TokenKind::Comma],
+                self.peek_token(),
+            );
+            self.emit(err);
+            return None;
+        }
         Some(args)
     }
 
@@ -533,24 +1119,11 @@ impl Parser {
         let mut span = array_expr.span.clone();
 
         // skip '['
-        if !self.skip_expected_token(TokenKind::OpenBracket) {
-            eprintln!(
-                "Expected '[', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        self.expect_token(TokenKind::OpenBracket)?;
         let index = self.parse_expr()?;
 
-        span = span.concat(&self.peek_token().span);
         // skip ']'
-        if !self.skip_expected_token(TokenKind::CloseBracket) {
-            eprintln!(
-                "Expected ']', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        span = span.concat(&self.expect_token(TokenKind::CloseBracket)?.span);
         Some(Expr {
             kind: ExprKind::Index(Box::new(array_expr), Box::new(index)),
             id: self.get_next_id(),
@@ -558,13 +1131,22 @@ impl Parser {
         })
     }
 
-    /// fieldExpr ::= primary "(" callParams? ")"
+    /// fieldExpr ::= primary "." ident
+    /// awaitExpr ::= primary "." "await"
     /// NOTE: first primary is already parsed
     fn parse_field_expr(&mut self, recv: Expr) -> Option<Expr> {
         let mut span = recv.span.clone();
 
         // skip '.'
         self.skip_token();
+        if self.peek_is(&TokenKind::Await) {
+            span = span.concat(&self.skip_token().span);
+            return Some(Expr {
+                kind: ExprKind::Await(Box::new(recv)),
+                id: self.get_next_id(),
+                span,
+            });
+        }
         let fd = self.parse_ident()?;
 
         span = span.concat(&fd.span);
@@ -575,3 +1157,934 @@ impl Parser {
         })
     }
 }
+
+#[test]
+fn test_call_any_callee() {
+    // f()() => (f())()
+    let expr = Parser::from_source("f()()").parse_expr().unwrap();
+    let ExprKind::Call(callee, _, args) = expr.kind else {
+        panic!()
+    };
+    assert!(args.is_empty());
+    assert_eq!(callee.span.to_snippet(), "f()");
+    let ExprKind::Call(f, _, _) = callee.kind else {
+        panic!()
+    };
+    assert!(matches!(f.kind, ExprKind::Path(_)));
+
+    let mut parser = Parser::from_source("(a)(1)");
+    parser.set_preserve_parens(true);
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Call(callee, _, args) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(args.len(), 1);
+    let ExprKind::Paren(a) = callee.kind else {
+        panic!()
+    };
+    assert!(matches!(a.kind, ExprKind::Path(_)));
+
+    let expr = Parser::from_source("arr[0]()").parse_expr().unwrap();
+    let ExprKind::Call(callee, _, _) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(callee.kind, ExprKind::Index(..)));
+}
+
+#[test]
+fn test_postfix_chain() {
+    // a.b()[0].c => (((a.b)())[0]).c
+    let mut parser = Parser::from_source("a.b()[0].c");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Field(index, c) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(c.as_str(), "c");
+    let ExprKind::Index(call, zero) = index.kind else {
+        panic!()
+    };
+    assert!(matches!(zero.kind, ExprKind::NumLit(0, None)));
+    let ExprKind::Call(callee, _, args) = call.kind else {
+        panic!()
+    };
+    assert!(args.is_empty());
+    let ExprKind::Field(a, b) = callee.kind else {
+        panic!()
+    };
+    assert_eq!(b.as_str(), "b");
+    assert!(matches!(a.kind, ExprKind::Path(_)));
+
+    // postfix binds tighter than unary: -a[0] => -(a[0])
+    let mut parser = Parser::from_source("-a[0]");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Unary(_, operand) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::Index(..)));
+}
+
+#[test]
+fn test_byte_lit() {
+    let expr = Parser::from_source("b'A'").parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::ByteLit(65)));
+    let expr = Parser::from_source(r#"b"a\x00""#).parse_expr().unwrap();
+    let ExprKind::ByteStrLit(s) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(s, b"a\0");
+
+    let mut parser = Parser::from_source("b'\u{e9}'");
+    assert!(parser.parse_expr().is_none());
+    assert_eq!(
+        parser.errors()[0].msg,
+        "Non-ASCII character in byte literal: `\u{e9}`"
+    );
+}
+
+#[test]
+fn test_await() {
+    let mut parser = Parser::from_source("foo().await");
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), "foo().await");
+    let ExprKind::Await(fut) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(fut.kind, ExprKind::Call(..)));
+
+    // fut.await? => (fut.await)?
+    let expr = Parser::from_source("fut.await?.x").parse_expr().unwrap();
+    let ExprKind::Field(operand, x) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(x.as_str(), "x");
+    let ExprKind::Try(awaited) = operand.kind else {
+        panic!()
+    };
+    let ExprKind::Await(fut) = awaited.kind else {
+        panic!()
+    };
+    assert!(matches!(fut.kind, ExprKind::Path(_)));
+
+    // `await` is a keyword
+    assert!(Parser::from_source("await").parse_expr().is_none());
+}
+
+#[test]
+fn test_preserve_parens() {
+    let mut parser = Parser::from_source("(1 + 2)");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::Binary(..)));
+
+    let mut parser = Parser::from_source("(1 + 2)");
+    parser.set_preserve_parens(true);
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Paren(inner) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(inner.kind, ExprKind::Binary(..)));
+    assert_eq!(expr.span.to_snippet(), "(1 + 2)");
+}
+
+#[test]
+fn test_missing_close_paren() {
+    let mut parser = Parser::from_source("(1 + 2");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Binary(ast::BinOp::Add, lhs, rhs) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(lhs.kind, ExprKind::NumLit(1, None)));
+    assert!(matches!(rhs.kind, ExprKind::NumLit(2, None)));
+    assert_eq!(expr.span.to_snippet(), "(1 + 2");
+    let [error] = parser.errors() else { panic!() };
+    assert_eq!(error.msg, "Expected `)`, but found `<eof>`");
+
+    // parsing continues after the missing paren
+    let mut parser = Parser::from_source("{ let a: i32 = (1 + 2; a }");
+    let block = parser.parse_block().unwrap();
+    assert_eq!(block.stmts.len(), 2);
+    assert_eq!(parser.errors().len(), 1);
+}
+
+#[test]
+fn test_nested_unary() {
+    let mut parser = Parser::from_source("- + -1");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Unary(UnOp::Minus, inner) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Unary(UnOp::Plus, inner) = inner.kind else {
+        panic!()
+    };
+    let ExprKind::Unary(UnOp::Minus, inner) = inner.kind else {
+        panic!()
+    };
+    assert!(matches!(inner.kind, ExprKind::NumLit(1, None)));
+
+    // does not overflow the stack
+    let src = format!("{}1", "-".repeat(10_000));
+    let mut parser = Parser::from_source(&src);
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.hi(), 10_001);
+    // drop deeply nested expression without recursion
+    let mut expr = expr;
+    while let ExprKind::Unary(_, inner) = expr.kind {
+        expr = *inner;
+    }
+}
+
+#[test]
+fn test_to_ast_binop() {
+    let pairs = [
+        (lexer::BinOp::Plus, Some(ast::BinOp::Add)),
+        (lexer::BinOp::Minus, Some(ast::BinOp::Sub)),
+        (lexer::BinOp::Star, Some(ast::BinOp::Mul)),
+        (lexer::BinOp::Eq, Some(ast::BinOp::Eq)),
+        (lexer::BinOp::Ne, Some(ast::BinOp::Ne)),
+        (lexer::BinOp::Gt, Some(ast::BinOp::Gt)),
+        (lexer::BinOp::Lt, Some(ast::BinOp::Lt)),
+        (lexer::BinOp::AndAnd, Some(ast::BinOp::And)),
+        (lexer::BinOp::And, None),
+    ];
+    for (op, expected) in pairs {
+        assert_eq!(to_ast_binop(op), expected);
+    }
+}
+
+#[test]
+fn test_turbofish() {
+    use crate::ast::TyKind;
+
+    let mut parser = Parser::from_source("collect::<Vec>()");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Call(callee, type_args, args) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Path(path) = callee.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments.len(), 1);
+    assert_eq!(path.segments[0].as_str(), "collect");
+    assert_eq!(type_args.len(), 1);
+    let TyKind::Adt(ty_path, _) = &type_args[0].kind else {
+        panic!()
+    };
+    assert_eq!(ty_path.segments[0].as_str(), "Vec");
+    assert!(args.is_empty());
+    assert_eq!(expr.span.to_snippet(), "collect::<Vec>()");
+
+    let mut parser = Parser::from_source("a::f::<i32, bool>(1) < 2");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Binary(ast::BinOp::Lt, call, _) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Call(_, type_args, args) = call.kind else {
+        panic!()
+    };
+    assert_eq!(type_args.len(), 2);
+    assert_eq!(args.len(), 1);
+
+    // turbofish must be followed by a call
+    let mut parser = Parser::from_source("f::<i32>");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_else_if_chain() {
+    // else binds to the nearest if: if c1 {} else { if c2 {} else {} }
+    let src = "if true { 1 } else if false { 2 } else { 3 }";
+    let mut parser = Parser::from_source(src);
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::If(_, _, Some(els)) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::If(cond, _, Some(els)) = els.kind else {
+        panic!()
+    };
+    assert!(matches!(cond.kind, ExprKind::BoolLit(false)));
+    assert!(matches!(els.kind, ExprKind::Block(_)));
+    assert_eq!(expr.span.to_snippet(), src);
+}
+
+#[test]
+fn test_struct_field_shorthand() {
+    let mut parser = Parser::from_source("Point { x, y: 2 }");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Struct(_, fields, _) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(fields.len(), 2);
+    // `x` is `x: x`
+    let (name, value) = &fields[0];
+    assert_eq!(name.as_str(), "x");
+    let ExprKind::Path(path) = &value.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments, std::slice::from_ref(name));
+    let (name, value) = &fields[1];
+    assert_eq!(name.as_str(), "y");
+    assert!(matches!(value.kind, ExprKind::NumLit(2, None)));
+
+    let mut parser = Parser::from_source("Point { x, y }");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::Struct(_, fields, None) if fields.len() == 2));
+
+    let mut parser = Parser::from_source("Point { x 1 }");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_struct_base() {
+    let mut parser = Parser::from_source("Config { debug: true, ..defaults }");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Struct(_, fields, Some(base)) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].0.as_str(), "debug");
+    assert_eq!(base.span.to_snippet(), "defaults");
+
+    let mut parser = Parser::from_source("Config { ..defaults }");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::Struct(_, fields, Some(_)) if fields.is_empty()));
+
+    // `..base` must be the last element
+    let cases = [
+        "Config { ..defaults, debug: true }",
+        "Config { ..defaults, }",
+    ];
+    for src in cases {
+        let mut parser = Parser::from_source(src);
+        assert!(parser.parse_expr().is_none());
+    }
+
+    // without a comma, `true ..defaults` is a range in the field value (as in Rust)
+    let mut parser = Parser::from_source("Config { debug: true ..defaults }");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Struct(_, fields, None) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(fields.len(), 1);
+    assert!(matches!(
+        fields[0].1.kind,
+        ExprKind::Range(Some(_), Some(_))
+    ));
+}
+
+#[test]
+fn test_range_index() {
+    let mut parser = Parser::from_source("a[0..n]");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Index(array, index) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(array.kind, ExprKind::Path(_)));
+    let ExprKind::Range(Some(start), Some(end)) = index.kind else {
+        panic!()
+    };
+    assert!(matches!(start.kind, ExprKind::NumLit(0, None)));
+    assert!(matches!(end.kind, ExprKind::Path(_)));
+    assert_eq!(index.span.to_snippet(), "0..n");
+
+    // operands bind tighter than `..`
+    let mut parser = Parser::from_source("a[1 + 2..3 * 4]");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Index(_, index) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Range(Some(start), Some(end)) = index.kind else {
+        panic!()
+    };
+    assert!(matches!(start.kind, ExprKind::Binary(ast::BinOp::Add, ..)));
+    assert!(matches!(end.kind, ExprKind::Binary(ast::BinOp::Mul, ..)));
+
+    // open-ended ranges
+    let cases = [
+        ("a[1..]", true, false),
+        ("a[..2]", false, true),
+        ("a[..]", false, false),
+    ];
+    for (src, has_start, has_end) in cases {
+        let mut parser = Parser::from_source(src);
+        let expr = parser.parse_expr().unwrap();
+        let ExprKind::Index(_, index) = expr.kind else {
+            panic!()
+        };
+        let ExprKind::Range(start, end) = index.kind else {
+            panic!()
+        };
+        assert_eq!(start.is_some(), has_start);
+        assert_eq!(end.is_some(), has_end);
+    }
+
+    // negative index
+    let mut parser = Parser::from_source("a[-1]");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Index(_, index) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(index.kind, ExprKind::Unary(UnOp::Minus, _)));
+}
+
+#[test]
+fn test_macro_call() {
+    let mut parser = Parser::from_source("foo!(1, 2)");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::MacroCall(name, Delim::Paren, tokens) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(name.as_str(), "foo");
+    let kinds: Vec<_> = tokens.into_iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::NumLit(1, None),
+            TokenKind::Comma,
+            TokenKind::NumLit(2, None)
+        ]
+    );
+    assert_eq!(expr.span.to_snippet(), "foo!(1, 2)");
+
+    // nested delimiters are captured as tokens
+    let mut parser = Parser::from_source("vec![(1), {}][0]");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Index(vec, _) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::MacroCall(_, Delim::Bracket, tokens) = vec.kind else {
+        panic!()
+    };
+    assert_eq!(tokens.len(), 6);
+
+    let mut parser = Parser::from_source("m! {}");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::MacroCall(_, Delim::Brace, _)));
+
+    // unbalanced
+    assert!(Parser::from_source("foo!(1, 2").parse_expr().is_none());
+    assert!(Parser::from_source("foo!(1, 2]").parse_expr().is_none());
+}
+
+#[test]
+fn test_unit_expr() {
+    let mut parser = Parser::from_source("()");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::Unit));
+    assert_eq!(expr.span.to_snippet(), "()");
+
+    // parentheses are not a tuple
+    let mut parser = Parser::from_source("(())");
+    parser.set_preserve_parens(true);
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Paren(inner) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(inner.kind, ExprKind::Unit));
+}
+
+#[test]
+fn test_prefix_and_postfix_binding_power() {
+    let parse = |src: &str| Parser::from_source(src).parse_expr().unwrap();
+
+    // -a.b()? + c * d => (-(((a.b)())?)) + (c * d)
+    let expr = parse("-a.b()? + c * d");
+    let ExprKind::Binary(ast::BinOp::Add, lhs, rhs) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(rhs.kind, ExprKind::Binary(ast::BinOp::Mul, ..)));
+    let ExprKind::Unary(UnOp::Minus, operand) = lhs.kind else {
+        panic!()
+    };
+    let ExprKind::Try(call) = operand.kind else {
+        panic!()
+    };
+    assert_eq!(call.span.to_snippet(), "a.b()");
+    let ExprKind::Call(callee, _, _) = call.kind else {
+        panic!()
+    };
+    assert!(matches!(callee.kind, ExprKind::Field(..)));
+
+    // prefix operators bind tighter than `as` and binary operators
+    let expr = parse("-x as i32");
+    let ExprKind::Cast(operand, _) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::Unary(UnOp::Minus, _)));
+    let expr = parse("!a == *b[0]");
+    let ExprKind::Binary(ast::BinOp::Eq, lhs, rhs) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(lhs.kind, ExprKind::Unary(UnOp::Not, _)));
+    let ExprKind::Unary(UnOp::Deref, operand) = rhs.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::Index(..)));
+    let expr = parse("&a.b");
+    let ExprKind::Unary(UnOp::Ref, operand) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::Field(..)));
+}
+
+#[test]
+fn test_match_char_range() {
+    let src = "match c { 'a'..='z' => 1, '0' => 2, _ => { 3 } }";
+    let mut parser = Parser::from_source(src);
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), src);
+    let ExprKind::Match(scrutinee, arms) = expr.kind else {
+        panic!()
+    };
+    // not a struct literal `c { ... }`
+    assert!(matches!(scrutinee.kind, ExprKind::Path(_)));
+    assert_eq!(arms.len(), 3);
+    let ast::PatternKind::Range(Some(start), Some(end), true) = &arms[0].pat.kind else {
+        panic!()
+    };
+    assert!(matches!(start.kind, ExprKind::CharLit('a')));
+    assert!(matches!(end.kind, ExprKind::CharLit('z')));
+    assert!(matches!(arms[1].pat.kind, ast::PatternKind::Lit(_)));
+    assert!(matches!(arms[2].pat.kind, ast::PatternKind::Wild));
+    assert!(matches!(arms[2].body.kind, ExprKind::Block(_)));
+
+    // struct literals are allowed in nested expressions of the scrutinee
+    let mut parser = Parser::from_source("match (S { a: 1 }) { _ => 0, }");
+    assert!(parser.parse_expr().is_some());
+    // missing comma between arms
+    let mut parser = Parser::from_source("match x { 1 => 1 2 => 2 }");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_match_block_arm_without_comma() {
+    let src = "match x { 0 => { 1 } _ => 2 }";
+    let mut parser = Parser::from_source(src);
+    let expr = parser.parse_expr().unwrap();
+    assert!(parser.errors().is_empty());
+    assert_eq!(expr.span.to_snippet(), src);
+    let ExprKind::Match(_, arms) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(arms.len(), 2);
+    assert_eq!(arms[0].body.span.to_snippet(), "{ 1 }");
+    assert!(matches!(arms[1].pat.kind, ast::PatternKind::Wild));
+
+    // other block-like bodies and a comma after a block are allowed too
+    let src = "match x { 0 => if a { 1 } else { 2 } 1 => loop {}, _ => { 3 } }";
+    let mut parser = Parser::from_source(src);
+    let ExprKind::Match(_, arms) = parser.parse_expr().unwrap().kind else {
+        panic!()
+    };
+    assert!(parser.errors().is_empty());
+    assert_eq!(arms.len(), 3);
+
+    // a comma is still required after an expression body
+    let mut parser = Parser::from_source("match x { 0 => { 1 } 1 => 2 _ => 3 }");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_for_loop() {
+    let src = "for i in 0..n { i }";
+    let mut parser = Parser::from_source(src);
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), src);
+    assert!(expr.is_block_like());
+    let ExprKind::For(pat, iter, body, label) = expr.kind else {
+        panic!()
+    };
+    let ast::PatternKind::Ident(ident) = pat.kind else {
+        panic!()
+    };
+    assert_eq!(ident.as_str(), "i");
+    // not a struct literal `n { i }`
+    let ExprKind::Range(Some(_), Some(end)) = iter.kind else {
+        panic!()
+    };
+    assert!(matches!(end.kind, ExprKind::Path(_)));
+    assert_eq!(body.stmts.len(), 1);
+    assert!(label.is_none());
+
+    let mut parser = Parser::from_source("'outer: for _ in xs {}");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::For(_, _, _, Some(_))));
+    // missing `in`
+    let mut parser = Parser::from_source("for i 0..n {}");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_prefix_op_on_postfix_expr() {
+    let parse = |src: &str| Parser::from_source(src).parse_expr().unwrap();
+
+    let expr = parse("-f()");
+    let ExprKind::Unary(UnOp::Minus, operand) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Call(func, _, args) = operand.kind else {
+        panic!()
+    };
+    assert!(matches!(func.kind, ExprKind::Path(_)));
+    assert!(args.is_empty());
+
+    let expr = parse("-a[0]");
+    let ExprKind::Unary(UnOp::Minus, operand) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Index(array, index) = operand.kind else {
+        panic!()
+    };
+    assert!(matches!(array.kind, ExprKind::Path(_)));
+    assert!(matches!(index.kind, ExprKind::NumLit(0, None)));
+
+    let expr = parse("+f(1)[2]");
+    let ExprKind::Unary(UnOp::Plus, operand) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::Index(..)));
+}
+
+#[test]
+fn test_unexpected_token_after_expr() {
+    let mut parser = Parser::from_source("1 + 2 )");
+    assert!(parser.parse_expr_to_eof().is_none());
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].msg, "Unexpected token after expression: `)`");
+    assert_eq!(errors[0].span.to_snippet(), ")");
+    assert_eq!((errors[0].span.lo(), errors[0].span.hi()), (6, 7));
+
+    let mut parser = Parser::from_source("1 + 2");
+    assert!(parser.parse_expr_to_eof().is_some());
+    assert!(parser.errors().is_empty());
+}
+
+#[test]
+fn test_labeled_block() {
+    let src = "'lbl: { f(); break 'lbl 3 }";
+    let mut parser = Parser::from_source(src);
+    let expr = parser.parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), src);
+    assert!(expr.is_block_like());
+    let ExprKind::LabeledBlock(body, label) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(label.as_str(), "lbl");
+    assert_eq!(body.stmts.len(), 2);
+    let ast::StmtKind::Expr(brk) = &body.stmts[1].kind else {
+        panic!()
+    };
+    let ExprKind::Break(Some(label), Some(value)) = &brk.kind else {
+        panic!()
+    };
+    assert_eq!(label.as_str(), "lbl");
+    assert!(matches!(value.kind, ExprKind::NumLit(3, None)));
+}
+
+#[test]
+fn test_loop_as_block_tail() {
+    let kinds_of = |src: &str| {
+        let block = Parser::from_source(src).parse_block().unwrap();
+        block
+            .stmts
+            .into_iter()
+            .map(|stmt| stmt.kind)
+            .collect::<Vec<_>>()
+    };
+
+    // `while` followed by `;` is a statement and `i` is the tail
+    let stmts = kinds_of("{ let mut i = 0; while i < 3 { i = i + 1 }; i }");
+    assert_eq!(stmts.len(), 3);
+    let ast::StmtKind::Semi(while_expr) = &stmts[1] else {
+        panic!()
+    };
+    assert!(matches!(while_expr.kind, ExprKind::While(..)));
+    let ast::StmtKind::Expr(tail) = &stmts[2] else {
+        panic!()
+    };
+    assert!(matches!(tail.kind, ExprKind::Path(_)));
+
+    // `loop` and `while` without `;` at the end of a block are the tail
+    let stmts = kinds_of("{ f(); loop { break 1 } }");
+    let [_, ast::StmtKind::Expr(tail)] = &stmts[..] else {
+        panic!()
+    };
+    assert!(matches!(tail.kind, ExprKind::Loop(..)));
+    let stmts = kinds_of("{ while c {} }");
+    let [ast::StmtKind::Expr(tail)] = &stmts[..] else {
+        panic!()
+    };
+    assert!(matches!(tail.kind, ExprKind::While(..)));
+}
+
+#[test]
+fn test_assign_in_call_arg() {
+    let mut parser = Parser::from_source("f(x = 1, y)");
+    let expr = parser.parse_expr().unwrap();
+    assert!(!parser.has_errors());
+    let ExprKind::Call(_, _, args) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(args.len(), 2);
+    let ExprKind::Assign(lhs, rhs, _) = &args[0].kind else {
+        panic!()
+    };
+    assert_eq!(lhs.span.to_snippet(), "x");
+    assert!(matches!(rhs.kind, ExprKind::NumLit(1, None)));
+}
+
+#[test]
+fn test_compound_assign() {
+    let mut parser = Parser::from_source("a[i] += x * 2");
+    let expr = parser.parse_expr().unwrap();
+    assert!(!parser.has_errors());
+    let ExprKind::AssignOp(ast::BinOp::Add, place, value, op_span) = &expr.kind else {
+        panic!()
+    };
+    assert_eq!(op_span.to_snippet(), "+=");
+    assert_eq!(place.span.to_snippet(), "a[i]");
+    assert!(matches!(value.kind, ExprKind::Binary(ast::BinOp::Mul, ..)));
+    assert_eq!(expr.span.to_snippet(), "a[i] += x * 2");
+
+    // right associative
+    let mut parser = Parser::from_source("x -= y *= 2");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::AssignOp(ast::BinOp::Sub, _, value, _) = &expr.kind else {
+        panic!()
+    };
+    assert!(matches!(
+        value.kind,
+        ExprKind::AssignOp(ast::BinOp::Mul, ..)
+    ));
+
+    let mut parser = Parser::from_source("1 += 2");
+    parser.parse_expr().unwrap();
+    assert_eq!(parser.errors()[0].msg, "Cannot assign to a literal");
+}
+
+#[test]
+fn test_multi_line_binary_expr_span() {
+    use crate::span::{LineCol, Locator};
+
+    let locator = Locator::default();
+    for src in ["1 +\n2", "1\n+ 2", "1 +\r\n  2"] {
+        let expr = Parser::from_source(src).parse_expr().unwrap();
+        let ExprKind::Binary(_, lhs, rhs) = &expr.kind else {
+            panic!()
+        };
+        assert_eq!((expr.span.lo(), expr.span.hi()), (0, src.len()));
+        assert_eq!(lhs.span.to_snippet(), "1");
+        assert_eq!(rhs.span.to_snippet(), "2");
+        assert_eq!(locator.line_col(&expr.span), LineCol { line: 1, col: 1 });
+        assert_eq!(locator.end_line_col(&expr.span).line, 2);
+        assert_eq!(
+            locator.end_line_col(&expr.span),
+            locator.end_line_col(&rhs.span)
+        );
+    }
+    // newlines are dropped from snippets
+    let expr = Parser::from_source("1 +\n2").parse_expr().unwrap();
+    assert_eq!(expr.span.to_snippet(), "1 +2");
+}
+
+#[test]
+fn test_invalid_assign_target() {
+    let errors_of = |src: &str, preserve_parens: bool| {
+        let mut parser = Parser::from_source(src);
+        parser.set_preserve_parens(preserve_parens);
+        assert!(parser.parse_expr().is_some());
+        parser
+            .errors()
+            .iter()
+            .map(|e| (e.msg.clone(), e.span.to_snippet()))
+            .collect::<Vec<_>>()
+    };
+
+    // errors point at `=`
+    let literal = ("Cannot assign to a literal".to_string(), "=".to_string());
+    assert_eq!(errors_of("5 = x", false), vec![literal]);
+    let result = "Cannot assign to the result of an expression".to_string();
+    for preserve_parens in [false, true] {
+        let errors = errors_of("(a + b) = x", preserve_parens);
+        assert_eq!(errors, vec![(result.clone(), "=".to_string())]);
+    }
+    let mut parser = Parser::from_source("1 = 2");
+    let expr = parser.parse_expr().unwrap();
+    let span = &parser.errors()[0].span;
+    assert_eq!((span.lo(), span.hi()), (2, 3));
+    let ExprKind::Assign(_, _, eq_span) = expr.kind else {
+        panic!()
+    };
+    assert_eq!((eq_span.lo(), eq_span.hi()), (2, 3));
+    assert_eq!(
+        errors_of("{ a } = x", false)[0].0,
+        "Invalid left-hand side of assignment"
+    );
+
+    for src in [
+        "x = 1",
+        "a[0] = 1",
+        "s.f = 1",
+        "*p = 1",
+        "(x) = 1",
+        "a = b = 1",
+    ] {
+        assert!(errors_of(src, true).is_empty(), "{}", src);
+    }
+}
+
+#[test]
+fn test_match_guard() {
+    let mut parser = Parser::from_source("match x { n if n > 0 && n < 10 => 1, _ => 0 }");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::Match(_, arms) = expr.kind else {
+        panic!()
+    };
+    let ast::PatternKind::Ident(ident) = &arms[0].pat.kind else {
+        panic!()
+    };
+    assert_eq!(ident.as_str(), "n");
+    let guard = arms[0].guard.as_ref().unwrap();
+    assert_eq!(guard.span.to_snippet(), "n > 0 && n < 10");
+    // `&&` binds looser than comparisons
+    let ExprKind::Binary(ast::BinOp::And, lhs, rhs) = &guard.kind else {
+        panic!()
+    };
+    assert!(matches!(lhs.kind, ExprKind::Binary(ast::BinOp::Gt, ..)));
+    assert!(matches!(rhs.kind, ExprKind::Binary(ast::BinOp::Lt, ..)));
+    assert!(arms[1].guard.is_none());
+
+    // missing guard expression
+    let mut parser = Parser::from_source("match x { n if => 1 }");
+    assert!(parser.parse_expr().is_none());
+}
+
+#[test]
+fn test_custom_precedence_table() {
+    use ast::BinOp;
+
+    let table = PrecedenceTable::new(&[(BinOp::Add, 4), (BinOp::Sub, 4), (BinOp::Mul, 3)]);
+    let mut parser = Parser::from_source("1 + 2 * 3");
+    parser.set_precedence_table(table.unwrap());
+    let expr = parser.parse_expr().unwrap();
+    // (1 + 2) * 3
+    let ExprKind::Binary(BinOp::Mul, lhs, rhs) = expr.kind else {
+        panic!()
+    };
+    assert!(matches!(lhs.kind, ExprKind::Binary(BinOp::Add, ..)));
+    assert!(matches!(rhs.kind, ExprKind::NumLit(3, None)));
+
+    assert!(PrecedenceTable::new(&[(BinOp::Add, 4), (BinOp::Add, 3)]).is_err());
+    assert!(PrecedenceTable::new(&[(BinOp::Add, 0)]).is_err());
+    assert!(PrecedenceTable::new(&[(BinOp::Add, Bp::CAST)]).is_err());
+    // `==` cannot be chained, but `+` can
+    assert!(PrecedenceTable::new(&[(BinOp::Add, 2)]).is_err());
+}
+
+#[test]
+fn test_float_lit() {
+    let mut parser = Parser::from_source("1.0f32");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::FloatLit(n, suffix) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(n, 1.0);
+    assert_eq!(suffix.as_deref(), Some("f32"));
+    assert_eq!(expr.span.to_snippet(), "1.0f32");
+
+    let mut parser = Parser::from_source("2f64");
+    let expr = parser.parse_expr().unwrap();
+    let ExprKind::FloatLit(n, suffix) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(n, 2.0);
+    assert_eq!(suffix.as_deref(), Some("f64"));
+
+    // not a float
+    let mut parser = Parser::from_source("1..2");
+    let expr = parser.parse_expr().unwrap();
+    assert!(matches!(expr.kind, ExprKind::Range(..)));
+}
+
+#[test]
+fn test_no_struct_literal_in_condition() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        let expr = parser.parse_expr();
+        (expr, parser.has_errors())
+    };
+
+    let (expr, has_errors) = parse("if x {}");
+    assert!(!has_errors);
+    let ExprKind::If(cond, then, _) = expr.unwrap().kind else {
+        panic!()
+    };
+    assert!(matches!(cond.kind, ExprKind::Path(_)));
+    assert!(matches!(then.kind, ExprKind::Block(_)));
+
+    let (expr, has_errors) = parse("while a == b { }");
+    assert!(!has_errors);
+    let ExprKind::While(cond, ..) = expr.unwrap().kind else {
+        panic!()
+    };
+    assert!(matches!(cond.kind, ExprKind::Binary(..)));
+
+    // struct literals need parentheses
+    let (expr, has_errors) = parse("if (Point { x: 1 }).x == 1 {}");
+    assert!(!has_errors);
+    assert!(matches!(expr.unwrap().kind, ExprKind::If(..)));
+    let mut parser = Parser::from_source("if Point { x: 1 } {}");
+    assert!(parser.parse_expr().is_some());
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].msg,
+        "Struct literals are not allowed here without parentheses"
+    );
+    assert_eq!(errors[0].span.to_snippet(), "Point { x: 1 }");
+    // allowed in nested expressions
+    let (_, has_errors) = parse("if f(P { x: 1 }) {} else if [P { x: 1 }][0].x == 1 {}");
+    assert!(!has_errors);
+}
+
+#[test]
+fn test_return_value() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        let expr = parser.parse_expr().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(expr.span.to_snippet(), src);
+        expr
+    };
+
+    let ExprKind::Return(Some(value)) = parse("return (1)").kind else {
+        panic!()
+    };
+    assert!(matches!(value.kind, ExprKind::NumLit(1, None)));
+    assert_eq!(value.span.to_snippet(), "(1)");
+    let ExprKind::Return(Some(value)) = parse("return (1 + 2)").kind else {
+        panic!()
+    };
+    assert!(matches!(value.kind, ExprKind::Binary(..)));
+    assert_eq!(value.span.to_snippet(), "(1 + 2)");
+    let ExprKind::Return(Some(value)) = parse("return { 1 }").kind else {
+        panic!()
+    };
+    assert!(matches!(value.kind, ExprKind::Block(_)));
+
+    // no value before `}` or `;`
+    let ExprKind::Block(block) = parse("{ return }").kind else {
+        panic!()
+    };
+    let ast::StmtKind::Expr(ret) = &block.stmts[0].kind else {
+        panic!()
+    };
+    assert!(matches!(ret.kind, ExprKind::Return(None)));
+    assert_eq!(ret.span.to_snippet(), "return");
+    let ExprKind::Block(block) = parse("{ return; }").kind else {
+        panic!()
+    };
+    let ast::StmtKind::Semi(ret) = &block.stmts[0].kind else {
+        panic!()
+    };
+    assert!(matches!(ret.kind, ExprKind::Return(None)));
+}
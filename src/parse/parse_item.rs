@@ -1,21 +1,29 @@
 use super::Parser;
-use crate::ast::{ExternBlock, Func, Item, ItemKind, Module, StructItem, Ty, TyKind};
+use crate::ast::{
+    ExternBlock, Func, Item, ItemKind, Module, Path, StaticItem, StructItem, Ty, TyKind, UseItem,
+};
 use crate::lexer::{self, Token, TokenKind};
 use crate::span::Ident;
 
 pub fn is_item_start(token: &Token) -> bool {
     matches!(
         token.kind,
-        TokenKind::Fn | TokenKind::Extern | TokenKind::Struct | TokenKind::Mod
+        TokenKind::Fn
+            | TokenKind::Const
+            | TokenKind::Extern
+            | TokenKind::Struct
+            | TokenKind::Mod
+            | TokenKind::Use
+            | TokenKind::Static
     )
 }
 
 impl Parser {
-    /// item ::= func | structItem | externBlock | module
+    /// item ::= func | structItem | externBlock | module | useItem | staticItem
     pub fn parse_item(&mut self) -> Option<Item> {
         let t = self.peek_token();
         match &t.kind {
-            TokenKind::Fn => Some(Item {
+            TokenKind::Fn | TokenKind::Const => Some(Item {
                 kind: ItemKind::Func(self.parse_func(None)?),
             }),
             TokenKind::Struct => Some(Item {
@@ -27,11 +35,14 @@ impl Parser {
             TokenKind::Mod => Some(Item {
                 kind: ItemKind::Mod(self.parse_module()?),
             }),
+            TokenKind::Use => Some(Item {
+                kind: ItemKind::Use(self.parse_use_item()?),
+            }),
+            TokenKind::Static => Some(Item {
+                kind: ItemKind::Static(self.parse_static_item()?),
+            }),
             _ => {
-                eprintln!(
-                    "Expected item, but found `{}`",
-                    self.peek_token().span.to_snippet()
-                );
+                self.error_expected("item");
                 None
             }
         }
@@ -47,10 +58,7 @@ impl Parser {
 
         // `{`
         if !self.skip_expected_token(TokenKind::OpenBrace) {
-            eprintln!(
-                "Expected '{{' for extern block, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'{' for extern block");
             return None;
         }
 
@@ -58,10 +66,7 @@ impl Parser {
 
         // `{`
         if !self.skip_expected_token(TokenKind::CloseBrace) {
-            eprintln!(
-                "Expected '}}' for extern block, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'}' for extern block");
             return None;
         }
 
@@ -72,6 +77,64 @@ impl Parser {
         })
     }
 
+    /// useItem ::= "use" ident ("::" ident)* ("::" "*")? ";"
+    /// https://doc.rust-lang.org/reference/items/use-declarations.html
+    fn parse_use_item(&mut self) -> Option<UseItem> {
+        // skip `use`
+        self.skip_token();
+
+        let ident = self.parse_ident()?;
+        let mut span = ident.span.clone();
+        let mut segments = vec![ident];
+        let mut is_glob = false;
+
+        while self.skip_expected_token(TokenKind::ColCol) {
+            // `*`
+            if self.skip_expected_token(TokenKind::BinOp(lexer::BinOp::Star)) {
+                is_glob = true;
+                break;
+            }
+            let seg = self.parse_ident()?;
+            span = span.concat(&seg.span);
+            segments.push(seg);
+        }
+
+        if !self.skip_expected_token(TokenKind::Semi) {
+            self.error_expected("';' for use item");
+            return None;
+        }
+
+        Some(UseItem {
+            path: Path { span, segments },
+            is_glob,
+        })
+    }
+
+    /// staticItem ::= "static" "mut"? ident ":" type "=" expr ";"
+    /// https://doc.rust-lang.org/reference/items/static-items.html
+    fn parse_static_item(&mut self) -> Option<StaticItem> {
+        // skip `static`
+        self.skip_token();
+
+        let mutable = self.skip_expected_token(TokenKind::Mut);
+        let name = self.parse_ident()?;
+        self.expect_token(TokenKind::Colon)?;
+        let ty = self.parse_type()?;
+        self.expect_token(TokenKind::Eq)?;
+        let value = self.parse_expr()?;
+        if !self.skip_expected_token(TokenKind::Semi) {
+            self.error_expected("';' for static item");
+            return None;
+        }
+
+        Some(StaticItem {
+            name,
+            mutable,
+            ty,
+            value,
+        })
+    }
+
     /// externBlock ::= "extern" abi "{" externalItem* "}"
     /// abi ::= "\"C\""
     /// https://doc.rust-lang.org/reference/items/external-blocks.html
@@ -80,85 +143,84 @@ impl Parser {
         self.skip_token();
         // parse ABI
         let t = self.skip_token();
+        let abi_span = t.span.clone();
         let abi = if let TokenKind::StrLit(s) = t.kind {
             s
         } else {
-            eprintln!("Expected extern ABI, but found `{}`", t.span.to_snippet());
+            let span = t.span.clone();
+            self.error(format!("Expected extern ABI, but found `{}`", t), span);
             return None;
         };
         // check if ABI is "C"
         if abi != "C" {
-            eprintln!(
-                "Found `extern {}`, but `extern \"C\"` can only be supported",
-                abi
+            self.error(
+                format!(
+                    "Found `extern {}`, but `extern \"C\"` can only be supported",
+                    abi
+                ),
+                abi_span,
             );
             return None;
         }
 
         if !self.skip_expected_token(TokenKind::OpenBrace) {
-            eprintln!(
-                "Expected '{{' for extern block, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'{' for extern block");
             return None;
         }
 
         let mut funcs = vec![];
-        while self.peek_token().kind == TokenKind::Fn {
+        while self.peek_is(&TokenKind::Fn) {
             funcs.push(self.parse_func(Some(abi.clone()))?);
         }
 
         if !self.skip_expected_token(TokenKind::CloseBrace) {
-            eprintln!(
-                "Expected '}}' or external item, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'}' or external item");
             return None;
         }
 
         Some(ExternBlock { funcs })
     }
 
-    /// func ::= "fn" ident "(" funcParams? ")" "->" "i32" (block | ";")
+    /// func ::= "const"? "fn" ident genericParams? "(" funcParams? ")" ("->" type)? whereClause? (block | ";")
+    /// whereClause ::= "where" token* (tokens until the body are not parsed)
     /// https://doc.rust-lang.org/reference/items/functions.html
     pub fn parse_func(&mut self, ext: Option<String>) -> Option<Func> {
+        let is_const = self.skip_expected_token(TokenKind::Const);
         if !self.skip_expected_token(TokenKind::Fn) {
-            eprintln!(
-                "Expected \"fn\", but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("\"fn\"");
             return None;
         }
         let name = self.parse_ident()?;
-        if !self.skip_expected_token(TokenKind::OpenParen) {
-            eprintln!(
-                "Expected '(', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        // `<` right after the function name always starts generic parameters
+        let generics = if self.peek_is(&TokenKind::BinOp(lexer::BinOp::Lt)) {
+            self.parse_generic_params()?
+        } else {
+            vec![]
+        };
+        self.expect_token(TokenKind::OpenParen)?;
         let t = self.peek_token();
         let params = if t.kind == TokenKind::CloseParen {
             vec![]
         } else {
             self.parse_func_params()?
         };
-        if !self.skip_expected_token(TokenKind::CloseParen) {
-            eprintln!(
-                "Expected ')', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        let close_paren_span = self.peek_token().span.clone();
+        self.expect_token(TokenKind::CloseParen)?;
 
-        if !self.skip_expected_token(TokenKind::Arrow) {
-            eprintln!(
-                "Expected '->', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
+        // return type can be omitted
+        let ret_ty = if self.skip_expected_token(TokenKind::Arrow) {
+            self.parse_type()?
+        } else {
+            Ty {
+                kind: TyKind::Unit,
+                span: close_paren_span,
+            }
+        };
+
+        // where clause is skipped since generics are not supported yet
+        if self.skip_expected_token(TokenKind::Where) {
+            self.recover_to(&[TokenKind::OpenBrace, TokenKind::Semi]);
         }
-        let ret_ty = self.parse_type()?;
 
         let t = self.peek_token();
         let body = if t.kind == TokenKind::OpenBrace {
@@ -167,32 +229,46 @@ impl Parser {
             self.skip_token();
             None
         } else {
-            eprintln!(
-                "Expected function body or ';', but found `{}`",
-                t.span.to_snippet()
-            );
+            self.error_expected("function body or ';'");
             return None;
         };
 
         Some(Func {
             name,
+            generics,
             params,
-            ret_ty: ret_ty,
+            ret_ty,
             ext,
+            is_const,
             body,
             id: self.get_next_id(),
         })
     }
 
+    /// genericParams ::= "<" (ident ("," ident)* ","?)? ">"
+    fn parse_generic_params(&mut self) -> Option<Vec<Ident>> {
+        self.expect_token(TokenKind::BinOp(lexer::BinOp::Lt))?;
+        let mut generics = vec![];
+        while self.at(&TokenKind::Ident(String::new())) {
+            generics.push(self.parse_ident()?);
+            if !self.peek_is(&TokenKind::Comma) {
+                break;
+            }
+            self.skip_token();
+        }
+        self.expect_token(TokenKind::BinOp(lexer::BinOp::Gt))?;
+        Some(generics)
+    }
+
     /// funcParams ::= funcParam ("," funcParam)* ","?
     /// funcParam ::= ident ":" type
     fn parse_func_params(&mut self) -> Option<Vec<(Ident, Ty)>> {
         let mut params = vec![];
         params.push(self.parse_func_param()?);
 
-        while matches!(self.peek_token().kind, TokenKind::Comma) {
+        while self.peek_is(&TokenKind::Comma) {
             self.skip_token();
-            if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+            if self.at(&TokenKind::Ident(String::new())) {
                 params.push(self.parse_func_param()?);
             }
         }
@@ -201,44 +277,29 @@ impl Parser {
 
     fn parse_func_param(&mut self) -> Option<(Ident, Ty)> {
         let ident = self.parse_ident()?;
-        if !self.skip_expected_token(TokenKind::Colon) {
-            eprintln!(
-                "Expected ':', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        self.expect_token(TokenKind::Colon)?;
         let ty = self.parse_type()?;
         Some((ident, ty))
     }
 
     fn parse_struct_item(&mut self) -> Option<StructItem> {
         if !self.skip_expected_token(TokenKind::Struct) {
-            eprintln!(
-                "Expected \"struct\", but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("\"struct\"");
             return None;
         }
         let ident = self.parse_ident()?;
         if !self.skip_expected_token(TokenKind::OpenBrace) {
-            eprintln!(
-                "Expected '{{' for struct definiton, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'{' for struct definiton");
             return None;
         }
 
-        let fields = if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+        let fields = if self.at(&TokenKind::Ident(String::new())) {
             self.parse_struct_fields()?
         } else {
             vec![]
         };
         if !self.skip_expected_token(TokenKind::CloseBrace) {
-            eprintln!(
-                "Expected '}}' for struct definition, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("'}' for struct definition");
             return None;
         }
 
@@ -253,9 +314,9 @@ impl Parser {
         let mut fields = vec![];
         fields.push(self.parse_struct_field()?);
 
-        while matches!(self.peek_token().kind, TokenKind::Comma) {
+        while self.peek_is(&TokenKind::Comma) {
             self.skip_token();
-            if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+            if self.at(&TokenKind::Ident(String::new())) {
                 fields.push(self.parse_struct_field()?);
             }
         }
@@ -265,43 +326,92 @@ impl Parser {
     /// structField ::= ident ":" type
     fn parse_struct_field(&mut self) -> Option<(Ident, Ty)> {
         let name = self.parse_ident()?;
-        if !self.skip_expected_token(TokenKind::Colon) {
-            eprintln!(
-                "Expected ':', but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        self.expect_token(TokenKind::Colon)?;
         let ty = self.parse_type()?;
         Some((name, ty))
     }
 
+    /// typeList ::= (type ("," type)* ","?)?
+    /// Parses types until ')' and returns them with whether a trailing comma is present
+    fn parse_type_list(&mut self) -> Option<(Vec<Ty>, bool)> {
+        let mut tys = vec![];
+        let mut trailing_comma = false;
+        while !self.peek_is(&TokenKind::CloseParen) {
+            tys.push(self.parse_type()?);
+            trailing_comma = self.peek_is(&TokenKind::Comma);
+            if !trailing_comma {
+                break;
+            }
+            self.skip_token();
+        }
+        Some((tys, trailing_comma))
+    }
+
     pub fn parse_type(&mut self) -> Option<Ty> {
-        if matches!(self.peek_token().kind, TokenKind::Ident(_)) {
+        // path genericArgs?
+        if self.at(&TokenKind::Ident(String::new())) {
             let path = self.parse_path()?;
+            let mut span = path.span.clone();
+            let args = if self.peek_is(&TokenKind::BinOp(lexer::BinOp::Lt)) {
+                let (args, close_span) = self.parse_generic_args()?;
+                span = span.concat(&close_span);
+                args
+            } else {
+                vec![]
+            };
             return Some(Ty {
-                span: path.span.clone(),
-                kind: TyKind::Adt(path),
+                span,
+                kind: TyKind::Adt(path, args),
             });
         }
 
         let t = self.skip_token();
         let mut span = t.span;
         match t.kind {
-            // Unit type: ()
+            // Unit type: (), tuple type: (T1, T2, ...), or parenthesized type: (T)
             TokenKind::OpenParen => {
-                if !self.skip_expected_token(TokenKind::CloseParen) {
-                    eprintln!(
-                        "Expected ')', but found `{}`",
-                        self.peek_token().span.to_snippet()
-                    );
-                    None
+                let (mut tys, trailing_comma) = self.parse_type_list()?;
+                let span = span.concat(&self.expect_token(TokenKind::CloseParen)?.span);
+                let kind = if tys.is_empty() {
+                    TyKind::Unit
+                } else if tys.len() == 1 && !trailing_comma {
+                    tys.pop().unwrap().kind
                 } else {
-                    Some(Ty {
+                    TyKind::Tuple(tys)
+                };
+                Some(Ty { kind, span })
+            }
+            // Function type: fn(T1, T2, ...) -> U
+            TokenKind::Fn => {
+                self.expect_token(TokenKind::OpenParen)?;
+                let (params, _) = self.parse_type_list()?;
+                span = span.concat(&self.expect_token(TokenKind::CloseParen)?.span);
+                // return type can be omitted
+                let ret_ty = if self.skip_expected_token(TokenKind::Arrow) {
+                    let ret_ty = self.parse_type()?;
+                    span = span.concat(&ret_ty.span);
+                    ret_ty
+                } else {
+                    Ty {
                         kind: TyKind::Unit,
-                        span,
-                    })
-                }
+                        span: span.clone(),
+                    }
+                };
+                Some(Ty {
+                    kind: TyKind::Fn(params, Box::new(ret_ty)),
+                    span,
+                })
+            }
+            // impl Trait, dyn Trait
+            TokenKind::Impl | TokenKind::Dyn => {
+                let path = self.parse_path()?;
+                let span = span.concat(&path.span);
+                let kind = if t.kind == TokenKind::Impl {
+                    TyKind::ImplTrait(path)
+                } else {
+                    TyKind::DynTrait(path)
+                };
+                Some(Ty { kind, span })
             }
             // Never type: !
             TokenKind::Bang => Some(Ty {
@@ -326,28 +436,22 @@ impl Parser {
             // [type; n]
             TokenKind::OpenBracket => {
                 let elem_ty = self.parse_type()?;
-                if !self.skip_expected_token(TokenKind::Semi) {
-                    eprintln!(
-                        "Expected ';', but found `{}`",
-                        self.peek_token().span.to_snippet()
-                    );
-                    return None;
-                }
+                self.expect_token(TokenKind::Semi)?;
                 let t = self.skip_token();
-                let TokenKind::NumLit(n) = t.kind else {
+                let TokenKind::NumLit(n, _) = t.kind else {
                     return None;
                 };
-                let span = span.concat(&self.peek_token().span);
-                if !self.skip_expected_token(TokenKind::CloseBracket) {
-                    eprintln!(
-                        "Expected ']', but found `{}`",
-                        self.peek_token().span.to_snippet()
+                let Ok(n) = n.try_into() else {
+                    let span = t.span.clone();
+                    self.error(
+                        format!("Array length is too large: `{}`", span.to_snippet()),
+                        span,
                     );
                     return None;
-                }
-                // u32 is safely converted to usize
+                };
+                let span = span.concat(&self.expect_token(TokenKind::CloseBracket)?.span);
                 Some(Ty {
-                    kind: TyKind::Array(Box::new(elem_ty), n.try_into().unwrap()),
+                    kind: TyKind::Array(Box::new(elem_ty), n),
                     span,
                 })
             }
@@ -357,7 +461,9 @@ impl Parser {
             TokenKind::BinOp(lexer::BinOp::And) => {
                 let t = self.peek_token();
                 let region = if let TokenKind::Lifetime(_) = t.kind {
-                    let TokenKind::Lifetime(r) = self.skip_token().kind else { unreachable!() };
+                    let TokenKind::Lifetime(r) = self.skip_token().kind else {
+                        unreachable!()
+                    };
                     Some(r)
                 } else {
                     None
@@ -374,7 +480,8 @@ impl Parser {
                 // TODO: *mut T
                 let t = self.skip_token();
                 if t.kind != TokenKind::Const {
-                    eprintln!("Expected 'const', but found `{}`", t.span.to_snippet());
+                    let span = t.span.clone();
+                    self.error(format!("Expected 'const', but found `{}`", t), span);
                     return None;
                 }
                 let referent = self.parse_type()?;
@@ -385,9 +492,243 @@ impl Parser {
                 })
             }
             _ => {
-                eprintln!("Expected type, but found `{}`", span.to_snippet());
+                self.error(format!("Expected type, but found `{}`", t.kind), span);
                 None
             }
         }
     }
 }
+
+#[test]
+fn test_parse_fn_and_tuple_type() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_type().unwrap()
+    };
+
+    let ty = parse("fn(i32) -> bool");
+    let TyKind::Fn(params, ret_ty) = ty.kind else {
+        panic!()
+    };
+    assert!(matches!(
+        params[..],
+        [Ty {
+            kind: TyKind::I32,
+            ..
+        }]
+    ));
+    assert!(matches!(ret_ty.kind, TyKind::Bool));
+    assert_eq!(ty.span.to_snippet(), "fn(i32) -> bool");
+
+    let ty = parse("fn(i32, &str,)");
+    let TyKind::Fn(params, ret_ty) = ty.kind else {
+        panic!()
+    };
+    assert_eq!(params.len(), 2);
+    assert!(matches!(ret_ty.kind, TyKind::Unit));
+
+    let ty = parse("(i32, bool)");
+    let TyKind::Tuple(elems) = ty.kind else {
+        panic!()
+    };
+    assert_eq!(elems.len(), 2);
+    assert!(matches!(elems[0].kind, TyKind::I32));
+    assert!(matches!(elems[1].kind, TyKind::Bool));
+    assert_eq!(ty.span.to_snippet(), "(i32, bool)");
+
+    // one-element tuple needs a trailing comma
+    assert!(matches!(parse("(i32,)").kind, TyKind::Tuple(elems) if elems.len() == 1));
+    assert!(matches!(parse("(i32)").kind, TyKind::I32));
+    assert!(matches!(parse("()").kind, TyKind::Unit));
+}
+
+#[test]
+fn test_parse_func_ret_ty() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_func(None).unwrap()
+    };
+
+    let func = parse("fn f() -> i32 {}");
+    assert!(matches!(func.ret_ty.kind, TyKind::I32));
+    // missing arrow means unit return
+    let func = parse("fn g() {}");
+    assert!(matches!(func.ret_ty.kind, TyKind::Unit));
+    assert!(func.body.is_some());
+    let func = parse("fn h() -> () {}");
+    assert!(matches!(func.ret_ty.kind, TyKind::Unit));
+}
+
+#[test]
+fn test_parse_func_body() {
+    use crate::ast::{ExprKind, StmtKind};
+
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_func(None).unwrap().body.unwrap()
+    };
+
+    // empty body evaluates to `()`
+    let body = parse("fn f() {}");
+    assert!(body.stmts.is_empty());
+    assert_eq!(body.span.to_snippet(), "{}");
+
+    // tail expression
+    let body = parse("fn f() -> i32 { 42 }");
+    let [stmt] = &body.stmts[..] else { panic!() };
+    let StmtKind::Expr(tail) = &stmt.kind else {
+        panic!()
+    };
+    assert!(matches!(tail.kind, ExprKind::NumLit(42, None)));
+
+    // `;` discards the value
+    let body = parse("fn f() { 42; }");
+    assert!(matches!(body.stmts[0].kind, StmtKind::Semi(_)));
+}
+
+#[test]
+fn test_parse_generic_params() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_func(None)
+    };
+
+    let func = parse("fn id<T>(x: T) {}").unwrap();
+    let generics: Vec<_> = func.generics.iter().map(|g| g.as_str()).collect();
+    assert_eq!(generics, ["T"]);
+    let func = parse("fn f<T, U,>() {}").unwrap();
+    assert_eq!(func.generics.len(), 2);
+    let func = parse("fn f() {}").unwrap();
+    assert!(func.generics.is_empty());
+    assert!(parse("fn f<T() {}").is_none());
+}
+
+#[test]
+fn test_parse_static_item() {
+    use crate::ast::ExprKind;
+
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_item()
+    };
+
+    let item = parse("static N: i32 = 1;").unwrap();
+    let ItemKind::Static(static_item) = item.kind else {
+        panic!()
+    };
+    assert_eq!(static_item.name.as_str(), "N");
+    assert!(!static_item.mutable);
+    assert!(matches!(static_item.ty.kind, TyKind::I32));
+    assert!(matches!(static_item.value.kind, ExprKind::NumLit(1, None)));
+
+    let item = parse("static mut COUNTER: i32 = 0;").unwrap();
+    let ItemKind::Static(static_item) = item.kind else {
+        panic!()
+    };
+    assert_eq!(static_item.name.as_str(), "COUNTER");
+    assert!(static_item.mutable);
+
+    // missing semicolon
+    assert!(parse("static N: i32 = 1").is_none());
+}
+
+#[test]
+fn test_skip_where_clause() {
+    let parse = |src: &str| {
+        let mut parser = Parser::from_source(src);
+        parser.parse_func(None)
+    };
+
+    let func = parse("fn f<T>() where T: Clone {}").unwrap();
+    assert_eq!(func.generics.len(), 1);
+    assert!(func.body.is_some());
+    let func = parse("fn g<T, U>(x: T) -> U where T: Into<U>, U: Fn(i32) -> i32 { x }").unwrap();
+    assert_eq!(func.body.unwrap().stmts.len(), 1);
+    let func = parse("fn h<T>() where T: Copy;").unwrap();
+    assert!(func.body.is_none());
+}
+
+#[test]
+fn test_parse_impl_and_dyn_trait_type() {
+    let parse = |src: &str| Parser::from_source(src).parse_type().unwrap();
+
+    let ty = parse("impl Iterator");
+    let TyKind::ImplTrait(path) = &ty.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments[0].as_str(), "Iterator");
+    assert_eq!(ty.span.to_snippet(), "impl Iterator");
+
+    let ty = parse("&dyn std::error::Error");
+    let TyKind::Ref(_, referent) = &ty.kind else {
+        panic!()
+    };
+    let TyKind::DynTrait(path) = &referent.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments.len(), 3);
+    assert_eq!(referent.span.to_snippet(), "dyn std::error::Error");
+
+    let func = Parser::from_source("fn f() -> impl Iterator;")
+        .parse_func(None)
+        .unwrap();
+    assert!(matches!(func.ret_ty.kind, TyKind::ImplTrait(_)));
+    assert!(Parser::from_source("dyn").parse_type().is_none());
+}
+
+#[test]
+fn test_parse_generic_type_args() {
+    let parse = |src: &str| Parser::from_source(src).parse_type().unwrap();
+
+    let ty = parse("Vec<i32>");
+    let TyKind::Adt(path, args) = &ty.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments[0].as_str(), "Vec");
+    assert!(matches!(args[0].kind, TyKind::I32));
+    assert_eq!(ty.span.to_snippet(), "Vec<i32>");
+
+    let ty = parse("Vec<Vec<i32>>");
+    let TyKind::Adt(_, args) = &ty.kind else {
+        panic!()
+    };
+    let TyKind::Adt(inner_path, inner_args) = &args[0].kind else {
+        panic!()
+    };
+    assert_eq!(inner_path.segments[0].as_str(), "Vec");
+    assert_eq!(inner_args.len(), 1);
+    assert_eq!(args[0].span.to_snippet(), "Vec<i32>");
+    assert_eq!(ty.span.to_snippet(), "Vec<Vec<i32>>");
+
+    let ty = parse("std::collections::HashMap<K, V>");
+    let TyKind::Adt(path, args) = &ty.kind else {
+        panic!()
+    };
+    assert_eq!(path.segments.len(), 3);
+    assert_eq!(args.len(), 2);
+
+    let TyKind::Adt(_, args) = parse("P").kind else {
+        panic!()
+    };
+    assert!(args.is_empty());
+    assert!(Parser::from_source("Vec<i32").parse_type().is_none());
+}
+
+#[test]
+fn test_parse_const_fn() {
+    let parse = |src: &str| Parser::from_source(src).parse_item();
+
+    let item = parse("const fn f() {}").unwrap();
+    let ItemKind::Func(func) = item.kind else {
+        panic!()
+    };
+    assert_eq!(func.name.as_str(), "f");
+    assert!(func.is_const);
+
+    let ItemKind::Func(func) = parse("fn f() {}").unwrap().kind else {
+        panic!()
+    };
+    assert!(!func.is_const);
+    // `const` must be followed by `fn`
+    assert!(parse("const f() {}").is_none());
+}
@@ -0,0 +1,422 @@
+use super::Parser;
+use crate::ast::{Expr, ExprKind, Pattern, PatternKind, UnOp};
+use crate::lexer::{BinOp, Token, TokenKind};
+use crate::span::Span;
+
+fn is_literal_pattern_start(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::NumLit(..)
+            | TokenKind::FloatLit(..)
+            | TokenKind::CharLit(_)
+            | TokenKind::ByteLit(_)
+            | TokenKind::ByteStrLit(_)
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::StrLit(_)
+            | TokenKind::BinOp(BinOp::Minus)
+    )
+}
+
+impl Parser {
+    /// pattern ::= "_" | ident | literalPattern | rangePattern | tuplePattern | arrayPattern
+    /// rangePattern ::= literalPattern ("..=" | "..") literalPattern
+    ///                | literalPattern ".."
+    ///                | ("..=" | "..") literalPattern
+    /// ref: https://doc.rust-lang.org/reference/patterns.html
+    pub fn parse_pattern(&mut self) -> Option<Pattern> {
+        match self.peek_token().kind {
+            TokenKind::OpenParen => return self.parse_tuple_pattern(),
+            TokenKind::OpenBracket => return self.parse_array_pattern(),
+            _ => (),
+        }
+        if let TokenKind::Ident(name) = &self.peek_token().kind {
+            if name == "_" {
+                let span = self.skip_token().span;
+                return Some(Pattern {
+                    kind: PatternKind::Wild,
+                    span,
+                });
+            }
+            let ident = self.parse_ident()?;
+            return Some(Pattern {
+                span: ident.span.clone(),
+                kind: PatternKind::Ident(ident),
+            });
+        }
+
+        let start = if self.peek_is(&TokenKind::DotDot) || self.peek_is(&TokenKind::DotDotEq) {
+            None
+        } else {
+            Some(self.parse_literal_pattern()?)
+        };
+        let inclusive = match self.peek_token().kind {
+            TokenKind::DotDotEq => true,
+            TokenKind::DotDot => false,
+            _ => {
+                // `start` is always parsed here
+                let lit = start.unwrap();
+                return Some(Pattern {
+                    span: lit.span.clone(),
+                    kind: PatternKind::Lit(Box::new(lit)),
+                });
+            }
+        };
+        let op_span = self.skip_token().span;
+        let end = if is_literal_pattern_start(self.peek_token()) {
+            Some(self.parse_literal_pattern()?)
+        } else {
+            None
+        };
+
+        if inclusive && end.is_none() {
+            self.error(
+                "Inclusive range patterns must have an end".to_string(),
+                op_span,
+            );
+            return None;
+        }
+        if start.is_none() && end.is_none() {
+            self.error(
+                "`..` can only be used in tuple or slice patterns".to_string(),
+                op_span,
+            );
+            return None;
+        }
+        let lo = start.as_ref().map_or(&op_span, |start| &start.span);
+        let hi = end.as_ref().map_or(&op_span, |end| &end.span);
+        Some(Pattern {
+            span: lo.concat(hi),
+            kind: PatternKind::Range(start.map(Box::new), end.map(Box::new), inclusive),
+        })
+    }
+
+    /// tuplePattern ::= "(" (patternOrRest ("," patternOrRest)* ","?)? ")"
+    /// NOTE: `(pattern)` without a trailing comma is a parenthesized pattern, but `(..)` is a tuple
+    fn parse_tuple_pattern(&mut self) -> Option<Pattern> {
+        let open_span = self.peek_token().span.clone();
+        let (pats, trailing_comma, close_span) = self.parse_pattern_list(TokenKind::CloseParen)?;
+        if pats.len() == 1 && !trailing_comma && !matches!(pats[0].kind, PatternKind::Rest) {
+            return pats.into_iter().next();
+        }
+        Some(Pattern {
+            kind: PatternKind::Tuple(pats),
+            span: open_span.concat(&close_span),
+        })
+    }
+
+    /// arrayPattern ::= "[" (patternOrRest ("," patternOrRest)* ","?)? "]"
+    fn parse_array_pattern(&mut self) -> Option<Pattern> {
+        let open_span = self.peek_token().span.clone();
+        let (pats, _, close_span) = self.parse_pattern_list(TokenKind::CloseBracket)?;
+        Some(Pattern {
+            kind: PatternKind::Array(pats),
+            span: open_span.concat(&close_span),
+        })
+    }
+
+    /// Parse comma-separated patterns enclosed by an opening delimiter and `close`.
+    /// Returns the patterns, whether a trailing comma exists, and the span of `close`
+    /// patternOrRest ::= pattern | ".."
+    fn parse_pattern_list(&mut self, close: TokenKind) -> Option<(Vec<Pattern>, bool, Span)> {
+        // skip opening delimiter
+        self.skip_token();
+        let mut pats = vec![];
+        let mut trailing_comma = false;
+        let mut has_rest = false;
+        while !self.peek_is(&close) {
+            // `..` followed by a literal is a range pattern (e.g. `[..5]`)
+            let is_rest = self.peek_is(&TokenKind::DotDot) && {
+                let next = &self.peek_nth(1).kind;
+                *next == TokenKind::Comma || *next == close
+            };
+            if is_rest {
+                let span = self.skip_token().span;
+                if has_rest {
+                    self.error(
+                        "`..` can only be used once per tuple or slice pattern".to_string(),
+                        span.clone(),
+                    );
+                }
+                has_rest = true;
+                pats.push(Pattern {
+                    kind: PatternKind::Rest,
+                    span,
+                });
+            } else {
+                pats.push(self.parse_pattern()?);
+            }
+            trailing_comma = self.skip_expected_token(TokenKind::Comma);
+            if !trailing_comma {
+                break;
+            }
+        }
+        if !self.peek_is(&close) {
+            self.error_expected(&format!("',' or '{}'", close));
+            return None;
+        }
+        let close_span = self.skip_token().span;
+        Some((pats, trailing_comma, close_span))
+    }
+
+    /// literalPattern ::= "-"? num | char | "true" | "false" | stringLit | byteLit | byteStrLit
+    fn parse_literal_pattern(&mut self) -> Option<Expr> {
+        if self.peek_is(&TokenKind::BinOp(BinOp::Minus)) {
+            let minus_span = self.skip_token().span;
+            if !matches!(
+                self.peek_token().kind,
+                TokenKind::NumLit(..) | TokenKind::FloatLit(..)
+            ) {
+                self.error_expected("number literal after '-'");
+                return None;
+            }
+            let lit = self.parse_literal_pattern()?;
+            return Some(Expr {
+                span: minus_span.concat(&lit.span),
+                kind: ExprKind::Unary(UnOp::Minus, Box::new(lit)),
+                id: self.get_next_id(),
+            });
+        }
+        let t = self.skip_token();
+        let kind = match t.kind {
+            TokenKind::FloatLit(..) => {
+                self.error(
+                    "Float literals cannot be used in patterns".to_string(),
+                    t.span,
+                );
+                return None;
+            }
+            TokenKind::NumLit(n, suffix) => ExprKind::NumLit(n, suffix),
+            TokenKind::CharLit(c) => ExprKind::CharLit(c),
+            TokenKind::ByteLit(b) => ExprKind::ByteLit(b),
+            TokenKind::ByteStrLit(s) => ExprKind::ByteStrLit(s),
+            TokenKind::True => ExprKind::BoolLit(true),
+            TokenKind::False => ExprKind::BoolLit(false),
+            TokenKind::StrLit(s) => ExprKind::StrLit(s),
+            _ => {
+                let span = t.span.clone();
+                self.error(format!("Expected pattern, but found `{}`", t), span);
+                return None;
+            }
+        };
+        Some(Expr {
+            kind,
+            id: self.get_next_id(),
+            span: t.span,
+        })
+    }
+}
+
+#[test]
+fn test_parse_pattern() {
+    let parse = |src: &str| Parser::from_source(src).parse_pattern();
+
+    assert!(matches!(parse("_").unwrap().kind, PatternKind::Wild));
+    let pat = parse("1").unwrap();
+    let PatternKind::Lit(lit) = pat.kind else {
+        panic!()
+    };
+    assert!(matches!(lit.kind, ExprKind::NumLit(1, None)));
+
+    let pat = parse("'a'..='z'").unwrap();
+    assert_eq!(pat.span.to_snippet(), "'a'..='z'");
+    let PatternKind::Range(Some(start), Some(end), true) = pat.kind else {
+        panic!()
+    };
+    assert!(matches!(start.kind, ExprKind::CharLit('a')));
+    assert!(matches!(end.kind, ExprKind::CharLit('z')));
+
+    let pat = parse("x").unwrap();
+    let PatternKind::Ident(ident) = pat.kind else {
+        panic!()
+    };
+    assert_eq!(ident.as_str(), "x");
+
+    assert!(parse("+ 1").is_none());
+
+    // float literals
+    let mut parser = Parser::from_source("match x { 1.0 => 0, _ => 1 }");
+    assert!(parser.parse_expr().is_none());
+    let errors = parser.errors();
+    assert_eq!(errors[0].msg, "Float literals cannot be used in patterns");
+    assert_eq!(errors[0].span.to_snippet(), "1.0");
+    assert!(parse("'a'..=").is_none());
+}
+
+#[test]
+fn test_nested_patterns() {
+    use crate::ast::StmtKind;
+
+    let pat = Parser::from_source("(a, (b, _), [c, 1])")
+        .parse_pattern()
+        .unwrap();
+    let PatternKind::Tuple(pats) = &pat.kind else {
+        panic!()
+    };
+    assert_eq!(pats.len(), 3);
+    assert!(matches!(&pats[1].kind, PatternKind::Tuple(inner) if inner.len() == 2));
+    assert!(matches!(&pats[2].kind, PatternKind::Array(inner) if inner.len() == 2));
+    let names: Vec<_> = pat.bindings().iter().map(|ident| ident.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+
+    // parenthesized pattern, 1-tuple and unit
+    let parse = |src: &str| Parser::from_source(src).parse_pattern().unwrap().kind;
+    assert!(matches!(parse("(x)"), PatternKind::Ident(_)));
+    assert!(matches!(parse("(x,)"), PatternKind::Tuple(pats) if pats.len() == 1));
+    assert!(matches!(parse("()"), PatternKind::Tuple(pats) if pats.is_empty()));
+    assert!(matches!(parse("[x, y,]"), PatternKind::Array(pats) if pats.len() == 2));
+    assert!(Parser::from_source("(a b)").parse_pattern().is_none());
+
+    let stmt = Parser::from_source("let (a, (b, c)) = t;")
+        .parse_stmt()
+        .unwrap();
+    let StmtKind::Let(let_stmt) = stmt.kind else {
+        panic!()
+    };
+    assert_eq!(let_stmt.pat.span.to_snippet(), "(a, (b, c))");
+    let PatternKind::Tuple(pats) = &let_stmt.pat.kind else {
+        panic!()
+    };
+    assert!(matches!(&pats[0].kind, PatternKind::Ident(_)));
+    assert!(matches!(&pats[1].kind, PatternKind::Tuple(inner) if inner.len() == 2));
+
+    let expr = Parser::from_source("match arr { [x, y] => x, _ => 0 }")
+        .parse_expr()
+        .unwrap();
+    let ExprKind::Match(_, arms) = &expr.kind else {
+        panic!()
+    };
+    let PatternKind::Array(pats) = &arms[0].pat.kind else {
+        panic!()
+    };
+    assert_eq!(pats.len(), 2);
+    assert!(matches!(arms[1].pat.kind, PatternKind::Wild));
+}
+
+#[test]
+fn test_rest_pattern() {
+    use crate::ast::StmtKind;
+
+    let stmt = Parser::from_source("let [head, ..] = arr;")
+        .parse_stmt()
+        .unwrap();
+    let StmtKind::Let(let_stmt) = stmt.kind else {
+        panic!()
+    };
+    let PatternKind::Array(pats) = &let_stmt.pat.kind else {
+        panic!()
+    };
+    assert!(matches!(&pats[0].kind, PatternKind::Ident(ident) if ident.as_str() == "head"));
+    assert!(matches!(pats[1].kind, PatternKind::Rest));
+    assert_eq!(pats[1].span.to_snippet(), "..");
+
+    let parse = |src: &str| Parser::from_source(src).parse_pattern().unwrap();
+    let pat = parse("[first, .., last]");
+    assert!(matches!(&pat.kind, PatternKind::Array(pats) if pats.len() == 3));
+    let names: Vec<_> = pat.bindings().iter().map(|ident| ident.as_str()).collect();
+    assert_eq!(names, ["first", "last"]);
+    assert!(matches!(parse("(a, ..)").kind, PatternKind::Tuple(pats) if pats.len() == 2));
+    // `(..)` is a tuple pattern
+    assert!(matches!(parse("(..)").kind, PatternKind::Tuple(pats) if pats.len() == 1));
+
+    // multiple rest patterns
+    let mut parser = Parser::from_source("[a, .., b, ..]");
+    parser.parse_pattern();
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].msg,
+        "`..` can only be used once per tuple or slice pattern"
+    );
+    assert_eq!(errors[0].span.lo(), 11);
+    let mut parser = Parser::from_source("(a, .., b, ..)");
+    parser.parse_pattern();
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].msg,
+        "`..` can only be used once per tuple or slice pattern"
+    );
+    assert_eq!(errors[0].span.lo(), 11);
+    // nested sequences have their own rest patterns
+    let mut parser = Parser::from_source("(.., [.., x])");
+    parser.parse_pattern();
+    assert!(!parser.has_errors());
+    // not in a sequence
+    assert!(Parser::from_source("..").parse_pattern().is_none());
+}
+
+#[test]
+fn test_open_range_pattern() {
+    let parse = |src: &str| Parser::from_source(src).parse_pattern();
+    let bounds = |src: &str| {
+        let pat = parse(src).unwrap();
+        assert_eq!(pat.span.to_snippet(), src);
+        let PatternKind::Range(start, end, inclusive) = pat.kind else {
+            panic!()
+        };
+        let snippet = |bound: Option<Box<Expr>>| bound.map(|e| e.span.to_snippet());
+        (snippet(start), snippet(end), inclusive)
+    };
+
+    assert_eq!(bounds("5.."), (Some("5".to_string()), None, false));
+    assert_eq!(bounds("..5"), (None, Some("5".to_string()), false));
+    assert_eq!(bounds("..=9"), (None, Some("9".to_string()), true));
+    assert_eq!(
+        bounds("0..10"),
+        (Some("0".to_string()), Some("10".to_string()), false)
+    );
+    assert_eq!(
+        bounds("-5..=-1"),
+        (Some("-5".to_string()), Some("-1".to_string()), true)
+    );
+
+    // negative literals
+    let pat = parse("-1").unwrap();
+    let PatternKind::Lit(lit) = pat.kind else {
+        panic!()
+    };
+    let ExprKind::Unary(UnOp::Minus, operand) = lit.kind else {
+        panic!()
+    };
+    assert!(matches!(operand.kind, ExprKind::NumLit(1, None)));
+    assert!(parse("-'a'").is_none());
+
+    // `..` in slice patterns is a rest pattern unless followed by a bound
+    let PatternKind::Array(pats) = parse("[.., ..5]").unwrap().kind else {
+        panic!()
+    };
+    assert!(matches!(pats[0].kind, PatternKind::Rest));
+    assert!(matches!(
+        pats[1].kind,
+        PatternKind::Range(None, Some(_), false)
+    ));
+
+    let expr = Parser::from_source("match n { 0..=9 => \"small\", _ => \"big\" }")
+        .parse_expr()
+        .unwrap();
+    let ExprKind::Match(_, arms) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(arms[0].pat.span.to_snippet(), "0..=9");
+    assert!(matches!(
+        arms[0].pat.kind,
+        PatternKind::Range(Some(_), Some(_), true)
+    ));
+    assert!(matches!(arms[1].pat.kind, PatternKind::Wild));
+    let expr = Parser::from_source("match n { ..0 => 1, 10.. => 2, _ => 3 }")
+        .parse_expr()
+        .unwrap();
+    let ExprKind::Match(_, arms) = expr.kind else {
+        panic!()
+    };
+    assert_eq!(arms.len(), 3);
+
+    let mut parser = Parser::from_source("5..=");
+    assert!(parser.parse_pattern().is_none());
+    assert_eq!(
+        parser.errors()[0].msg,
+        "Inclusive range patterns must have an end"
+    );
+    assert!(parse("..").is_none());
+}
@@ -1,19 +1,44 @@
 use super::parse_expr::is_expr_start;
-use super::Parser;
-use crate::ast::{Block, LetStmt, Stmt, StmtKind};
+use super::parse_item::is_item_start;
+use super::{ParseError, Parser};
+use crate::ast::{Block, LetStmt, PatternKind, Stmt, StmtKind};
 use crate::lexer::{Token, TokenKind};
 
 pub fn is_stmt_start(t: &Token) -> bool {
-    is_expr_start(t) || matches!(t.kind, TokenKind::Let)
+    is_expr_start(t) || is_item_start(t) || matches!(t.kind, TokenKind::Let)
 }
 
 impl Parser {
-    pub fn parse_stmt(&mut self) -> Option<Stmt> {
+    /// Parse a single statement (e.g. a line of REPLs).
+    /// Diagnostics are kept in `Parser::errors` as well, and the first error is returned.
+    pub fn parse_stmt(&mut self) -> Result<Stmt, ParseError> {
+        self.first_error = None;
+        let stmt = self.parse_block_stmt();
+        match (stmt, self.first_error.take()) {
+            (Some(stmt), None) => Ok(stmt),
+            (_, Some(error)) => Err(error),
+            (None, None) => {
+                self.error_expected("statement");
+                Err(self.first_error.take().unwrap())
+            }
+        }
+    }
+
+    /// stmt ::= letStmt | item | expr ";"?
+    fn parse_block_stmt(&mut self) -> Option<Stmt> {
         let t = self.peek_token();
         let mut span = t.span.clone();
 
         match &t.kind {
             TokenKind::Let => self.parse_let_stmt(),
+            _ if is_item_start(t) => {
+                let item = self.parse_item()?;
+                Some(Stmt {
+                    kind: StmtKind::Item(Box::new(item)),
+                    id: self.get_next_id(),
+                    span: self.span_since(&span),
+                })
+            }
             _ if is_expr_start(t) => {
                 let expr = self.parse_expr()?;
                 span = span.concat(&expr.span);
@@ -36,32 +61,34 @@ impl Parser {
                 }
             }
             _ => {
-                eprintln!(
-                    "Expected expr, but found `{}`",
-                    self.peek_token().span.to_snippet()
-                );
+                self.error_expected("expr");
                 None
             }
         }
     }
 
-    /// letStmt ::= "let" ident (: type)? ("=" expr)? ";"
+    /// letStmt ::= "let" "mut"? pattern (: type)? ("=" expr)? ";"
     /// https://doc.rust-lang.org/reference/statements.html#let-statements
     fn parse_let_stmt(&mut self) -> Option<Stmt> {
         // skip "let"
         let mut span = self.skip_token().span;
 
-        let ident = self.parse_ident()?;
-        // skip colon
-        if !self.skip_expected_token(TokenKind::Colon) {
-            eprintln!(
-                "Expected ':', but found `{}`",
-                self.peek_token().span.to_snippet()
+        let mut_span = self.peek_token().span.clone();
+        let mutable = self.skip_expected_token(TokenKind::Mut);
+        let pat = self.parse_pattern()?;
+        // e.g. `let mut (a, b)`, which is `let (mut a, mut b)` in Rust
+        if mutable && !matches!(pat.kind, PatternKind::Ident(_)) {
+            self.error(
+                "`mut` must be attached to each individual binding".to_string(),
+                mut_span,
             );
-            return None;
         }
-        // parse type
-        let ty = self.parse_type()?;
+        // parse (":" type)?
+        let ty = if self.skip_expected_token(TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
 
         // parse ("=" expr)?
         let t = self.peek_token();
@@ -75,17 +102,15 @@ impl Parser {
         // skip semi
         span = span.concat(&self.peek_token().span);
         if !self.skip_expected_token(TokenKind::Semi) {
-            eprintln!(
-                "Expected ';' for let statement, but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
+            self.error_expected("';' for let statement");
             return None;
         }
 
         Some(Stmt {
             kind: StmtKind::Let(LetStmt {
-                ident,
-                ty: Some(ty),
+                pat,
+                mutable,
+                ty,
                 init,
             }),
             id: self.get_next_id(),
@@ -97,36 +122,240 @@ impl Parser {
     pub fn parse_block(&mut self) -> Option<Block> {
         let mut span = self.peek_token().span.clone();
 
-        if !self.skip_expected_token(TokenKind::OpenBrace) {
-            eprintln!(
-                "Expected '{{' but found `{}`",
-                self.peek_token().span.to_snippet()
-            );
-            return None;
-        }
+        self.expect_token(TokenKind::OpenBrace)?;
         let mut stmts = vec![];
+        let mut saw_error = false;
         loop {
             let t = self.peek_token();
             if is_stmt_start(t) {
-                let stmt = self.parse_stmt()?;
-                span = span.concat(&stmt.span);
-                stmts.push(stmt);
+                if let Some(stmt) = self.parse_block_stmt() {
+                    span = span.concat(&stmt.span);
+                    // expression without trailing semicolon must be the last statement, or block-like
+                    if let StmtKind::Expr(expr) = &stmt.kind
+                        && !expr.is_block_like()
+                        && !self.peek_is(&TokenKind::CloseBrace)
+                    {
+                        let err = ParseError::expected(
+                            &[TokenKind::Semi, TokenKind::CloseBrace],
+                            self.peek_token(),
+                        );
+                        self.emit(err);
+                        saw_error = true;
+                    }
+                    stmts.push(stmt);
+                } else {
+                    // skip the broken statement and continue parsing to find more errors
+                    saw_error = true;
+                    self.recover_to(&[TokenKind::Semi, TokenKind::CloseBrace]);
+                    self.skip_expected_token(TokenKind::Semi);
+                }
+            } else if t.kind == TokenKind::Else {
+                // `else` is consumed by `parse_if_expr`, so it has no preceding `if` here
+                let span = self.skip_token().span;
+                self.error(
+                    "Unexpected `else` without a preceding `if` block".to_string(),
+                    span,
+                );
+                saw_error = true;
+            } else if t.kind == TokenKind::Semi {
+                // empty statement
+                let span = self.skip_token().span;
+                self.warn("Unnecessary trailing semicolon".to_string(), span);
             } else if t.kind == TokenKind::CloseBrace {
                 // skip '}'
                 span = span.concat(&self.skip_token().span);
+                if saw_error {
+                    return None;
+                }
                 return Some(Block {
                     stmts,
                     span,
                     id: self.get_next_id(),
                 });
             } else {
-                eprintln!(
-                    "Expected '}}' or statement, but found `{}`",
-                    t.span.to_snippet()
-                );
+                self.error_expected("'}' or statement");
                 break;
             }
         }
         None
     }
 }
+
+#[test]
+fn test_expr_and_semi_stmt() {
+    // `1` is the tail expression
+    let mut parser = Parser::from_source("{ 1 }");
+    let block = parser.parse_block().unwrap();
+    assert_eq!(block.stmts.len(), 1);
+    assert!(matches!(block.stmts[0].kind, StmtKind::Expr(_)));
+
+    // value of `1` is discarded
+    let mut parser = Parser::from_source("{ 1; }");
+    let block = parser.parse_block().unwrap();
+    assert_eq!(block.stmts.len(), 1);
+    assert!(matches!(block.stmts[0].kind, StmtKind::Semi(_)));
+
+    // block-like expressions do not need `;`
+    let mut parser = Parser::from_source("{ if true { } { } 1 }");
+    let block = parser.parse_block().unwrap();
+    assert_eq!(block.stmts.len(), 3);
+
+    let mut parser = Parser::from_source("{ 1 2 }");
+    assert!(parser.parse_block().is_none());
+}
+
+#[test]
+fn test_unnecessary_semicolon() {
+    use super::Severity;
+
+    let mut parser = Parser::from_source("{ 1;; ; }");
+    let block = parser.parse_block().unwrap();
+    assert_eq!(block.stmts.len(), 1);
+    let errors = parser.errors();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(errors[0].span.to_snippet(), ";");
+
+    // empty statements produce no statements
+    let mut parser = Parser::from_source("{ ; ; 1 }");
+    let block = parser.parse_block().unwrap();
+    let [tail] = &block.stmts[..] else { panic!() };
+    let StmtKind::Expr(expr) = &tail.kind else {
+        panic!()
+    };
+    assert_eq!(expr.span.to_snippet(), "1");
+    assert!(!parser.has_errors());
+}
+
+#[test]
+fn test_stray_else() {
+    let mut parser = Parser::from_source("{ else { } }");
+    assert!(parser.parse_block().is_none());
+    let mut parser = Parser::from_source("{ if true { } ; else { } }");
+    assert!(parser.parse_block().is_none());
+}
+
+#[test]
+fn test_let_if() {
+    use crate::ast::ExprKind;
+
+    let src = "{ let x = if c == 1 { 1 } else { 2 }; }";
+    let mut parser = Parser::from_source(src);
+    let block = parser.parse_block().unwrap();
+    let StmtKind::Let(let_stmt) = &block.stmts[0].kind else {
+        panic!()
+    };
+    assert_eq!(let_stmt.pat.bindings()[0].as_str(), "x");
+    assert!(let_stmt.ty.is_none());
+    let init = let_stmt.init.as_ref().unwrap();
+    let ExprKind::If(_, then, Some(els)) = &init.kind else {
+        panic!()
+    };
+    for branch in [then, els] {
+        let ExprKind::Block(block) = &branch.kind else {
+            panic!()
+        };
+        assert!(matches!(
+            block.stmts[..],
+            [Stmt {
+                kind: StmtKind::Expr(_),
+                ..
+            }]
+        ));
+    }
+}
+
+#[test]
+fn test_comments_between_stmts() {
+    use crate::lexer::Lexer;
+
+    let srcs = [
+        "{ let x = 1; // c\n x }",
+        "{ let x = 1 /* c */ ; /* c */ x /* c */ }",
+        "{ loop { } // c\n /* c */ x // c\n }",
+    ];
+    for src in srcs {
+        for keep_trivia in [false, true] {
+            let mut lexer = Lexer::new(src.to_string());
+            lexer.set_keep_trivia(keep_trivia);
+            let mut parser = Parser::new(lexer);
+            let block = parser.parse_block().unwrap();
+            assert!(parser.errors().is_empty(), "{}", src);
+            assert_eq!(block.stmts.len(), 2, "{}", src);
+            // the comment does not separate the tail expression from the block
+            assert!(matches!(block.stmts[1].kind, StmtKind::Expr(_)), "{}", src);
+            assert!(parser.at_eof());
+        }
+    }
+}
+
+#[test]
+fn test_let_mut() {
+    let parse = |src: &str| {
+        let stmt = Parser::from_source(src).parse_stmt().unwrap();
+        let StmtKind::Let(let_stmt) = stmt.kind else {
+            panic!()
+        };
+        let_stmt
+    };
+
+    let let_stmt = parse("let mut x = 0;");
+    assert!(let_stmt.mutable);
+    assert_eq!(let_stmt.pat.bindings()[0].as_str(), "x");
+    let let_stmt = parse("let y = 0;");
+    assert!(!let_stmt.mutable);
+    assert_eq!(let_stmt.pat.bindings()[0].as_str(), "y");
+
+    // only a single binding can be mutable
+    for src in [
+        "let mut (a, b) = t;",
+        "let mut _ = 0;",
+        "let mut [a] = arr;",
+    ] {
+        let mut parser = Parser::from_source(src);
+        parser.parse_stmt().unwrap_err();
+        let error = &parser.errors()[0];
+        assert_eq!(
+            error.msg,
+            "`mut` must be attached to each individual binding"
+        );
+        assert_eq!(error.span.to_snippet(), "mut");
+    }
+}
+
+#[test]
+fn test_parse_stmt() {
+    use crate::ast::{ExprKind, ItemKind};
+
+    let mut parser = Parser::from_source("let x = 1;");
+    let stmt = parser.parse_stmt().unwrap();
+    assert!(matches!(stmt.kind, StmtKind::Let(_)));
+    assert_eq!(stmt.span.to_snippet(), "let x = 1;");
+    assert!(parser.at_eof());
+
+    // trailing semicolon is consumed
+    let mut parser = Parser::from_source("f(x);");
+    let stmt = parser.parse_stmt().unwrap();
+    let StmtKind::Semi(expr) = stmt.kind else {
+        panic!()
+    };
+    assert!(matches!(expr.kind, ExprKind::Call(..)));
+    assert!(parser.at_eof());
+    let mut parser = Parser::from_source("x + 1");
+    let stmt = parser.parse_stmt().unwrap();
+    assert!(matches!(stmt.kind, StmtKind::Expr(_)));
+
+    let mut parser = Parser::from_source("fn f() -> i32 { 1 } 2");
+    let stmt = parser.parse_stmt().unwrap();
+    let StmtKind::Item(item) = stmt.kind else {
+        panic!()
+    };
+    assert!(matches!(item.kind, ItemKind::Func(_)));
+    assert_eq!(stmt.span.to_snippet(), "fn f() -> i32 { 1 }");
+
+    let mut parser = Parser::from_source("let x = ;");
+    let error = parser.parse_stmt().unwrap_err();
+    assert_eq!(error.msg, "Expected num or (expr), but found `;`");
+    // the error is kept
+    assert!(parser.has_errors());
+}
@@ -0,0 +1,294 @@
+use super::{ParseError, Parser};
+use crate::ast::{visitor, Block, Expr, ExprKind, NodeId, Pattern, Stmt, StmtKind};
+use crate::lexer::TokenKind;
+use crate::span::{Ident, Symbol};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub enum EvalError {
+    Parse(Vec<ParseError>),
+    /// Assignment to a binding not declared with `let mut`
+    AssignToImmutable(Ident),
+}
+
+/// `let` binding declared in a previous line
+struct Binding {
+    /// Id of the let statement
+    id: NodeId,
+    mutable: bool,
+}
+
+/// Parsing session for a REPL, which keeps its state across lines.
+/// Node ids are unique across lines, and `let` bindings of previous lines are visible.
+/// Symbols are interned globally, so they are shared by all lines as well.
+pub struct Session {
+    parser: Parser,
+    /// `let` bindings declared so far
+    bindings: HashMap<Symbol, Binding>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session {
+            parser: Parser::from_source(""),
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Parse a line as a statement. `;` of a let statement can be omitted.
+    /// Assignments to immutable bindings are rejected.
+    /// NOTE: the statement is not evaluated since there is no evaluator yet
+    pub fn eval_line(&mut self, line: &str) -> Result<Stmt, EvalError> {
+        self.parser.set_source(line);
+        if self.parser.peek_is(&TokenKind::Let) && !line.trim_end().ends_with(';') {
+            self.parser.set_source(&format!("{};", line));
+        }
+
+        let stmt = self.parser.parse_stmt().ok();
+        if stmt.is_some() && !self.parser.at_eof() {
+            self.parser.error_expected("end of line");
+        }
+        let errors = self.parser.take_errors();
+        let stmt = match stmt {
+            Some(stmt) if !errors.iter().any(|e| e.severity == super::Severity::Error) => stmt,
+            _ => return Err(EvalError::Parse(errors)),
+        };
+
+        // assignments may be nested (e.g. `if c { y = 2 }`)
+        let expr = match &stmt.kind {
+            StmtKind::Let(let_stmt) => let_stmt.init.as_ref(),
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => Some(&**expr),
+            StmtKind::Item(_) => None,
+        };
+        if let Some(expr) = expr {
+            let mut checker = AssignChecker {
+                bindings: &self.bindings,
+                scopes: vec![],
+                pending_scopes: HashMap::new(),
+                arm_bodies: vec![],
+                found: None,
+            };
+            visitor::go_expr(&mut checker, expr);
+            if let Some(ident) = checker.found {
+                return Err(EvalError::AssignToImmutable(ident.clone()));
+            }
+        }
+
+        match &stmt.kind {
+            StmtKind::Let(let_stmt) => {
+                // shadows the previous binding of the same name
+                for ident in let_stmt.pat.bindings() {
+                    let binding = Binding {
+                        id: stmt.id,
+                        mutable: let_stmt.mutable,
+                    };
+                    self.bindings.insert(ident.symbol, binding);
+                }
+            }
+            StmtKind::Expr(_) | StmtKind::Semi(_) | StmtKind::Item(_) => (),
+        }
+        Ok(stmt)
+    }
+
+    /// Find the let statement declaring `name` in previous lines
+    pub fn lookup_binding(&self, name: &Ident) -> Option<NodeId> {
+        self.bindings.get(&name.symbol).map(|binding| binding.id)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+/// Find the first assignment to an immutable binding.
+/// Bindings declared inside the statement shadow the ones of previous lines.
+struct AssignChecker<'a, 'ctx> {
+    bindings: &'a HashMap<Symbol, Binding>,
+    /// Names bound in each enclosing block, `for` body or match arm, with their mutability
+    scopes: Vec<Vec<(Symbol, bool)>>,
+    /// Names bound by `for` or match arm patterns, keyed by the id of the body
+    pending_scopes: HashMap<NodeId, Vec<(Symbol, bool)>>,
+    /// Match arm bodies whose scopes are pushed
+    arm_bodies: Vec<NodeId>,
+    found: Option<&'ctx Ident>,
+}
+
+impl AssignChecker<'_, '_> {
+    /// Whether the innermost binding of `name` is declared with `let mut`
+    fn is_mutable(&self, name: Symbol) -> Option<bool> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.iter())
+            .rev()
+            .find(|(symbol, _)| *symbol == name)
+            .map(|(_, mutable)| *mutable)
+            .or_else(|| self.bindings.get(&name).map(|binding| binding.mutable))
+    }
+}
+
+impl<'ctx> visitor::Visitor<'ctx> for AssignChecker<'_, 'ctx> {
+    fn visit_block(&mut self, block: &'ctx Block) {
+        let scope = self.pending_scopes.remove(&block.id).unwrap_or_default();
+        self.scopes.push(scope);
+    }
+
+    fn visit_block_post(&mut self, _block: &'ctx Block) {
+        self.scopes.pop();
+    }
+
+    fn visit_stmt_post(&mut self, stmt: &'ctx Stmt) {
+        // the initializer is out of the scope of the new bindings
+        if let StmtKind::Let(let_stmt) = &stmt.kind
+            && let Some(scope) = self.scopes.last_mut()
+        {
+            scope.extend(binding_names(&let_stmt.pat, let_stmt.mutable));
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ctx Expr) {
+        if let Some(scope) = self.pending_scopes.remove(&expr.id) {
+            self.scopes.push(scope);
+            self.arm_bodies.push(expr.id);
+        }
+        match &expr.kind {
+            ExprKind::Assign(lhs, _, _) | ExprKind::AssignOp(_, lhs, _, _) => {
+                if let Some(ident) = assigned_binding(lhs)
+                    && self.is_mutable(ident.symbol) == Some(false)
+                {
+                    self.found.get_or_insert(ident);
+                }
+            }
+            ExprKind::For(pat, _, body, _) => {
+                self.pending_scopes
+                    .insert(body.id, binding_names(pat, false));
+            }
+            ExprKind::Match(_, arms) => {
+                for arm in arms {
+                    self.pending_scopes
+                        .insert(arm.body.id, binding_names(&arm.pat, false));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn visit_expr_post(&mut self, expr: &'ctx Expr) {
+        if self.arm_bodies.last() == Some(&expr.id) {
+            self.arm_bodies.pop();
+            self.scopes.pop();
+        }
+    }
+}
+
+fn binding_names(pat: &Pattern, mutable: bool) -> Vec<(Symbol, bool)> {
+    pat.bindings()
+        .iter()
+        .map(|ident| (ident.symbol, mutable))
+        .collect()
+}
+
+/// Find the variable whose (part of) value is assigned (e.g. `x` for `x.a[0] = 1`)
+fn assigned_binding(place: &Expr) -> Option<&Ident> {
+    match &place.kind {
+        ExprKind::Path(path) if path.segments.len() == 1 => Some(&path.segments[0]),
+        ExprKind::Field(base, _) | ExprKind::Index(base, _) | ExprKind::Paren(base) => {
+            assigned_binding(base)
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_session() {
+    use crate::ast::ExprKind;
+
+    let mut session = Session::new();
+    let first = session.eval_line("let x = 1").unwrap();
+    let second = session.eval_line("x + 1").unwrap();
+    assert_ne!(first.id, second.id);
+
+    let StmtKind::Expr(expr) = second.kind else {
+        panic!()
+    };
+    let ExprKind::Binary(_, lhs, _) = expr.kind else {
+        panic!()
+    };
+    let ExprKind::Path(path) = lhs.kind else {
+        panic!()
+    };
+    assert_eq!(session.lookup_binding(&path.segments[0]), Some(first.id));
+
+    // errors do not break the session
+    assert!(session.eval_line("x +").is_err());
+    let Err(EvalError::Parse(errors)) = session.eval_line("x 1") else {
+        panic!()
+    };
+    assert_eq!(errors[0].msg, "Expected end of line, but found `1`");
+    let third = session.eval_line("let x = x;").unwrap();
+    assert_ne!(session.lookup_binding(&path.segments[0]), Some(first.id));
+    assert_eq!(session.lookup_binding(&path.segments[0]), Some(third.id));
+}
+
+#[test]
+fn test_assign_to_immutable() {
+    let mut session = Session::new();
+    session.eval_line("let mut x = 1").unwrap();
+    assert!(session.eval_line("x = 2").is_ok());
+    assert!(session.eval_line("x.a[0] = 2;").is_ok());
+
+    session.eval_line("let y = 1").unwrap();
+    let Err(EvalError::AssignToImmutable(ident)) = session.eval_line("y = 2") else {
+        panic!()
+    };
+    assert_eq!(ident.as_str(), "y");
+    assert!(matches!(
+        session.eval_line("y.a = 2;"),
+        Err(EvalError::AssignToImmutable(_))
+    ));
+
+    // nested assignments
+    for line in [
+        "{ y = 2 }",
+        "if c { y = 2 }",
+        "loop { y = 2; }",
+        "f(y = 2)",
+        "y += 1",
+        "let z = { y = 2; 1 };",
+        "match v { 0 => { y = 2 } _ => () }",
+        "{ let y = { y = 2; 1 }; }",
+    ] {
+        let Err(EvalError::AssignToImmutable(ident)) = session.eval_line(line) else {
+            panic!("{}", line)
+        };
+        assert_eq!(ident.as_str(), "y");
+    }
+    // shadowed inside the statement
+    assert!(session.eval_line("{ let mut y = 1; y = 2 }").is_ok());
+    // bindings inside the statement are checked as well
+    for (line, name) in [
+        ("{ let z = 1; z = 2 }", "z"),
+        ("{ let mut y = 1; let y = y; y = 2 }", "y"),
+        ("for y in 0..3 { y = 2; }", "y"),
+        ("match v { y => { y = 2 } }", "y"),
+    ] {
+        let Err(EvalError::AssignToImmutable(ident)) = session.eval_line(line) else {
+            panic!("{}", line)
+        };
+        assert_eq!(ident.as_str(), name);
+    }
+    // unknown names are left to the resolver
+    assert!(session.eval_line("w = 2").is_ok());
+    // the shadowing ends with the block or the arm
+    for line in [
+        "{ { let mut y = 1; } y = 2; }",
+        "match v { y => (), _ => { y = 2 } }",
+    ] {
+        assert!(session.eval_line(line).is_err(), "{}", line);
+    }
+
+    // shadowed by a mutable binding
+    session.eval_line("let mut y = y").unwrap();
+    assert!(session.eval_line("y = 2").is_ok());
+}
@@ -1,6 +1,9 @@
 mod resolve_crate;
 
-use crate::{ast::Path, span::Ident};
+use crate::{
+    ast::Path,
+    span::{Ident, Symbol},
+};
 use std::{collections::HashMap, rc::Rc};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -31,7 +34,7 @@ impl BindingKind {
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct CanonicalPath {
-    segments: Vec<Rc<String>>,
+    segments: Vec<Symbol>,
 }
 
 impl CanonicalPath {
@@ -41,23 +44,23 @@ impl CanonicalPath {
 
     fn krate() -> Self {
         CanonicalPath {
-            segments: vec![Rc::new("crate".to_string())],
+            segments: vec![Symbol::intern("crate")],
         }
     }
 
     fn from_path(prefix: &CanonicalPath, path: &Path) -> Self {
         let mut ret = prefix.clone();
         for seg in &path.segments {
-            ret.segments.push(Rc::clone(&seg.symbol));
+            ret.segments.push(seg.symbol);
         }
         ret
     }
 
-    fn push_seg(&mut self, seg: Rc<String>) {
+    fn push_seg(&mut self, seg: Symbol) {
         self.segments.push(seg);
     }
 
-    fn pop_seg(&mut self) -> Option<Rc<String>> {
+    fn pop_seg(&mut self) -> Option<Symbol> {
         self.segments.pop()
     }
 
@@ -68,7 +71,7 @@ impl CanonicalPath {
             if i == 0 {
                 continue;
             }
-            s.push_str(&seg);
+            s.push_str(seg.as_str());
             if i != self.segments.len() - 1 {
                 s.push_str("..");
             }
@@ -83,7 +86,7 @@ impl std::fmt::Debug for CanonicalPath {
             if i != 0 {
                 write!(f, "::")?;
             }
-            write!(f, "{}", *seg)?;
+            write!(f, "{}", seg)?;
         }
         Ok(())
     }
@@ -101,7 +104,7 @@ pub struct Rib {
     //     ^
     kind: RibKind,
     cpath: CanonicalPath,
-    bindings: HashMap<Rc<String>, Rc<Binding>>,
+    bindings: HashMap<Symbol, Rc<Binding>>,
     parent: Option<RibId>,
     children: Vec<RibId>,
 }
@@ -129,7 +132,7 @@ impl Rib {
     }
 
     // TODO: shadowing
-    pub fn insert_binding(&mut self, symbol: Rc<String>, binding: Binding) {
+    pub fn insert_binding(&mut self, symbol: Symbol, binding: Binding) {
         // FIXME: duplicate symbol?
         self.bindings.insert(symbol, Rc::new(binding));
     }
@@ -148,7 +151,7 @@ pub struct Resolver {
     current_cpath: CanonicalPath,
     next_rib_id: u32,
     // stack representing name scopes of variables
-    current_variable_scopes: Vec<HashMap<Rc<String>, Rc<Binding>>>,
+    current_variable_scopes: Vec<HashMap<Symbol, Rc<Binding>>>,
     // interned ribs
     interned: HashMap<RibId, Rib>,
     crate_rib_id: RibId,
@@ -264,7 +267,7 @@ impl Resolver {
         let prefixes = vec![&emp_cpath, &crate_cpath, &rib.cpath];
 
         // absolute path
-        if *path.segments.first().unwrap().symbol == "crate" {
+        if path.segments.first().unwrap().as_str() == "crate" {
             let mut result = None;
             // prefix: ["", "crate"]
             self.resolve_to_item_with_dfs(&prefixes, path, self.crate_rib_id, &mut result);
@@ -3,7 +3,7 @@ use std::{collections::HashMap, rc::Rc};
 use super::{Binding, BindingKind, ResolvedOrRib, Resolver, Rib, RibId, RibKind};
 use crate::{
     ast::{self, Path, StmtKind},
-    span::Ident,
+    span::{Ident, Symbol},
 };
 
 impl Resolver {
@@ -34,11 +34,11 @@ impl Resolver {
         id
     }
 
-    fn push_segment_to_current_cpath(&mut self, seg: Rc<String>) {
+    fn push_segment_to_current_cpath(&mut self, seg: Symbol) {
         self.current_cpath.push_seg(seg);
     }
 
-    fn pop_segment_from_current_cpath(&mut self) -> Option<Rc<String>> {
+    fn pop_segment_from_current_cpath(&mut self) -> Option<Symbol> {
         self.current_cpath.pop_seg()
     }
 
@@ -50,11 +50,11 @@ impl Resolver {
         self.current_variable_scopes.pop();
     }
 
-    fn get_current_scope_mut(&mut self) -> Option<&mut HashMap<Rc<String>, Rc<Binding>>> {
+    fn get_current_scope_mut(&mut self) -> Option<&mut HashMap<Symbol, Rc<Binding>>> {
         self.current_variable_scopes.last_mut()
     }
 
-    fn get_current_scopes(&self) -> &[HashMap<Rc<String>, Rc<Binding>>] {
+    fn get_current_scopes(&self) -> &[HashMap<Symbol, Rc<Binding>>] {
         &self.current_variable_scopes
     }
 
@@ -63,9 +63,9 @@ impl Resolver {
             .insert(ident.clone(), *self.current_ribs.last().unwrap());
 
         let mut cpath = self.current_cpath.clone();
-        cpath.push_seg(Rc::clone(&ident.symbol));
+        cpath.push_seg(ident.symbol);
         self.get_current_rib_mut().insert_binding(
-            Rc::clone(&ident.symbol),
+            ident.symbol,
             Binding {
                 kind: kind,
                 cpath: Rc::new(cpath),
@@ -86,7 +86,7 @@ impl Resolver {
 
     fn insert_var_decl(&mut self, ident: &Ident, kind: BindingKind) {
         let mut cpath = self.current_cpath.clone();
-        cpath.push_seg(Rc::clone(&ident.symbol));
+        cpath.push_seg(ident.symbol);
         let binding = Binding {
             kind,
             cpath: Rc::new(cpath),
@@ -97,7 +97,7 @@ impl Resolver {
 
         self.get_current_scope_mut()
             .unwrap()
-            .insert(Rc::clone(&ident.symbol), binding);
+            .insert(ident.symbol, binding);
     }
 
     fn find_variable_in_scope(&self, path: &Path) -> Option<Rc<Binding>> {
@@ -125,12 +125,29 @@ impl Resolver {
             ResolvedOrRib::UnResolved(*self.current_ribs.last().unwrap()),
         );
     }
+
+    /// Resolve names in an expression which is not in any item, as if it were a function body
+    #[cfg(test)]
+    pub fn resolve_expr(&mut self, expr: &ast::Expr) {
+        self.push_segment_to_current_cpath(Symbol::intern("crate"));
+        self.push_rib(RibKind::Mod);
+        self.crate_rib_id = self.get_current_rib_mut().id;
+        self.push_rib(RibKind::Func);
+        self.push_variable_scope();
+
+        ast::visitor::go_expr(self, expr);
+
+        self.pop_variable_scope();
+        self.pop_rib();
+        self.pop_rib();
+        self.pop_segment_from_current_cpath().unwrap();
+    }
 }
 
 impl<'ctx> ast::visitor::Visitor<'ctx> for Resolver {
     fn visit_crate(&mut self, _krate: &'ctx ast::Crate) {
         // push "crate" to cpath
-        self.push_segment_to_current_cpath(Rc::new("crate".to_string()));
+        self.push_segment_to_current_cpath(Symbol::intern("crate"));
 
         // push new rib
         self.push_rib(RibKind::Mod);
@@ -142,7 +159,7 @@ impl<'ctx> ast::visitor::Visitor<'ctx> for Resolver {
         // pop "crate" from current cpath
         let krate = self.pop_segment_from_current_cpath().unwrap();
 
-        assert_eq!(*krate, "crate");
+        assert_eq!(krate.as_str(), "crate");
         // pop rib
         let krate_rib = self.pop_rib();
         assert_eq!(krate_rib, 0);
@@ -153,7 +170,7 @@ impl<'ctx> ast::visitor::Visitor<'ctx> for Resolver {
         self.insert_item_def(&module.name, BindingKind::Mod);
 
         // push module name to cpath
-        self.push_segment_to_current_cpath(Rc::clone(&module.name.symbol));
+        self.push_segment_to_current_cpath(module.name.symbol);
         // push new rib
         self.push_rib(RibKind::Mod);
     }
@@ -171,7 +188,7 @@ impl<'ctx> ast::visitor::Visitor<'ctx> for Resolver {
         self.insert_item_def(&func.name, BindingKind::Item);
 
         // push func name to cpath
-        self.push_segment_to_current_cpath(Rc::clone(&func.name.symbol));
+        self.push_segment_to_current_cpath(func.name.symbol);
 
         // push new rib
         self.push_rib(RibKind::Func);
@@ -220,8 +237,10 @@ impl<'ctx> ast::visitor::Visitor<'ctx> for Resolver {
     fn visit_stmt_post(&mut self, stmt: &'ctx ast::Stmt) {
         if let StmtKind::Let(let_stmt) = &stmt.kind {
             // insert local variables
-            let shadowing_index = self.get_num_of_same_variable_name_in_scopes(&let_stmt.ident);
-            self.insert_var_decl(&let_stmt.ident, BindingKind::Let(shadowing_index));
+            for ident in let_stmt.pat.bindings() {
+                let shadowing_index = self.get_num_of_same_variable_name_in_scopes(ident);
+                self.insert_var_decl(ident, BindingKind::Let(shadowing_index));
+            }
         }
     }
 
@@ -0,0 +1,65 @@
+use crate::{
+    ast::{self, structural::Structural, Crate, Expr, ExprKind, UnOp},
+    parse::{ParseError, Severity},
+};
+
+/// Warn assignments whose lhs and rhs are the same place (e.g. `x = x`, `a.b = a.b`),
+/// which are no-ops and likely typos.
+pub fn check(krate: &Crate) -> Vec<ParseError> {
+    let mut checker = Checker { warnings: vec![] };
+    ast::visitor::go(&mut checker, krate);
+    checker.warnings
+}
+
+struct Checker {
+    warnings: Vec<ParseError>,
+}
+
+/// Whether evaluating the place expression has no side effects, so that
+/// assigning it to itself does nothing.
+/// e.g. `a[i].b` is, but `a[f()]` is not
+fn is_pure_place(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Path(_) | ExprKind::NumLit(..) => true,
+        ExprKind::Field(recv, _) | ExprKind::Paren(recv) | ExprKind::Unary(UnOp::Deref, recv) => {
+            is_pure_place(recv)
+        }
+        ExprKind::Index(array, index) => is_pure_place(array) && is_pure_place(index),
+        _ => false,
+    }
+}
+
+impl<'ctx> ast::visitor::Visitor<'ctx> for Checker {
+    fn visit_expr(&mut self, expr: &'ctx Expr) {
+        let ExprKind::Assign(lhs, rhs, _) = &expr.kind else {
+            return;
+        };
+        if is_pure_place(lhs) && Structural(lhs) == Structural(rhs) {
+            self.warnings.push(ParseError {
+                msg: format!("`{}` is assigned to itself", lhs.span.to_snippet()),
+                span: expr.span.clone(),
+                severity: Severity::Warning,
+            });
+        }
+    }
+}
+
+#[test]
+fn test_self_assign() {
+    use crate::parse::Parser;
+
+    let check_src = |src: &str| check(&Parser::from_source(src).parse_crate());
+
+    let warnings = check_src("fn f() { x = x; }");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].msg, "`x` is assigned to itself");
+    assert_eq!(warnings[0].span.to_snippet(), "x = x");
+
+    assert_eq!(check_src("fn f() { a.b[0] = a.b[0]; *p = (*p); }").len(), 2);
+    assert!(check_src("fn f() { x = y; }").is_empty());
+    assert!(check_src("fn f() { a.b = a.c; a[0] = a[1]; }").is_empty());
+    assert!(check_src("fn f() { x += x; x *= 1; }").is_empty());
+    // evaluating the place may have side effects
+    assert!(check_src("fn f() { a[g()] = a[g()]; }").is_empty());
+}
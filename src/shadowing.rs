@@ -0,0 +1,65 @@
+use crate::{
+    ast::{self, Crate, StmtKind},
+    parse::{ParseError, Severity},
+    span::Symbol,
+};
+use std::collections::HashSet;
+
+/// Warn `let` statements shadowing a binding declared in the same block
+/// (e.g. `let x = 1; let x = 2;`). Shadowing bindings of outer blocks is fine.
+pub fn check(krate: &Crate) -> Vec<ParseError> {
+    let mut checker = Checker {
+        scopes: vec![],
+        warnings: vec![],
+    };
+    ast::visitor::go(&mut checker, krate);
+    checker.warnings
+}
+
+struct Checker {
+    /// Names bound in each block being visited (innermost last)
+    scopes: Vec<HashSet<Symbol>>,
+    warnings: Vec<ParseError>,
+}
+
+impl<'ctx> ast::visitor::Visitor<'ctx> for Checker {
+    fn visit_block(&mut self, _block: &'ctx ast::Block) {
+        self.scopes.push(HashSet::new());
+    }
+
+    fn visit_block_post(&mut self, _block: &'ctx ast::Block) {
+        self.scopes.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ctx ast::Stmt) {
+        let StmtKind::Let(let_stmt) = &stmt.kind else {
+            return;
+        };
+        let scope = self.scopes.last_mut().unwrap();
+        for ident in let_stmt.pat.bindings() {
+            if !scope.insert(ident.symbol) {
+                self.warnings.push(ParseError {
+                    msg: format!("`{}` shadows a binding in the same block", ident.as_str()),
+                    span: ident.span.clone(),
+                    severity: Severity::Warning,
+                });
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shadowing_in_same_block() {
+    use crate::parse::Parser;
+
+    let check_src = |src: &str| check(&Parser::from_source(src).parse_crate());
+
+    let warnings = check_src("fn f() { let x = 1; let x = 2; }");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].span.to_snippet(), "x");
+
+    assert!(check_src("fn f() { let x = 1; { let x = 2; } }").is_empty());
+    assert!(check_src("fn f() { let x = 1; let y = 2; }").is_empty());
+    assert!(check_src("fn f() { { let x = 1; } let x = 2; }").is_empty());
+}
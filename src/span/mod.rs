@@ -1,5 +1,14 @@
-use std::cmp::{max, min};
-use std::rc::Rc;
+#[cfg(not(feature = "no_std"))]
+mod symbol;
+
+// the interner needs thread-local storage
+#[cfg(not(feature = "no_std"))]
+pub use symbol::Symbol;
+
+use alloc::rc::Rc;
+#[cfg(feature = "no_std")]
+use alloc::string::String;
+use core::cmp::{max, min};
 
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct Span {
@@ -37,24 +46,134 @@ impl Span {
     pub fn hi(&self) -> usize {
         self.hi
     }
+
+    /// Whether the byte offset is in the span
+    pub fn contains(&self, offset: usize) -> bool {
+        self.lo <= offset && offset < self.hi
+    }
+
+    /// Source which the span points into
+    pub fn src(&self) -> Rc<String> {
+        Rc::clone(&self.src)
+    }
+}
+
+/// Line and column numbers (both 1-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Computes line and column numbers from spans (byte offsets)
+pub struct Locator {
+    /// Tabs advance columns to the next multiple of `tab_width`, as editors display them
+    pub tab_width: usize,
+}
+
+impl Default for Locator {
+    fn default() -> Self {
+        Locator { tab_width: 4 }
+    }
+}
+
+impl Locator {
+    /// Line and column where the span starts
+    pub fn line_col(&self, span: &Span) -> LineCol {
+        self.line_col_at(&span.src, span.lo)
+    }
+
+    /// Line and column just after the end of the span, which differ from
+    /// `line_col` in line for spans over multiple lines
+    pub fn end_line_col(&self, span: &Span) -> LineCol {
+        self.line_col_at(&span.src, span.hi)
+    }
+
+    fn line_col_at(&self, src: &str, offset: usize) -> LineCol {
+        let before = &src[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        // count columns by chars, not bytes
+        let col = before[line_start..].chars().fold(0, |col, c| {
+            if c == '\t' {
+                (col / self.tab_width + 1) * self.tab_width
+            } else {
+                col + 1
+            }
+        });
+        LineCol { line, col: col + 1 }
+    }
 }
 
-impl std::fmt::Debug for Span {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Span {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}..{}", self.lo, self.hi)
     }
 }
 
+#[cfg(not(feature = "no_std"))]
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Ident {
     // TODO: remove symbol and span
     // add ident: crate::span::Ident
-    pub symbol: Rc<String>,
+    pub symbol: Symbol,
     pub span: Span,
 }
 
-impl std::fmt::Debug for Ident {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[cfg(not(feature = "no_std"))]
+impl Ident {
+    pub fn as_str(&self) -> &'static str {
+        self.symbol.as_str()
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl core::fmt::Debug for Ident {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "\"{}\" ({:?})", self.symbol, self.span)
     }
 }
+
+#[test]
+fn test_line_col() {
+    use crate::lexer::{Lexer, TokenKind};
+
+    // build spans from the lexer (tabs are lexed as unknown tokens)
+    let mut lexer = Lexer::new("a\n\tb\n  \tc 'é'\td \"日本\" e".to_string());
+    let mut tokens = vec![];
+    while lexer.peek_token().kind != TokenKind::Eof {
+        tokens.push(lexer.skip_token());
+    }
+    let span_of = |snippet: &str| {
+        let token = tokens.iter().find(|t| t.span.to_snippet() == snippet);
+        token.unwrap().span.clone()
+    };
+
+    let locator = Locator::default();
+    assert_eq!(locator.line_col(&span_of("a")), LineCol { line: 1, col: 1 });
+    // a token after a tab
+    assert_eq!(locator.line_col(&span_of("b")), LineCol { line: 2, col: 5 });
+    // a tab after spaces advances to the next tab stop
+    assert_eq!(locator.line_col(&span_of("c")), LineCol { line: 3, col: 5 });
+    // multi-byte chars are one column
+    assert_eq!(
+        locator.line_col(&span_of("d")),
+        LineCol { line: 3, col: 13 }
+    );
+    assert_eq!(
+        locator.line_col(&span_of("e")),
+        LineCol { line: 3, col: 20 }
+    );
+    assert_eq!(
+        locator.end_line_col(&span_of("\"日本\"")),
+        LineCol { line: 3, col: 19 }
+    );
+
+    // a span over lines ends on the last line
+    let span = span_of("a").concat(&span_of("b"));
+    assert_eq!(locator.line_col(&span), LineCol { line: 1, col: 1 });
+    assert_eq!(locator.end_line_col(&span), LineCol { line: 2, col: 6 });
+
+    let locator = Locator { tab_width: 8 };
+    assert_eq!(locator.line_col(&span_of("b")), LineCol { line: 2, col: 9 });
+}
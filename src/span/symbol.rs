@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Interned string
+/// Two symbols are equal iff their strings are equal, so comparing and hashing is cheap.
+/// ref: https://doc.rust-lang.org/stable/nightly-rustc/rustc_span/symbol/struct.Symbol.html
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn intern(s: &str) -> Self {
+        INTERNER.with(|interner| interner.borrow_mut().intern(s))
+    }
+
+    /// Get the string the symbol is resolved to
+    pub fn as_str(&self) -> &'static str {
+        INTERNER.with(|interner| interner.borrow().get(*self))
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+#[derive(Default)]
+struct Interner {
+    names: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+impl Interner {
+    fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(sym) = self.names.get(s) {
+            return *sym;
+        }
+        let sym = Symbol(self.strings.len() as u32);
+        // interned strings live until the compiler exits
+        let s: &'static str = Box::leak(s.to_string().into_boxed_str());
+        self.strings.push(s);
+        self.names.insert(s, sym);
+        sym
+    }
+
+    fn get(&self, sym: Symbol) -> &'static str {
+        self.strings[sym.0 as usize]
+    }
+}
+
+#[test]
+fn test_intern() {
+    let x1 = Symbol::intern("x");
+    let x2 = Symbol::intern("x");
+    let y = Symbol::intern("y");
+    assert_eq!(x1, x2);
+    assert_ne!(x1, y);
+    assert_eq!(x1.as_str(), "x");
+    assert_eq!(y.as_str(), "y");
+}
@@ -1,6 +1,8 @@
-use crate::ast::{self, BinOp, Crate, ExprKind, LetStmt, Stmt, StmtKind};
+use crate::ast::{self, BinOp, Crate, ExprKind, LetStmt, PatternKind, Stmt, StmtKind};
 use crate::middle::ty::{self, AdtDef, Ty, TyKind};
 use crate::middle::Ctxt;
+use crate::span::{Ident, Symbol};
+use std::collections::HashSet;
 use std::rc::Rc;
 
 pub fn typeck<'ctx, 'chk>(
@@ -16,17 +18,52 @@ pub fn typeck<'ctx, 'chk>(
     }
 }
 
+/// Errors found by typechecking a standalone expression
+#[cfg(test)]
+#[derive(Debug)]
+pub struct TypeError(pub Vec<String>);
+
+/// Typecheck an expression which is not in any item (e.g. `if 1 < 2 { true } else { false }`).
+/// Only local variables declared in the expression can be used.
+#[cfg(test)]
+pub fn check(expr: &ast::Expr) -> Result<Rc<Ty>, TypeError> {
+    let mut ctx = Ctxt::new(false);
+    ctx.run_resolver_on_expr(expr);
+    let mut checker = TypeChecker::new(&mut ctx);
+    ast::visitor::go_expr(&mut checker, expr);
+    if checker.errors.is_empty() {
+        Ok(ctx.get_type(expr.id))
+    } else {
+        Err(TypeError(checker.errors))
+    }
+}
+
 struct TypeChecker<'ctx, 'chk> {
     ctx: &'chk mut Ctxt<'ctx>,
     current_return_type: Option<Ty>,
+    /// Stack of loops enclosing the current expression
+    loops: Vec<LoopCtxt>,
+    /// Integer literals which are operands of unary minus (e.g. `-2147483648`)
+    negated_lits: HashSet<ast::NodeId>,
     errors: Vec<String>,
 }
 
+/// Information of a loop used to typecheck `break` and `continue`
+struct LoopCtxt {
+    label: Option<Symbol>,
+    /// `loop` can be broken with a value, but `while` and `for` cannot
+    is_while: bool,
+    /// Type of values given to `break`. `None` if no `break` is found so far.
+    break_ty: Option<Rc<Ty>>,
+}
+
 impl<'ctx, 'chk> TypeChecker<'ctx, 'chk> {
     fn new(ctx: &'chk mut Ctxt<'ctx>) -> Self {
         TypeChecker {
             ctx,
             current_return_type: None,
+            loops: vec![],
+            negated_lits: HashSet::new(),
             errors: vec![],
         }
     }
@@ -47,6 +84,18 @@ impl<'ctx, 'chk> TypeChecker<'ctx, 'chk> {
         self.current_return_type = None;
     }
 
+    /// Find the loop which `break` or `continue` with the label refers to
+    fn find_loop_mut(&mut self, label: &Option<Ident>) -> Option<&mut LoopCtxt> {
+        if let Some(label) = label {
+            self.loops
+                .iter_mut()
+                .rev()
+                .find(|l| l.label == Some(label.symbol))
+        } else {
+            self.loops.last_mut()
+        }
+    }
+
     fn get_block_type(&self, block: &ast::Block) -> Rc<Ty> {
         if let Some(stmt) = block.stmts.last() {
             let last_stmt_ty = &self.ctx.get_type(stmt.id);
@@ -69,9 +118,16 @@ impl<'ctx, 'chk> TypeChecker<'ctx, 'chk> {
             ast::TyKind::Array(elem_ty, n) => {
                 ty::TyKind::Array(Rc::new(self.ast_ty_to_ty(elem_ty)), *n)
             }
-            ast::TyKind::Adt(path) => {
+            ast::TyKind::Adt(_, args) if !args.is_empty() => {
+                self.error("Generic type arguments are not supported yet".to_string());
+                ty::TyKind::Error
+            }
+            ast::TyKind::Adt(path, _) => {
                 if let Some(binding) = self.ctx.resolve_path(path) {
                     ty::TyKind::Adt(Rc::clone(&binding.cpath))
+                } else if is_primitive(path, "char") {
+                    // `char` is not a keyword, so it is parsed as a path
+                    ty::TyKind::Char
                 } else {
                     self.error(format!("{:?}", path));
                     ty::TyKind::Error
@@ -80,6 +136,23 @@ impl<'ctx, 'chk> TypeChecker<'ctx, 'chk> {
             ast::TyKind::ConstPtr(referent) => {
                 ty::TyKind::ConstPtr(Rc::new(self.ast_ty_to_ty(referent)))
             }
+            // TODO: lower function pointers in codegen
+            ast::TyKind::Fn(..) => {
+                self.error("Function pointer types are not supported yet".to_string());
+                ty::TyKind::Error
+            }
+            ast::TyKind::Tuple(_) => {
+                self.error("Tuple types are not supported yet".to_string());
+                ty::TyKind::Error
+            }
+            ast::TyKind::ImplTrait(_) => {
+                self.error("`impl Trait` types are not supported yet".to_string());
+                ty::TyKind::Error
+            }
+            ast::TyKind::DynTrait(_) => {
+                self.error("`dyn Trait` types are not supported yet".to_string());
+                ty::TyKind::Error
+            }
         };
         Ty::new(kind)
     }
@@ -90,11 +163,26 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
 
     fn visit_crate_post(&mut self, _krate: &'chk Crate) {}
 
+    fn visit_item(&mut self, item: &'chk ast::Item) {
+        if let ast::ItemKind::Static(static_item) = &item.kind {
+            self.error(format!(
+                "Static items are not supported yet: `{}`",
+                static_item.name.symbol
+            ));
+        }
+    }
+
     // TODO: allow func call before finding declaration of the func
     // TODO: what if typechecker does not find a body of non-external func?
     // TODO: external func must not have its body (correct?)
     fn visit_func(&mut self, func: &'chk ast::Func) {
         // TODO: typecheck main func
+        if !func.generics.is_empty() {
+            self.error(format!(
+                "Generic functions are not supported yet: `{}`",
+                func.name.symbol
+            ));
+        }
         let param_tys = func
             .params
             .iter()
@@ -139,10 +227,10 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
     }
 
     fn visit_struct_item(&mut self, strct: &'chk ast::StructItem) {
-        let field_tys: Vec<(Rc<String>, Rc<Ty>)> = strct
+        let field_tys: Vec<(Symbol, Rc<Ty>)> = strct
             .fields
             .iter()
-            .map(|(name, ty)| (Rc::clone(&name.symbol), Rc::new(self.ast_ty_to_ty(ty))))
+            .map(|(name, ty)| (name.symbol, Rc::new(self.ast_ty_to_ty(ty))))
             .collect();
         let adt = AdtDef { fields: field_tys };
         let binding = self.ctx.get_binding(&strct.ident).unwrap();
@@ -159,8 +247,41 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                     Rc::new(Ty::unit())
                 }
             }
-            StmtKind::Let(LetStmt { init, ty, ident: _ }) => {
-                if let Some(init) = init {
+            StmtKind::Let(LetStmt { pat, init, ty, .. }) => {
+                // `None` for `let _`
+                let ident = match &pat.kind {
+                    PatternKind::Ident(ident) => Some(ident),
+                    PatternKind::Wild => None,
+                    // rejected in `visit_stmt`
+                    _ => {
+                        self.ctx.insert_type(stmt.id, Rc::new(Ty::error()));
+                        return;
+                    }
+                };
+                if ty.is_none() {
+                    // infer the type of the local variable from its initializer
+                    let binding = ident.map(|ident| self.ctx.get_binding(ident).unwrap());
+                    if let Some(init) = init {
+                        let init_ty = self.ctx.get_type(init.id);
+                        if let Some(binding) = binding {
+                            self.ctx.set_name_type(binding, Rc::clone(&init_ty));
+                        }
+                        if init_ty.is_never() {
+                            Rc::new(Ty::never())
+                        } else {
+                            Rc::new(Ty::unit())
+                        }
+                    } else {
+                        self.error(format!(
+                            "Type annotations needed for `{}`",
+                            pat.span.to_snippet()
+                        ));
+                        if let Some(binding) = binding {
+                            self.ctx.set_name_type(binding, Rc::new(Ty::error()));
+                        }
+                        Rc::new(Ty::error())
+                    }
+                } else if let Some(init) = init {
                     let init_ty = self.ctx.get_type(init.id);
                     let annotated_ty = self.ast_ty_to_ty(ty.as_ref().unwrap());
                     if init_ty.is_never() {
@@ -181,6 +302,8 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                 }
             }
             StmtKind::Expr(expr) => self.ctx.get_type(expr.id),
+            // rejected in `visit_stmt`
+            StmtKind::Item(_) => Rc::new(Ty::error()),
         };
         self.ctx.insert_type(stmt.id, ty);
     }
@@ -190,30 +313,88 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
     fn visit_stmt(&mut self, stmt: &'chk Stmt) {
         match &stmt.kind {
             StmtKind::Let(let_stmt) => {
-                // set local variable type
-                let binding = self.ctx.get_binding(&let_stmt.ident).unwrap();
-                // set type of local variable
-                // TODO: unwrap
-                let annotated_ty = self.ast_ty_to_ty(let_stmt.ty.as_ref().unwrap());
-                self.ctx
-                    .set_name_type(Rc::clone(&binding), Rc::new(annotated_ty));
+                let ident = match &let_stmt.pat.kind {
+                    PatternKind::Ident(ident) => Some(ident),
+                    PatternKind::Wild => None,
+                    _ => {
+                        self.error("Patterns in let statements are not supported yet".to_string());
+                        for ident in let_stmt.pat.bindings() {
+                            let binding = self.ctx.get_binding(ident).unwrap();
+                            self.ctx.set_name_type(binding, Rc::new(Ty::error()));
+                        }
+                        return;
+                    }
+                };
+                // type of local variable without annotation is inferred in `visit_stmt_post`
+                let Some(ty) = &let_stmt.ty else { return };
+                if let Some(ident) = ident {
+                    // set type of local variable
+                    let binding = self.ctx.get_binding(ident).unwrap();
+                    let annotated_ty = self.ast_ty_to_ty(ty);
+                    self.ctx
+                        .set_name_type(Rc::clone(&binding), Rc::new(annotated_ty));
+                }
                 // set type of statement
-                let stmt_ty = self.ast_ty_to_ty(let_stmt.ty.as_ref().unwrap());
-                // TODO: unwrap
+                let stmt_ty = self.ast_ty_to_ty(ty);
                 self.ctx.insert_type(stmt.id, Rc::new(stmt_ty));
             }
+            StmtKind::Item(_) => {
+                self.error("Items in blocks are not supported yet".to_string());
+            }
             _ => {}
         }
     }
 
+    fn visit_expr(&mut self, expr: &'chk ast::Expr) {
+        match &expr.kind {
+            ExprKind::Loop(_, label)
+            | ExprKind::While(_, _, label)
+            | ExprKind::For(_, _, _, label) => {
+                self.loops.push(LoopCtxt {
+                    label: label.as_ref().map(|l| l.symbol),
+                    is_while: !matches!(expr.kind, ExprKind::Loop(..)),
+                    break_ty: None,
+                });
+            }
+            ExprKind::Unary(ast::UnOp::Minus, inner)
+                if matches!(inner.kind, ExprKind::NumLit(..)) =>
+            {
+                self.negated_lits.insert(inner.id);
+            }
+            _ => (),
+        }
+    }
+
     // use post order
     fn visit_expr_post(&mut self, expr: &'chk ast::Expr) {
         let ty: Rc<Ty> = match &expr.kind {
-            ExprKind::NumLit(_) => Rc::new(Ty::new(TyKind::I32)),
+            ExprKind::NumLit(_, Some(suffix)) if suffix != "i32" => {
+                self.error(format!(
+                    "Integer literal suffixes other than `i32` are not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::NumLit(n, _) => {
+                let max = if self.negated_lits.contains(&expr.id) {
+                    i32::MAX as u128 + 1
+                } else {
+                    i32::MAX as u128
+                };
+                if *n <= max {
+                    Rc::new(Ty::new(TyKind::I32))
+                } else {
+                    self.error(format!(
+                        "Literal `{}` is out of range for i32",
+                        expr.span.to_snippet()
+                    ));
+                    Rc::new(Ty::error())
+                }
+            }
             ExprKind::BoolLit(_) => Rc::new(Ty::new(TyKind::Bool)),
             ExprKind::StrLit(_) => Rc::new(Ty::new(TyKind::Ref(Rc::new(Ty::new(TyKind::Str))))),
             ExprKind::Unit => Rc::new(Ty::unit()),
-            ExprKind::Assign(l, r) => {
+            ExprKind::Assign(l, r, _) => {
                 let lhs_ty = &self.ctx.get_type(l.id);
                 let rhs_ty = &self.ctx.get_type(r.id);
                 if rhs_ty.is_never() || **lhs_ty == **rhs_ty {
@@ -223,41 +404,75 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                     Rc::new(Ty::error())
                 }
             }
+            ExprKind::AssignOp(_, l, r, _) => {
+                let lhs_ty = &self.ctx.get_type(l.id);
+                let rhs_ty = &self.ctx.get_type(r.id);
+                if lhs_ty.kind == TyKind::I32 && rhs_ty.kind == TyKind::I32 {
+                    Rc::new(Ty::unit())
+                } else {
+                    self.error("Both lhs and rhs must be type of i32".to_string());
+                    Rc::new(Ty::error())
+                }
+            }
             // TODO: deal with never type
             ExprKind::Binary(op, l, r) => {
                 let lhs_ty = &self.ctx.get_type(l.id);
                 let rhs_ty = &self.ctx.get_type(r.id);
-                match op {
-                    BinOp::Add | BinOp::Sub | BinOp::Mul => {
+                let operands_ok = match op {
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Gt | BinOp::Lt => {
                         if lhs_ty.kind == TyKind::I32 && rhs_ty.kind == TyKind::I32 {
-                            Rc::new(Ty::new(TyKind::I32))
+                            true
                         } else {
                             self.error("Both lhs and rhs must be type of i32".to_string());
-                            Rc::new(Ty::error())
+                            false
                         }
                     }
-                    BinOp::Gt | BinOp::Lt => {
-                        if lhs_ty.kind == TyKind::I32 && rhs_ty.kind == TyKind::I32 {
-                            Rc::new(Ty::new(TyKind::I32))
+                    BinOp::And => {
+                        let is_bool = |ty: &Ty| ty.is_never() || ty.kind == TyKind::Bool;
+                        if is_bool(lhs_ty) && is_bool(rhs_ty) {
+                            true
                         } else {
-                            self.error("Both lhs and rhs must be type of i32".to_string());
-                            Rc::new(Ty::error())
+                            self.error("Both lhs and rhs must be type of bool".to_string());
+                            false
                         }
                     }
                     BinOp::Eq | BinOp::Ne => {
                         // TODO: other types?
-                        if (lhs_ty.kind == TyKind::I32 && rhs_ty.kind == TyKind::I32)
-                            || (lhs_ty.kind == TyKind::Bool && rhs_ty.kind == TyKind::Bool)
-                        {
-                            Rc::new(Ty::new(TyKind::Bool))
+                        let is_comparable =
+                            |ty: &Ty| matches!(ty.kind, TyKind::I32 | TyKind::Bool | TyKind::Char);
+                        if is_comparable(lhs_ty) && lhs_ty.kind == rhs_ty.kind {
+                            true
                         } else {
                             self.error("Both lhs and rhs must have the same type".to_string());
-                            Rc::new(Ty::error())
+                            false
                         }
                     }
+                };
+                if !operands_ok {
+                    Rc::new(Ty::error())
+                } else if op.result_is_bool() {
+                    Rc::new(Ty::new(TyKind::Bool))
+                } else {
+                    Rc::new(Ty::new(TyKind::I32))
                 }
             }
             // TODO: deal with never type
+            ExprKind::Unary(ast::UnOp::Deref | ast::UnOp::Ref, _) => {
+                self.error(format!(
+                    "References are not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::Unary(ast::UnOp::Not, inner) => {
+                let inner_ty = &self.ctx.get_type(inner.id);
+                if inner_ty.kind == TyKind::I32 || inner_ty.kind == TyKind::Bool {
+                    Rc::clone(inner_ty)
+                } else {
+                    self.error("inner expr of `!` must be type of i32 or bool".to_string());
+                    Rc::new(Ty::error())
+                }
+            }
             ExprKind::Unary(_op, inner) => {
                 let inner_ty = &self.ctx.get_type(inner.id);
                 if inner_ty.kind == TyKind::I32 {
@@ -282,8 +497,12 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                 }
             }
 
-            ExprKind::Return(expr) => {
-                let actual_ret_ty = self.ctx.get_type(expr.id);
+            ExprKind::Return(value) => {
+                let actual_ret_ty = if let Some(value) = value {
+                    self.ctx.get_type(value.id)
+                } else {
+                    Rc::new(Ty::unit())
+                };
                 let expected_ret_ty = self.peek_return_type();
                 if *actual_ret_ty == *expected_ret_ty {
                     Rc::new(Ty::never())
@@ -296,9 +515,19 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                 }
             }
             // TODO: deal with never type params
-            ExprKind::Call(expr, args) => {
+            ExprKind::Call(expr, type_args, args) => {
                 let maybe_func_ty = self.ctx.get_type(expr.id);
-                if let TyKind::Fn(param_ty, ret_ty) = &maybe_func_ty.kind {
+                if !type_args.is_empty() {
+                    self.error("Generic arguments are not supported yet".to_string());
+                    Rc::new(Ty::error())
+                } else if matches!(maybe_func_ty.kind, TyKind::Fn(..)) && !is_path(expr) {
+                    // e.g. `fns[0]()`
+                    self.error(format!(
+                        "Calling non-path expressions is not supported yet: `{}`",
+                        expr.span.to_snippet()
+                    ));
+                    Rc::new(Ty::error())
+                } else if let TyKind::Fn(param_ty, ret_ty) = &maybe_func_ty.kind {
                     if param_ty.len() == args.len() {
                         let mut ok = true;
                         for (arg, param_ty) in args.iter().zip(param_ty.iter()) {
@@ -330,17 +559,27 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                 }
             }
             ExprKind::Block(block) => self.ctx.get_type(block.id),
+            ExprKind::Paren(inner) => self.ctx.get_type(inner.id),
             ExprKind::If(cond, then, els) => {
                 let cond_ty = self.ctx.get_type(cond.id);
                 let then_ty = self.ctx.get_type(then.id);
                 if cond_ty.is_never() || cond_ty.kind == TyKind::Bool {
+                    // `if` without `else` is allowed in value position, but its type is unit
                     let els_ty = if let Some(els) = els {
                         self.ctx.get_type(els.id)
                     } else {
                         Rc::new(Ty::unit())
                     };
 
-                    if then_ty.is_never() || then_ty.kind == els_ty.kind {
+                    if els.is_none() && !then_ty.is_never() && then_ty.kind != TyKind::Unit {
+                        self.error(format!(
+                            "`if` without `else` must have unit type, but then block has `{:?}`",
+                            then_ty
+                        ));
+                        Rc::new(Ty::error())
+                    } else if then_ty.is_never() {
+                        els_ty
+                    } else if els_ty.is_never() || then_ty.kind == els_ty.kind {
                         then_ty
                     } else {
                         self.error(format!(
@@ -390,11 +629,24 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                     Rc::new(Ty::error())
                 }
             }
-            ExprKind::Struct(path, _fds) => {
+            ExprKind::Struct(path, _fds, base) => {
                 if let Some(binding) = self.ctx.resolve_path(path) {
                     if let Some(_adt) = self.ctx.lookup_adt_def(&binding.cpath) {
                         // TODO: typecheck fields
-                        Rc::new(Ty::new(TyKind::Adt(Rc::clone(&binding.cpath))))
+                        let struct_ty = Rc::new(Ty::new(TyKind::Adt(Rc::clone(&binding.cpath))));
+                        if let Some(base) = base
+                            && let base_ty = self.ctx.get_type(base.id)
+                            && !base_ty.is_never()
+                            && base_ty != struct_ty
+                        {
+                            self.error(format!(
+                                "Expected `{:?}` for base of struct expression, but found `{:?}`",
+                                struct_ty, base_ty
+                            ));
+                            Rc::new(Ty::error())
+                        } else {
+                            struct_ty
+                        }
                     } else {
                         self.error(format!("{:?} does not have struct type", binding.cpath));
                         Rc::new(Ty::error())
@@ -460,12 +712,215 @@ impl<'chk> ast::visitor::Visitor<'chk> for TypeChecker<'_, 'chk> {
                     }
                 }
             }
+            ExprKind::Loop(body, _label) => {
+                let loop_ctxt = self.loops.pop().unwrap();
+                let body_ty = self.ctx.get_type(body.id);
+                if !body_ty.is_never() && body_ty.kind != TyKind::Unit {
+                    self.error(format!(
+                        "Expected `()` for loop body, but found {:?}",
+                        body_ty
+                    ));
+                    Rc::new(Ty::error())
+                } else {
+                    // `loop` without `break` never returns
+                    loop_ctxt.break_ty.unwrap_or_else(|| Rc::new(Ty::never()))
+                }
+            }
+            ExprKind::While(cond, body, _label) => {
+                self.loops.pop().unwrap();
+                let cond_ty = self.ctx.get_type(cond.id);
+                let body_ty = self.ctx.get_type(body.id);
+                if !cond_ty.is_never() && cond_ty.kind != TyKind::Bool {
+                    self.error(format!(
+                        "Expected bool for conditional, but found {:?}",
+                        cond_ty
+                    ));
+                    Rc::new(Ty::error())
+                } else if !body_ty.is_never() && body_ty.kind != TyKind::Unit {
+                    self.error(format!(
+                        "Expected `()` for while body, but found {:?}",
+                        body_ty
+                    ));
+                    Rc::new(Ty::error())
+                } else {
+                    Rc::new(Ty::unit())
+                }
+            }
+            ExprKind::Break(label, value) => {
+                let value_ty = if let Some(value) = value {
+                    self.ctx.get_type(value.id)
+                } else {
+                    Rc::new(Ty::unit())
+                };
+                let Some(loop_ctxt) = self.find_loop_mut(label) else {
+                    self.error(loop_not_found_message("break", label));
+                    self.ctx.insert_type(expr.id, Rc::new(Ty::error()));
+                    return;
+                };
+
+                if loop_ctxt.is_while && value.is_some() {
+                    self.error("`break` with value from a `while` loop".to_string());
+                    Rc::new(Ty::error())
+                } else if value_ty.is_never() {
+                    Rc::new(Ty::never())
+                } else if let Some(break_ty) = &loop_ctxt.break_ty {
+                    if *break_ty == value_ty {
+                        Rc::new(Ty::never())
+                    } else {
+                        let msg = format!(
+                            "Expected {:?} type for `break`, but found {:?}",
+                            break_ty, value_ty
+                        );
+                        self.error(msg);
+                        Rc::new(Ty::error())
+                    }
+                } else {
+                    loop_ctxt.break_ty = Some(value_ty);
+                    Rc::new(Ty::never())
+                }
+            }
+            ExprKind::Continue(label) => {
+                if self.find_loop_mut(label).is_some() {
+                    Rc::new(Ty::never())
+                } else {
+                    self.error(loop_not_found_message("continue", label));
+                    Rc::new(Ty::error())
+                }
+            }
+            // TODO: slicing
+            ExprKind::Range(..) => {
+                self.error(format!(
+                    "Range expressions are not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::FloatLit(..) => {
+                self.error(format!(
+                    "Float literals are not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::CharLit(_) => Rc::new(Ty::new(TyKind::Char)),
+            // TODO: u8
+            ExprKind::ByteLit(_) | ExprKind::ByteStrLit(_) => {
+                self.error(format!(
+                    "Byte literals are not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::Match(..) => {
+                self.error("Match expressions are not supported yet".to_string());
+                Rc::new(Ty::error())
+            }
+            // TODO: `break` from labeled blocks
+            ExprKind::LabeledBlock(..) => {
+                self.error("Labeled blocks are not supported yet".to_string());
+                Rc::new(Ty::error())
+            }
+            // TODO: iterators
+            ExprKind::For(..) => {
+                self.loops.pop().unwrap();
+                self.error("For loops are not supported yet".to_string());
+                Rc::new(Ty::error())
+            }
+            ExprKind::Try(_) => {
+                self.error(format!(
+                    "The `?` operator is not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::Await(_) => {
+                self.error(format!(
+                    "`.await` is not supported yet: `{}`",
+                    expr.span.to_snippet()
+                ));
+                Rc::new(Ty::error())
+            }
+            ExprKind::MacroCall(name, _, _) => {
+                self.error(format!(
+                    "Macros are not supported yet: `{}!`",
+                    name.as_str()
+                ));
+                Rc::new(Ty::error())
+            }
         };
         self.ctx.insert_type(expr.id, ty);
     }
 
     fn visit_block_post(&mut self, block: &'chk ast::Block) {
+        // value of block-like expression statements in the middle of block is discarded
+        if let Some((_last, stmts)) = block.stmts.split_last() {
+            for stmt in stmts {
+                let StmtKind::Expr(expr) = &stmt.kind else {
+                    continue;
+                };
+                let ty = self.ctx.get_type(expr.id);
+                if !ty.is_never() && ty.kind != TyKind::Unit {
+                    self.error(format!(
+                        "Expected `()` for `{}`, but found {:?}. Consider adding `;`",
+                        expr.span.to_snippet(),
+                        ty
+                    ));
+                }
+            }
+        }
+
         let block_ty = self.get_block_type(block);
         self.ctx.insert_type(block.id, block_ty);
     }
 }
+
+/// Check if the path is the single segment `name` (e.g. `char`)
+fn is_primitive(path: &ast::Path, name: &str) -> bool {
+    matches!(&path.segments[..], [ident] if ident.symbol.as_str() == name)
+}
+
+/// Only paths (possibly parenthesized) can be called for now
+fn is_path(expr: &ast::Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Path(_) => true,
+        ExprKind::Paren(inner) => is_path(inner),
+        _ => false,
+    }
+}
+
+fn loop_not_found_message(keyword: &str, label: &Option<Ident>) -> String {
+    if let Some(label) = label {
+        format!("Use of undeclared label `'{}`", label.symbol)
+    } else {
+        format!("`{}` outside of a loop", keyword)
+    }
+}
+
+#[test]
+fn test_check_expr() {
+    use crate::parse::Parser;
+
+    let check_src = |src: &str| check(&Parser::from_source(src).parse_expr().unwrap());
+
+    let ty = check_src("{ let x = 1 + 2 * 3; if x < 10 && !false { x } else { -x } }").unwrap();
+    assert_eq!(ty.kind, TyKind::I32);
+    let ty = check_src("1 == 2 || true").unwrap();
+    assert_eq!(ty.kind, TyKind::Bool);
+
+    let cases = [
+        // mixed operands
+        ("1 + true", "Both lhs and rhs must be type of i32"),
+        // non-bool condition
+        ("if 1 {}", "Expected bool for conditional, but found I32"),
+        // mismatched arms
+        (
+            "if true { 1 } else { false }",
+            "Type mismatch then block has `I32`, but else block has `Bool`",
+        ),
+        ("x + 1", "Could not resolve ident"),
+    ];
+    for (src, msg) in cases {
+        let TypeError(errors) = check_src(src).unwrap_err();
+        assert!(errors[0].starts_with(msg), "{}: {:?}", src, errors);
+    }
+}
@@ -0,0 +1,189 @@
+#[cfg(feature = "no_std")]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Error in an escape sequence
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnescapeError {
+    pub msg: String,
+    /// Byte position of the `\` starting the bad escape
+    pub pos: usize,
+    /// Byte position just after the bad escape
+    pub end: usize,
+}
+
+/// Process escape sequences in the content of string and char literals.
+/// escape ::= "\\" ("n" | "r" | "t" | "0" | "\\" | "'" | "\"")
+///          | "\\x" hexDigit hexDigit
+///          | "\\u{" hexDigit+ "}"
+/// `\x` escapes must be at most `\x7F` and `\u` escapes take 1 to 6 digits.
+pub fn unescape(src: &str) -> Result<String, UnescapeError> {
+    let mut s = String::new();
+    unescape_with(src, false, |c| s.push(c))?;
+    Ok(s)
+}
+
+/// Process escape sequences in the content of byte and byte string literals (e.g. `b'a'`).
+/// Unlike `unescape`, only ASCII chars are allowed, `\x` escapes can be up to `\xFF`,
+/// and `\u` escapes cannot be used.
+pub fn unescape_bytes(src: &str) -> Result<Vec<u8>, UnescapeError> {
+    let mut bytes = vec![];
+    unescape_with(src, true, |c| bytes.push(c as u8))?;
+    Ok(bytes)
+}
+
+/// Pass unescaped chars to `push`. If `is_bytes` is set, they are less than 256.
+fn unescape_with(
+    src: &str,
+    is_bytes: bool,
+    mut push: impl FnMut(char),
+) -> Result<(), UnescapeError> {
+    let mut chars = src.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        let error = |msg: String, end: usize| UnescapeError { msg, pos, end };
+        if c != '\\' {
+            if is_bytes && !c.is_ascii() {
+                return Err(error(
+                    format!("Non-ASCII character in byte literal: `{}`", c),
+                    chars.offset(),
+                ));
+            }
+            push(c);
+            continue;
+        }
+        let escaped = match chars.next().map(|(_, c)| c) {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '\'' | '"')) => c,
+            Some('x') => {
+                let mut value = 0;
+                for _ in 0..2 {
+                    match chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                        Some(d) => value = value * 16 + d,
+                        None => {
+                            return Err(error(
+                                "Expected 2 hex digits in `\\x` escape".to_string(),
+                                chars.offset(),
+                            ))
+                        }
+                    }
+                }
+                if !is_bytes && value > 0x7f {
+                    return Err(error(
+                        format!(
+                            "`\\x{:02X}` is out of range (must be at most `\\x7F`)",
+                            value
+                        ),
+                        chars.offset(),
+                    ));
+                }
+                char::from_u32(value).unwrap()
+            }
+            Some('u') if is_bytes => {
+                return Err(error(
+                    "Unicode escapes cannot be used in byte literals".to_string(),
+                    chars.offset(),
+                ))
+            }
+            Some('u') => {
+                if chars.next().map(|(_, c)| c) != Some('{') {
+                    return Err(error(
+                        "Expected `{` in `\\u` escape".to_string(),
+                        chars.offset(),
+                    ));
+                }
+                let mut value: u32 = 0;
+                let mut n_digits = 0;
+                loop {
+                    match chars.next().map(|(_, c)| c) {
+                        Some('}') => break,
+                        Some(c) if c.is_ascii_hexdigit() && n_digits < 6 => {
+                            value = value * 16 + c.to_digit(16).unwrap();
+                            n_digits += 1;
+                        }
+                        _ => {
+                            return Err(error(
+                                "Expected 1 to 6 hex digits and `}` in `\\u` escape".to_string(),
+                                chars.offset(),
+                            ))
+                        }
+                    }
+                }
+                if n_digits == 0 {
+                    return Err(error("Empty `\\u` escape".to_string(), chars.offset()));
+                }
+                match char::from_u32(value) {
+                    Some(c) => c,
+                    None => {
+                        return Err(error(
+                            format!("`\\u{{{:X}}}` is not a valid unicode scalar value", value),
+                            chars.offset(),
+                        ))
+                    }
+                }
+            }
+            Some(c) => {
+                return Err(error(
+                    format!("Unknown character escape: `\\{}`", c),
+                    chars.offset(),
+                ))
+            }
+            None => return Err(error("Unterminated escape".to_string(), chars.offset())),
+        };
+        push(escaped);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("a\\nb").unwrap(), "a\nb");
+    assert_eq!(unescape("\\t\\r\\0\\\\\\'\\\"").unwrap(), "\t\r\0\\'\"");
+    assert_eq!(unescape("no escape").unwrap(), "no escape");
+}
+
+#[test]
+fn test_unescape_hex() {
+    assert_eq!(unescape("\\x41\\x7f").unwrap(), "A\x7f");
+    assert_eq!(unescape("\\x4").unwrap_err().pos, 0);
+    assert_eq!(unescape("ab\\x80").unwrap_err().pos, 2);
+}
+
+#[test]
+fn test_unescape_unicode() {
+    assert_eq!(unescape("\\u{1F600}").unwrap(), "😀");
+    assert_eq!(unescape("\\u{41}\\u{3042}").unwrap(), "Aあ");
+    assert!(unescape("\\u{}").is_err());
+    assert!(unescape("\\u{1234567}").is_err());
+    // surrogate
+    assert!(unescape("\\u{D800}").is_err());
+    assert!(unescape("\\u41").is_err());
+}
+
+#[test]
+fn test_unescape_error() {
+    // position is in bytes
+    let err = unescape("aあ\\q").unwrap_err();
+    assert_eq!(err.pos, 4);
+    assert_eq!(err.end, 6);
+    assert_eq!(err.msg, "Unknown character escape: `\\q`");
+    assert_eq!(unescape("a\\").unwrap_err().pos, 1);
+}
+
+#[test]
+fn test_unescape_bytes() {
+    assert_eq!(
+        unescape_bytes("A\\n\\xff\\x00").unwrap(),
+        vec![b'A', b'\n', 0xff, 0]
+    );
+    let err = unescape_bytes("a\u{e9}").unwrap_err();
+    assert_eq!(err.msg, "Non-ASCII character in byte literal: `\u{e9}`");
+    assert_eq!(err.pos, 1);
+    assert!(unescape_bytes("\\u{41}").is_err());
+}
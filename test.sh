@@ -11,6 +11,9 @@ success_or_stop() {
 }
 
 cargo build
+# the lexer and spans can be built without std
+cargo build --lib --features no_std
+success_or_stop "$?"
 ./tests/compile.sh
 success_or_stop "$?"
 
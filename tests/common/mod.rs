@@ -0,0 +1,23 @@
+use mini_rustc::ast::pretty::print_crate;
+use mini_rustc::ast::structural::Structural;
+use mini_rustc::parse::Parser;
+
+/// Parse `src`, print it, parse the output again, and check that the two trees are
+/// structurally equal (i.e. except for spans and node ids).
+/// The output must also be a fixed point of printing.
+pub fn assert_reparse(src: &str) {
+    let mut parser = Parser::from_source(src);
+    let krate = parser.parse_crate();
+    assert!(!parser.has_errors(), "{:?}", parser.errors());
+    let printed = print_crate(&krate);
+
+    let mut parser = Parser::from_source(&printed);
+    let reparsed = parser.parse_crate();
+    assert!(!parser.has_errors(), "{}\n{:?}", printed, parser.errors());
+    assert!(
+        Structural(&krate.items) == Structural(&reparsed.items),
+        "{}",
+        printed
+    );
+    assert_eq!(print_crate(&reparsed), printed);
+}
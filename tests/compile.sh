@@ -32,6 +32,8 @@ compile 'fn main() -> () { }'
 compile "fn main() -> () { // comment${NL}}"
 compile "//${NL}fn main() -> () { }"
 compile "fn main() -> () { //${NL}}"
+# shebang
+compile "#!/usr/bin/env mini-rustc${NL}fn main() -> () { }"
 # ref type
 compile 'fn main() -> i32 { let string: &'$QT'static str; 0  }'
 # string literal
@@ -105,6 +107,9 @@ compile 'mod a { fn f() -> () { } } mod b { fn f() -> () { } } fn main() -> () {
 compile 'fn f() -> () { } fn main() -> () { crate::f() }'
 compile 'mod a { fn f() -> () { } } fn main() -> () { a::f() }'
 compile 'mod a { fn f() -> () { } } mod b { fn f() -> () { } } fn main() -> () { b::f() }'
+# use
+compile 'use std::mem; fn main() -> () { }'
+compile 'mod a { fn f() -> () { } } use a::*; fn main() -> () { }'
 # TODO: typecheck all items first and then typecheck bodies
 #compile 'fn main() -> () { f(); } fn f() -> () { }'
 # scope
@@ -112,3 +117,10 @@ compile 'fn main() -> () { let a: i32 = 0; let b: i32 = { { a } }; }'
 # shadowing
 compile 'fn main() -> () { let a: i32 = 0; let a: i32 = 1; }'
 compile 'struct S { a: i32 } fn main() -> () { let a: i32 = 4; let a: S; }'
+# expression statement
+compile 'fn main() -> () { 1; }'
+compile 'fn main() -> () { if true { } { } loop { break; } }'
+# parenthesized type
+compile 'fn main() -> i32 { let a: (i32) = 3; a }'
+# omitted return type
+compile 'fn main() { }'
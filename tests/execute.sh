@@ -38,24 +38,47 @@ echo "===== Execute Tests ====="
 # return
 assert 0 'fn main() -> i32 { return 0; }'
 assert 0 'fn main() -> i32 { { return 0; } }'
+assert 3 'fn main() -> i32 { return (1 + 2) }'
+assert 1 'fn main() -> i32 { return { 1 } }'
+# return without a value
+assert 3 'fn f() { if true { return } } fn main() -> i32 { f(); 3 }'
+assert 4 'fn f() -> () { return; } fn main() -> i32 { f(); 4 }'
 # func body
 assert 0 'fn main() -> i32 { 0 }'
 assert 0 'fn main() -> i32 { { { 0 } } }'
 assert 10 'fn main() -> i32 { { { 10 } } }'
+assert 1 'fn f() -> i32 { 1 } fn main() -> i32 { f() }'
+# a diverging statement does not need a tail
+assert 2 'fn f() -> i32 { return 2; } fn main() -> i32 { f() }'
 # unary
 # Linux only?
 assert 255 'fn main() -> i32 { -1 }'
 assert 254 'fn main() -> i32 { -2 }'
+assert 2 'fn main() -> i32 { - -2 }'
+assert 253 'fn main() -> i32 { -+-+-3 }'
+assert 3 'fn f() -> i32 { -3 } fn main() -> i32 { -f() }'
 # numerical literals
 assert 200 'fn main() -> i32 { 100; 200 }'
 assert 3 'fn main() -> i32 { 0; 1; 2; 3 }'
+assert 5 'fn main() -> i32 { 2i32 + 3 }'
 # boolean literals
 assert 0 'fn main() -> i32 { true; 0 }'
+# equality on bools and chars
+assert 2 'fn main() -> i32 { if true == false { 1 } else { 2 } }'
+assert 1 'fn main() -> i32 { if true != false { 1 } else { 0 } }'
+assert 1 "fn main() -> i32 { if 'a' == 'a' { 1 } else { 0 } }"
+assert 2 "fn main() -> i32 { if 'a' == 'b' { 1 } else { 2 } }"
+assert 2 'fn main() -> i32 { let b: bool = true; if b == false { 1 } else { 2 } }'
+assert 1 'fn main() -> i32 { let a: bool = true; let b: bool = false; if a != b { 1 } else { 2 } }'
+# char type
+assert 21 "fn f(c: char) -> i32 { if c == 'a' { 1 } else { 2 } } fn main() -> i32 { let c: char = 'a'; f(c) + f('b') * 10 }"
 # binop
 assert 9 'fn main() -> i32 { 4 + 5 }'
 assert 3 'fn main() -> i32 { 10 - 7 }'
 assert 6 'fn main() -> i32 { 2 * 3 }'
 assert 9 'fn main() -> i32 { 11 + 8 * 2 - 3 * (1 + 5) }'
+assert 1 'fn main() -> i32 { 10 - 7 - 2 }'
+assert 3 'fn main() -> i32 { 10 - 4 - 2 - 1 }'
 # let
 assert 0 'fn main() -> i32 { let a: i32; let b: i32; 0 }'
 assert 0 'fn main() -> i32 { let a: i32 = 0; let b: i32; a }'
@@ -65,14 +88,24 @@ assert 0 'fn main() -> i32 { let a: i32; a = 1; 0 }'
 # load
 assert 1 'fn main() -> i32 { let a: i32; a = 1; a }'
 # func call
+assert 3 'const fn three() -> i32 { 3 } fn main() -> i32 { three() }'
 assert 0 'fn zero() -> i32 { 0 } fn main() -> i32 { zero() }'
 assert 0 'fn id(n: i32) -> i32 { n } fn main() -> i32 { id(0) }'
 assert 1 'fn id(n: i32) -> i32 { n } fn main() -> i32 { id(1) }'
+# assignment as a call argument
+assert 5 'fn f(u: ()) -> i32 { 1 } fn main() -> i32 { let x: i32 = 0; f(x = 5); x }'
+assert 12 'fn f(u: (), n: i32) -> i32 { n } fn main() -> i32 { let x: i32 = 0; f(x = 2, 10) + x }'
 # array
 assert 0 'fn main() -> i32 { let arr: [i32; 10]; 0 }'
 assert 0 'fn main() -> i32 { let arr: [[i32; 4]; 8]; 0 }'
 assert 5 'fn main() -> i32 { let arr: [i32; 8]; arr[1] = 5; arr[1] }'
 assert 10 'fn main() -> i32 { let arr: [[i32; 4]; 8]; arr[7][3] = 10; arr[7][3] }'
+# compound assignment
+assert 7 'fn main() -> i32 { let x: i32 = 2; x += 3; x *= 2; x -= 3; x }'
+assert 6 'fn main() -> i32 { let arr: [i32; 2]; arr[1] = 1; arr[1] += 5; arr[1] }'
+# the place of a compound assignment is evaluated once
+assert 101 'fn main() -> i32 { let arr: [i32; 3]; arr[0] = 0; arr[1] = 0; arr[2] = 0; let i: i32 = 0; arr[{ i = i + 1; i }] += 10; arr[1] * 10 + i }'
+assert 8 'struct P { x: i32 } fn main() -> i32 { let p: P = P { x: 1 }; p.x *= 4; p.x += p.x; p.x }'
 # unit
 assert 0 'fn main() -> i32 { (); 0 }'
 assert 100 'fn main() -> i32 { (); (); 100 }'
@@ -113,6 +146,8 @@ assert 2 'fn main() -> i32 { let a: i32 = 0; { let a: i32 = 1; let a: i32 = 2; a
 assert 4 'fn main() -> i32 { let a: i32 = 1; let a: i32 = a + a; a + a }'
 assert 0 'fn f(a: i32) -> i32 { let a: i32 = a; let a: i32 = a; a } fn main() -> i32 { f(0)  }'
 assert 10 'struct S { a: i32 } fn main() -> i32 { let a: i32 = 4; let a: S = S { a: 10 }; a.a }'
+# self-assignment is a warning
+assert 3 'fn main() -> i32 { let x: i32 = 3; x = x; x }'
 # if
 assert 1 'fn main() -> i32 { if true { 1 } else { 0 }  }'
 assert 0 'fn main() -> i32 { if false { 1 } else { 0 }  }'
@@ -122,9 +157,86 @@ assert 1 'struct S { a: i32 }
 fn main() -> i32 { let s: S; s = if false { S { a: 0 } } else { S { a: 1 } }; s.a }'
 assert 30 'fn main() -> i32 { let a: i32 = 3; if a == 1 { 10 } else if a == 2 { 20 } else { 30 } }'
 assert 20 'fn main() -> i32 { let a: i32 = 2; if a == 1 { 10 } else if a == 2 { 20 } else { 30 } }'
+assert 10 'fn main() -> i32 { let a: i32 = 2; let b: i32 = 2; if a == b { 10 } else { 20 } }'
+# if without else has unit value
+assert 3 'fn main() -> i32 { let mut a: i32 = 0; if true { a = 3; } a }'
+assert 0 'fn main() -> i32 { let mut a: i32 = 0; if false { a = 3; } a }'
+assert 4 'fn main() -> i32 { let u: () = if false { }; 4 }'
+assert 1 'struct P { x: i32 } fn main() -> i32 { if (P { x: 1 }).x == 1 { 1 } else { 0 } }'
 # return struct
 assert 0 'struct S { a: i32 } fn f() -> S { S { a: 0 } } fn main() -> i32 { f().a }'
 assert 42 'struct S { a: i32 } fn f() -> S { S { a: 42 } } fn main() -> i32 { f().a }'
 assert 100 'struct S { a: i32 } fn f(s: S) -> S { s } fn main() -> i32 { f(S { a: 100 }).a }'
 assert 5 'struct S { a: i32, b: i32 } fn f() -> S { S { a: 3, b: 5 } } fn main() -> i32 { f().b }'
 assert 7 'struct S { a: i32 } fn f(b: i32, c: i32) -> S { S { a: b + c } } fn main() -> i32 { f(3, 4).a }'
+# escapes in string and char literals
+assert 3 'extern "C" { fn strlen(s: &str) -> i32; } fn main() -> i32 { strlen("a\nb") }'
+assert 6 'extern "C" { fn strlen(s: &str) -> i32; } fn main() -> i32 { strlen("\x41\"\u{3042}\\") }'
+assert 1 "fn main() -> i32 { if '\\x41' == 'A' { 1 } else { 0 } }"
+assert 4 'extern "C" { fn strlen(s: &str) -> i32; } fn main() -> i32 { strlen("\u{1F600}") }'
+assert 1 "fn main() -> i32 { if '\\u{41}' == 'A' { 1 } else { 0 } }"
+# raw string literal
+assert 4 'extern "C" { fn strlen(s: &str) -> i32; } fn main() -> i32 { strlen(r"a\nb") }'
+assert 8 'extern "C" { fn strlen(s: &str) -> i32; } fn main() -> i32 { strlen(r#"say "hi""#) }'
+# loop
+assert 8 'fn main() -> i32 { let mut i: i32 = 0; let a: i32 = { loop { i = i + 1; if i == 4 { break i * 2 } } }; a }'
+assert 5 'fn main() -> i32 { loop { break 5 } }'
+assert 6 'fn main() -> i32 { let u: () = loop { break; }; 6 }'
+assert 2 'fn main() -> i32 { '$QT'a: loop { loop { break '$QT'a 2; } } }'
+assert 7 'fn main() -> i32 { let x: i32 = loop { break 7; }; x }'
+assert 10 'fn main() -> i32 { let i: i32 = 0; loop { i = i + 1; if i == 10 { break; } } i }'
+assert 9 'struct S { a: i32 } fn main() -> i32 { let s: S; s = loop { break S { a: 9 }; }; s.a }'
+assert 30 'fn main() -> i32 { let n: i32 = 0; let r: i32 = '$QT'outer: loop { loop { n = n + 1; if n == 3 { break '$QT'outer n * 10; } } }; r }'
+assert 4 'fn main() -> i32 { let i: i32 = 0; '$QT'a: while true { loop { i = i + 1; if i == 4 { break '$QT'a; } else { continue '$QT'a; } } } i }'
+# while
+assert 3 'fn main() -> i32 { let x: i32 = { let mut i: i32 = 0; while i < 3 { i = i + 1 }; i }; x }'
+assert 3 'fn main() -> i32 { let mut i: i32 = 0; while i < 3 { i = i + 1 } i }'
+assert 0 'fn main() -> i32 { let u: () = { while false {} }; 0 }'
+assert 3 'fn main() -> i32 { let i: i32 = 0; let n: i32 = 3; while i != n { i = i + 1; } i }'
+assert 12 'fn main() -> i32 { let i: i32 = 0; let s: i32 = 0; while i != 5 { i = i + 1; if i == 3 { continue; } s = s + i; } s }'
+# return in the middle of function
+assert 21 'fn f(x: i32) -> i32 { if x == 0 { return 1; } 2 } fn main() -> i32 { f(0) + f(1) * 10 }'
+assert 6 'fn main() -> i32 { let a: i32 = if true { 1 } else { return 4; }; let b: i32 = if false { return 2 } else { 5 }; a + b }'
+# i32::MAX and i32::MIN
+assert 47 'fn main() -> i32 { 2147483647 - 2147483600 }'
+assert 0 'fn main() -> i32 { let a: i32 = -2147483648; if a + 2147483647 == -1 { 0 } else { 1 } }'
+# omitted return type
+assert 3 'fn f(a: i32) { a; } fn main() -> i32 { f(1); 3 }'
+# empty statement
+assert 2 'fn main() -> i32 { ; 1;; 2 }'
+# let without type annotation
+assert 2 'fn main() -> i32 { let a = if false { 1 } else { 2 }; a }'
+assert 7 'fn main() -> i32 { let a: i32 = 3; let b = a + 4; let c = b; c }'
+# integer vs bool
+assert 3 'fn main() -> i32 { if 1 + 1 == 2 { 3 } else { 4 } }'
+# struct field shorthand
+assert 7 'struct P { x: i32, y: i32 } fn main() -> i32 { let x: i32 = 3; let y: i32 = 4; let p: P = P { y, x }; p.x + p.y }'
+# functional update syntax
+assert 9 'struct P { x: i32, y: i32, z: i32 } fn main() -> i32 { let p: P = P { x: 1, y: 2, z: 3 }; let q: P = P { y: 5, ..p }; q.x + q.y + q.z }'
+# the base is evaluated after the fields
+assert 7 'struct P { x: i32, y: i32 } fn main() -> i32 { let mut p: P = P { x: 1, y: 2 }; let q: P = P { x: { p.y = 7; 1 }, ..p }; q.y }'
+assert 6 'struct P { x: i32, y: i32 } fn main() -> i32 { let q: P = P { ..P { x: 2, y: 4 } }; q.x + q.y }'
+# block comments
+assert 3 'fn main() -> i32 { 1 /* one /* nested */ */ + 2 }'
+# logical/bitwise not
+assert 250 'fn main() -> i32 { !5 }'
+assert 1 'fn main() -> i32 { if !(1 == 2) { 1 } else { 0 } }'
+assert 3 'fn f() -> i32 { 2 } fn main() -> i32 { -!f() }'
+# bool variables are stored as i8
+assert 2 'fn main() -> i32 { let b: bool = true; let c: bool = !b; if c { 1 } else { 2 } }'
+assert 3 'fn main() -> i32 { let b: bool = false; if b && true { 1 } else if !b { 3 } else { 2 } }'
+assert 5 'fn main() -> i32 { let go: bool = true; let i: i32 = 0; while go { i = i + 1; go = !(i == 5); } i }'
+# lazy boolean and
+assert 1 'fn main() -> i32 { if 1 == 1 && 2 == 2 { 1 } else { 0 } }'
+assert 0 'fn main() -> i32 { if 1 == 1 && 2 == 3 { 1 } else { 0 } }'
+assert 0 'fn main() -> i32 { if 1 == 2 && 2 == 2 { 1 } else { 0 } }'
+assert 3 'fn main() -> i32 { if 1 == 2 && { return 7; } { 1 } else { 3 } }'
+assert 7 'fn main() -> i32 { if 1 == 1 && { return 7; } { 1 } else { 3 } }'
+# raw identifiers
+assert 3 'fn r#match() -> i32 { 3 } fn main() -> i32 { let r#if: i32 = r#match(); r#if }'
+# relational operators
+assert 1 'fn main() -> i32 { if 1 < 2 { 1 } else { 0 } }'
+assert 0 'fn main() -> i32 { if 1 > 2 { 1 } else { 0 } }'
+assert 4 'fn main() -> i32 { let i: i32 = 0; while i < 4 { i = i + 1; } i }'
+# mutable bindings
+assert 3 'fn main() -> i32 { let mut x: i32 = 1; x = x + 2; x }'
@@ -22,6 +22,20 @@ compile_fail() {
   fi
 }
 
+# check the diagnostics printed to stderr as well
+compile_fail_with() {
+  input="$1"
+  expected="$2"
+  output=$($RUSTC "$input" 2>&1 >/dev/null)
+  code="$?"
+  if [ "$code" = 1 ] && [ "$output" = "$expected" ]; then
+    echo -e "[${GREEN}OK${NC}] $input"
+  else
+    echo -e "[${RED}ERROR${NC}] $input ${GRAY}=> Unexpectedly exit with code $code and output:\n$output${NC}"
+    exit 1
+  fi
+}
+
 QT="'"
 
 echo "===== Failure Tests ====="
@@ -35,8 +49,16 @@ compile_fail 'fn main() -> i32 { let b: bool; b = 100; }'
 compile_fail 'fn main() -> i32 { let u: (); u = (return 0); }'
 # ill-typed arithmetic
 compile_fail 'fn main() -> i32 { return (1+true)*2; }'
+# equality on different types
+compile_fail 'fn main() -> () { let b: bool = 1 == true; }'
+compile_fail "fn main() -> () { let b: bool = 'a' == 1; }"
+compile_fail "fn main() -> () { let b: bool = 'a' != true; }"
+# comparison operators cannot be chained
+compile_fail 'fn main() -> () { let b: bool = 1 == 1 == true; }'
+compile_fail 'fn main() -> () { let b: bool = 1 < 2 == true; }'
 # unexpected type of return value
 compile_fail 'fn main() -> i32 { return true; }'
+compile_fail 'fn main() -> i32 { return }'
 # unexpected type of block expression
 compile_fail 'fn main() -> i32 { let a: i32; a = { 1; true }; }'
 # mismatch number of arguments
@@ -52,6 +74,9 @@ compile_fail 'fn main() -> () { let a: [i32; 1] = [true]; }'
 compile_fail 'fn main() -> () { let a: [i32; 1]; a[0] = true; }'
 # if
 compile_fail 'fn main() -> () { if (true) { } else { 1 } }'
+# if without else must have unit type
+compile_fail 'fn main() -> i32 { let c: bool = true; let x: i32 = if c { 1 }; x }'
+compile_fail 'fn f(c: bool) -> i32 { if c { 1 } } fn main() -> i32 { f(true) }'
 # name space
 compile_fail 'mod a { fn f() -> () { } } fn main() -> () { f() }'
 # name space
@@ -69,3 +94,128 @@ compile_fail 'fn main() -> () { { { let a: (); } }  { a } }'
 compile_fail 'fn main() -> () { a; let a: (); }'
 compile_fail 'fn main() -> () { let a: i32 = { { { let b: i32 = 0 } } b }; }'
 compile_fail 'fn main() -> () { let a: i32 = 0; let b: i32 = { { a + b } }; }'
+# use
+compile_fail 'use a::b fn main() -> () { }'
+compile_fail 'use a::*::b; fn main() -> () { }'
+# loop
+compile_fail_with 'fn main() -> () { break; }' $'1:19: `break` outside of a loop\nFailed to check control flow'
+compile_fail_with 'fn main() -> () { continue; }' $'1:19: `continue` outside of a loop\nFailed to check control flow'
+compile_fail 'fn main() -> () { loop { continue '$QT'a; } }'
+compile_fail 'fn main() -> () { while true { break 1; } }'
+compile_fail 'fn main() -> i32 { loop { if true { break 1; } break true; } }'
+compile_fail 'fn main() -> i32 { loop { break 1; break true } }'
+compile_fail 'fn main() -> i32 { loop { break 1; break; } }'
+compile_fail 'fn main() -> () { let b: bool = loop { break 1 }; }'
+compile_fail 'fn main() -> i32 { '$QT'a: loop { loop { break '$QT'a true; } break 1; } }'
+compile_fail 'fn main() -> () { loop { 1 } }'
+compile_fail 'fn main() -> () { while 1 { } }'
+compile_fail 'fn main() -> i32 { let x: i32 = { while true {} }; x }'
+compile_fail 'fn main() -> i32 { let i: i32 = 0; while i < 3 { i = i + 1 } }'
+# integer literal out of range
+compile_fail 'fn main() -> i32 { 99999999999999999999 }'
+compile_fail 'fn main() -> i32 { 340282366920938463463374607431768211456 }'
+compile_fail 'fn main() -> i32 { 2147483648 }'
+compile_fail 'fn main() -> i32 { -2147483649 }'
+# integer literal suffix
+compile_fail 'fn main() -> i32 { 255u8 }'
+compile_fail 'fn main() -> i32 { 1z32 }'
+# float literals
+compile_fail 'fn main() -> () { let x = 1.0f32; }'
+compile_fail 'fn main() -> () { let x = 2f64; }'
+compile_fail 'fn main() -> () { let x = 1.0f16; }'
+# error recovery
+compile_fail 'fn main() -> () { let a: i32 = ; let b: i32 = 1 +; { 1 + } 3; }'
+compile_fail 'fn main() -> () { ( ; 1 }'
+# expression statement
+compile_fail 'fn main() -> i32 { 1; }'
+compile_fail 'fn f() -> i32 { 1; } fn main() -> i32 { f() }'
+compile_fail 'fn f() -> i32 { { 1 }; } fn main() -> i32 { f() }'
+compile_fail 'fn main() -> i32 { 1 2 }'
+compile_fail 'fn main() -> i32 { if true { 1 } else { 2 } 3 }'
+# tuple type
+compile_fail 'fn main() -> () { let t: (i32, bool); }'
+# function pointer type
+compile_fail 'fn g(x: i32) -> i32 { x + 1 } fn main() -> i32 { let f: fn(i32) -> i32 = g; f(1) }'
+# omitted return type means unit
+compile_fail 'fn f() { 1 } fn main() -> () { }'
+# generic function
+compile_fail 'fn id<T>(x: T) -> T { x } fn main() -> () { }'
+# turbofish
+compile_fail 'fn f() -> i32 { 0 } fn main() -> i32 { f::<i32>() }'
+# stray else
+compile_fail 'fn main() -> () { else { } }'
+compile_fail 'fn main() -> () { if true { }; else { } }'
+# let without type annotation
+compile_fail 'fn main() -> () { let a; }'
+compile_fail 'fn main() -> i32 { let a = if true { 1 }; 0 }'
+# integer vs bool
+compile_fail 'fn main() -> () { if 1 { } }'
+compile_fail 'fn main() -> () { while 0 { } }'
+compile_fail 'fn main() -> i32 { true + 1 }'
+compile_fail 'fn main() -> i32 { -true }'
+compile_fail 'fn main() -> i32 { if true { 1 } else { false } }'
+# static item
+compile_fail 'static N: i32 = 1; fn main() -> () { }'
+compile_fail 'static mut N: i32 = 1; fn main() -> () { }'
+compile_fail 'static N: i32 = 1 fn main() -> () { }'
+# assignment as a call argument has type ()
+compile_fail 'fn f(n: i32) -> i32 { n } fn main() -> i32 { let x: i32 = 0; f(x = 1) }'
+# functional update syntax
+compile_fail 'struct P { x: i32 } struct Q { x: i32 } fn main() -> () { let q: Q = Q { x: 1 }; let p: P = P { ..q }; }'
+# range
+compile_fail 'fn main() -> i32 { let a: [i32; 3] = [1, 2, 3]; a[0..2]; 0 }'
+# macros
+compile_fail 'fn main() -> i32 { println!("{}", 1); 0 }'
+# references and `?`
+compile_fail 'fn main() -> i32 { let a: i32 = 1; *&a }'
+compile_fail 'fn f() -> i32 { 1 } fn main() -> i32 { f()? }'
+compile_fail 'fn main() -> i32 { !"a"; 0 }'
+# await
+compile_fail 'fn f() -> i32 { 1 } fn main() -> i32 { f().await }'
+compile_fail 'fn main() -> i32 { let await: i32 = 1; 0 }'
+# match expressions
+compile_fail 'fn main() -> i32 { match 1 { 0..=9 => 1, _ => 0 } }'
+compile_fail "fn main() -> i32 { let c: i32 = 'a'; 0 }"
+# for loops
+compile_fail 'fn main() -> i32 { for i in 0..3 { i; } 0 }'
+# labeled blocks
+compile_fail "fn main() -> i32 { 'a: { break 'a 1 } }"
+# invalid assignment targets
+compile_fail 'fn main() -> i32 { 5 = 1; 0 }'
+compile_fail 'fn main() -> i32 { 5 += 1; 0 }'
+compile_fail 'fn main() -> i32 { let b: bool = true; b += 1; 0 }'
+compile_fail 'fn main() -> i32 { let a: i32 = 1; (a + 1) = 1; 0 }'
+# destructuring let
+compile_fail 'fn main() -> i32 { let (a, (b, c)): (i32, (i32, i32)); a }'
+compile_fail 'fn main() -> i32 { let [x, y]: [i32; 2]; x }'
+compile_fail 'fn main() -> i32 { let arr: [i32; 2]; let [head, ..] = arr; head }'
+# rest patterns
+compile_fail_with 'fn main() -> i32 { let t: (i32, i32, i32); match t { (a, .., b, ..) => a } }' $'1:65: `..` can only be used once per tuple or slice pattern\nFailed to parse source code'
+compile_fail_with 'fn main() -> i32 { let arr: [i32; 3]; let [a, .., b, ..] = arr; a }' $'1:54: `..` can only be used once per tuple or slice pattern\nFailed to parse source code'
+# impl Trait and dyn Trait types
+compile_fail 'fn f() -> impl Iterator { 0 } fn main() -> i32 { 0 }'
+compile_fail 'fn f(e: &dyn Error) -> () { } fn main() -> i32 { 0 }'
+# generic type arguments
+compile_fail 'struct P { x: i32 } fn main() -> i32 { let p: P<i32>; 0 }'
+# struct literals in conditions need parentheses
+compile_fail 'struct P { x: i32 } fn main() -> i32 { if P { x: 1 }.x == 1 { 1 } else { 0 } }'
+compile_fail 'struct P { x: i32 } fn main() -> i32 { while P { x: 1 }.x == 1 { } 0 }'
+# items in blocks
+compile_fail 'fn main() -> i32 { fn f() -> i32 { 1 } 0 }'
+# bad escapes
+compile_fail 'fn main() -> () { "\q"; }'
+compile_fail 'fn main() -> () { "\x80"; }'
+compile_fail "fn main() -> () { '\\u{D800}'; }"
+compile_fail 'fn main() -> () { "\u{110000}"; }'
+compile_fail "fn main() -> () { '\\xFF'; }"
+# calling non-path expressions
+compile_fail 'fn f() -> i32 { 1 } fn main() -> i32 { f()() }'
+compile_fail 'fn f() -> i32 { 1 } fn main() -> i32 { [f][0]() }'
+# missing `)` is reported, and parsing continues
+compile_fail 'fn main() -> i32 { let a: i32 = (1 + 2; a }'
+# byte literals
+compile_fail "fn main() -> () { b'\\u{41}'; }"
+compile_fail "fn main() -> () { b'ab'; }"
+compile_fail 'fn main() -> () { b"\u{41}"; }'
+# TODO: u8
+compile_fail "fn main() -> () { b'A'; }"
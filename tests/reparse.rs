@@ -0,0 +1,79 @@
+mod common;
+
+use common::assert_reparse;
+
+#[test]
+fn test_reparse() {
+    // items
+    assert_reparse(
+        "use std::io::*; struct P { x: i32, y: [bool; 2] } static mut N: i32 = 1;
+        mod m { fn f<T>(a: &'a i32, b: *const P) -> fn(i32) -> (i32, bool) { g } const fn c() {} }
+        extern \"C\" { fn puts(s: &str) -> i32; }",
+    );
+    // operators and precedence
+    assert_reparse(
+        "fn main() -> i32 {
+            let a = (1 + 2) * 3 - -(4 - 5) * !6;
+            let b = a - (a - 1) + 2 * (3 * 4);
+            let c = (a == b) == true && (1 < 2 && 3 > 4);
+            x = y = (a as i32 + 1) as i32;
+            a[i] += 1; x -= y *= 2; (a.b) *= -c;
+            & &a; *&a; f()? + a[0].b.c(1, 2)[3];
+            fut.await?.x; (P { x: 1 }).await;
+            (return 1) + 2;
+            0
+        }",
+    );
+    // control flow
+    assert_reparse(
+        "fn main() -> () {
+            'outer: loop {
+                while c == 1 { break 'outer; }
+                for i in 0..n { continue; }
+                if a < 2 { 1 } else if b > 3 { 2 } else { 3 };
+            }
+            let v = 'a: { break 'a 1 };
+            let r = ..1;
+        }",
+    );
+    // patterns and match
+    assert_reparse(
+        "fn main() -> i32 {
+            let (a, (b, _)) = t;
+            let [x, y,]: [i32; 2] = arr;
+            struct Inner { a: i32 }
+            let [first, .., (..), (.., last)] = arr;
+            match (P { x: 1 }).x {
+                0 => 1,
+                1..=9 => 2,
+                'a'..='z' => 3,
+                ..0 => 4,
+                -3..=-1 => 5,
+                10.. => 6,
+                n if n > 10 => { n },
+                (p,) => p,
+                _ => 0,
+            }
+        }",
+    );
+    // literals, struct literals, macros and raw identifiers
+    assert_reparse(
+        "fn r#return() -> i32 {
+            let s = \"a 'b'\";
+            let c = '\\'';
+            let b = [b'a', b'\\'', b'\\xff'];
+            let bs = b\"\\\"a\\n\\x80\";
+            let f = 1.5 + 2f64;
+            let p = P { x: 1i32, y: Q { z: true }, ..base };
+            let q = P {};
+            println!(\"{}\", s);
+            vec![1, 2, 3];
+            f::<Vec<Vec<i32>>, impl Iterator, &dyn Error>(());
+            r#match
+        }",
+    );
+    // expressions over multiple lines
+    assert_reparse("fn main() -> i32 {\n    let a = 1 +\n        2;\n    a\n        * 3\n}");
+    // ambiguity with struct literals before blocks
+    assert_reparse("fn main() -> () { for x in (S { a: 1 }).a {} if (a == b) {} while (x) {} }");
+}